@@ -0,0 +1,37 @@
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+const SYNTAX_DIR: &str = "syntax";
+
+/// Embeds every `syntax/*.toml` so a new built-in language needs no code, only its definition file
+fn main() {
+    println!("cargo::rerun-if-changed={SYNTAX_DIR}");
+
+    let manifest_dir = env::var("CARGO_MANIFEST_DIR").expect("cargo sets CARGO_MANIFEST_DIR");
+    let out_dir = env::var("OUT_DIR").expect("cargo sets OUT_DIR");
+
+    let mut files: Vec<PathBuf> = fs::read_dir(Path::new(&manifest_dir).join(SYNTAX_DIR))
+        .expect("syntax directory is readable")
+        .filter_map(|entry| Some(entry.ok()?.path()))
+        .filter(|path| {
+            path.extension()
+                .is_some_and(|extension| extension == "toml")
+        })
+        .collect();
+    files.sort();
+
+    let entries: String = files
+        .iter()
+        .map(|path| {
+            let file_name = path.file_name().unwrap_or_default().to_string_lossy();
+            format!("    ({file_name:?}, include_str!({path:?})),\n")
+        })
+        .collect();
+
+    fs::write(
+        Path::new(&out_dir).join("built_in_syntax.rs"),
+        format!("&[\n{entries}]\n"),
+    )
+    .expect("generated file can be written");
+}
@@ -2,55 +2,139 @@ use crate::editor::ui_component::UiComponent;
 
 pub mod annotated_string;
 mod caret_position;
+pub mod cli;
 pub mod command;
+mod config;
+mod diagnostics;
 mod document_status;
+mod ex_command;
+#[cfg(test)]
+mod harness;
+mod layout;
 mod line;
+mod macro_registers;
+mod paths;
+mod prompt_history;
+mod search_pattern;
+mod session;
 pub mod size;
 mod terminal;
+#[cfg(test)]
+mod tests;
 mod ui_component;
 
-use crate::editor::command::system::System::{Abort, Quit, Resize, Save, Search};
+use crate::editor::command::system::System::{
+    self, Abort, BufferList, CloseSplit, CommandLine, CycleLineNumbers, CycleTheme, FocusNextSplit,
+    GoToLine, GrowSplit, NextBuffer, OpenRecent, PlayMacro, PreviousBuffer, Quit, RecordMacro,
+    Reload, Replace, Resize, Save, Search, ShrinkSplit, SplitHorizontal, SplitVertical, ToggleBom,
+    ToggleCaseInsensitive, ToggleLineEnding, ToggleRegex, ToggleSoftWrap, ToggleTrailingNewline,
+    ToggleWholeWord,
+};
 use caret_position::CaretPosition;
+use cli::{Args, Source};
 use command::Command;
+use command::clipboard::Clipboard;
 use command::edit::Edit;
+use command::key_bindings::KeyBindings;
+use command::mouse::Mouse;
 use command::movement::Move;
+use config::Config;
 use crossterm::event::Event;
+use diagnostics::Diagnostics;
+use ex_command::{ExCommand, LineRange, SetOption};
+use layout::{Direction, Layout};
+use macro_registers::{MAX_PLAYBACK_COUNT, MacroRegisters};
+use prompt_history::PromptHistory;
+use search_pattern::{SearchOptions, SearchPattern};
+use session::Session;
 use std::io::Error;
+use std::path::Path;
 use ui_component::command_bar::CommandBar;
 use ui_component::{
     message_bar::{FIVE_SECONDS, MessageBar},
-    status_bar::StatusBar,
-    view::View,
+    view::{View, ViewSettings, highlighter::Highlighter, location::Location},
 };
 
-use crossterm::event::read;
+use crossterm::event::{poll, read};
+use std::time::{Duration, Instant};
 
 use size::Size;
-use terminal::Terminal;
+use terminal::{Terminal, palette::ThemeName};
 
 const QUIT_COUNTER_START: usize = 3;
+const SWAP_INTERVAL: Duration = Duration::from_secs(4);
 
 enum Mode {
     Editing,
     SavingAs,
     Searching,
+    SearchingToReplace,
+    Replacing {
+        pattern: SearchPattern,
+    },
+    ConfirmingReplace {
+        pattern: SearchPattern,
+        replacement: String,
+        count: usize,
+    },
+    SwitchingBuffer,
+    GoingToLine,
+    /// Tab cycles through completions of the line as it was when Tab was first pressed
+    CommandLine {
+        completions: Vec<String>,
+        next_completion: usize,
+    },
+    Recovering {
+        content: String,
+    },
+    /// Next letter names the register to record into
+    ChoosingMacroRegister,
+    PlayingMacro,
 }
 
 const SAVE_PROMPT: &str = "Save As: ";
-const SEARCH_PROMPT: &str = "Search (Esc to cancel, Arrows to navigate): ";
+const SEARCH_PROMPT: &str = "Search (Esc to cancel, Arrows to navigate, PgUp/PgDn for history): ";
+const SEARCH_TO_REPLACE_PROMPT: &str = "Replace, search for (Esc to cancel, Enter to continue): ";
+const GO_TO_LINE_PROMPT: &str = "Go to line[:column]: ";
+const COMMAND_LINE_PROMPT: &str = ":";
+const RECORD_MACRO_PROMPT: &str = "Record macro into register (a-z, Esc to cancel): ";
+const PLAY_MACRO_PROMPT: &str = "Play macro, [count]register: ";
 
 pub struct Editor {
     should_quit: bool,
-    view: View,
-    status_bar: StatusBar,
+    views: Vec<View>,
+    /// Windows showing the views, the focused one shows the active view
+    layout: Layout,
     message_bar: MessageBar,
     command_bar: CommandBar,
     quit_counter: usize,
     mode: Mode,
+    clipboard: Option<String>,
+    search_options: SearchOptions,
+    keep_backup: bool,
+    last_swap: Instant,
+    awaiting_confirmation: Option<System>,
+    key_bindings: KeyBindings,
+    /// Settings given to every newly opened view
+    view_settings: ViewSettings,
+    command_history: PromptHistory,
+    search_history: PromptHistory,
+    macros: MacroRegisters,
+    /// Set when a movement could not go any further, stops macro playback
+    move_failed: bool,
+    /// Written back when the editor closes
+    session: Session,
+    /// Also given to buffers opened later
+    diagnostics: Diagnostics,
 }
 
 impl Editor {
-    pub fn new() -> Result<Self, Error> {
+    pub fn new(args: &Args) -> Result<Self, Error> {
+        // Read before raw mode, the terminal takes over the keyboard afterwards
+        let stdin = args
+            .reads_stdin()
+            .then(|| std::io::read_to_string(std::io::stdin()));
+
         let current_hook = std::panic::take_hook();
 
         std::panic::set_hook(Box::new(move |panic_info| {
@@ -60,29 +144,85 @@ impl Editor {
 
         Terminal::init()?;
 
-        let (mut view, status_bar, message_bar, command_bar) = Self::create_components();
+        // Broken config is reported once the editor is up, defaults are used meanwhile
+        let (config, config_error) = match Config::load() {
+            Ok(config) => (config, None),
+            Err(err) => (Config::default(), Some(err)),
+        };
+        let mut editor = Self::with_args(args, stdin, config, Session::load());
+
+        let errors: Vec<String> = config_error
+            .map(|err| format!("Config error: {err}"))
+            .into_iter()
+            .chain(
+                Highlighter::definition_errors()
+                    .iter()
+                    .map(|err| format!("Syntax definition error: {err}")),
+            )
+            .collect();
+        if !errors.is_empty() {
+            editor.message_bar.update_message(&errors.join(", "));
+        }
 
-        Self::load_file(&mut view);
+        Ok(editor)
+    }
 
-        Ok(Editor {
+    /// Opens the files on whatever terminal backend is in place, without touching the terminal modes
+    fn with_args(
+        args: &Args,
+        stdin: Option<Result<String, Error>>,
+        mut config: Config,
+        mut session: Session,
+    ) -> Self {
+        let (message_bar, command_bar) = Self::create_components();
+
+        Terminal::set_theme(config.take_theme());
+        let view_settings = config.view_settings();
+        let (views, errors) = Self::load_files(args, stdin, &view_settings, &session);
+
+        let mut editor = Editor {
             should_quit: false,
-            view,
-            status_bar,
+            views,
+            layout: Layout::new(0),
             message_bar,
             command_bar,
             quit_counter: QUIT_COUNTER_START,
             mode: Mode::Editing,
-        })
+            clipboard: None,
+            search_options: SearchOptions::default(),
+            keep_backup: config.editor.keep_backup,
+            last_swap: Instant::now(),
+            awaiting_confirmation: None,
+            key_bindings: std::mem::take(&mut config.keys),
+            view_settings,
+            command_history: PromptHistory::from_entries(std::mem::take(
+                &mut session.command_history,
+            )),
+            search_history: PromptHistory::from_entries(std::mem::take(
+                &mut session.search_history,
+            )),
+            macros: MacroRegisters::default(),
+            move_failed: false,
+            session,
+            diagnostics: Diagnostics::default(),
+        };
+
+        editor.arrange_windows(Terminal::size().unwrap_or_default());
+        editor.switch_to_view(0);
+        editor.offer_recovery(0);
+
+        if !errors.is_empty() {
+            editor.message_bar.update_message(&errors.join(", "));
+        }
+
+        editor
     }
 
-    fn create_components() -> (View, StatusBar, MessageBar, CommandBar) {
+    fn create_components() -> (MessageBar, CommandBar) {
         let message_bar = MessageBar::new();
-        let status_bar = StatusBar::new();
         let command_bar = CommandBar::new();
 
-        let view = View::new(2);
-
-        (view, status_bar, message_bar, command_bar)
+        (message_bar, command_bar)
     }
 
     pub fn run(&mut self) {
@@ -93,11 +233,15 @@ impl Editor {
                 break;
             }
 
-            match read() {
-                Ok(event) => {
+            let timeout = SWAP_INTERVAL.saturating_sub(self.last_swap.elapsed());
+
+            match poll(timeout).and_then(|has_event| has_event.then(read).transpose()) {
+                Ok(Some(event)) => {
                     self.evaluate_event(event);
                 }
 
+                Ok(None) => (),
+
                 Err(error) => {
                     #[cfg(debug_assertions)]
                     {
@@ -106,43 +250,426 @@ impl Editor {
                 }
             }
 
-            let status = self.view.get_status();
-            self.status_bar.update_status(status);
+            self.write_swap_files();
+            self.update_status();
+        }
+    }
+
+    // ================================================== Swap files ====================================================================
+
+    fn write_swap_files(&mut self) {
+        if self.last_swap.elapsed() < SWAP_INTERVAL {
+            return;
+        }
+
+        self.last_swap = Instant::now();
+
+        let failed = self
+            .views
+            .iter_mut()
+            .filter_map(|view| view.write_swap().err())
+            .count();
+
+        if failed > 0 {
+            self.message_bar
+                .update_message(&format!("Could not write {failed} swap file(s)"));
         }
     }
 
-    fn load_file(view: &mut View) {
-        let env_vars: Vec<String> = std::env::args().collect();
+    /// Asks about the first buffer starting from given index which has a swap file newer than the file
+    /// Read-only buffers are left as they are on disk
+    fn offer_recovery(&mut self, from: usize) {
+        let found = self
+            .views
+            .iter()
+            .enumerate()
+            .skip(from)
+            .filter(|(_, view)| !view.is_read_only())
+            .find_map(|(idx, view)| view.get_swap_content().map(|content| (idx, content)));
+
+        let Some((idx, content)) = found else {
+            self.exit_mode();
+            return;
+        };
+
+        self.switch_to_view(idx);
+
+        let file_name = self.view().get_status().file_name.unwrap_or_default();
+        self.command_bar.clear_line();
+        self.command_bar.set_prompt(format!(
+            "Found unsaved changes to '{file_name}', recover them? (y)es (n)o: "
+        ));
+        self.mode = Mode::Recovering { content };
+    }
 
-        if env_vars.len() == 1 {
+    fn handle_recovery_command(&mut self, command: Command) {
+        let Mode::Recovering { content } = &self.mode else {
             return;
+        };
+        let content = content.clone();
+
+        match command {
+            Command::System(Resize(size)) => self.resize(size),
+            Command::Edit(Edit::Input('y')) => {
+                self.view_mut().recover(&content);
+                self.offer_recovery(self.active_view().saturating_add(1));
+            }
+            Command::Edit(Edit::Input('n')) => {
+                self.view().remove_swap();
+                self.offer_recovery(self.active_view().saturating_add(1));
+            }
+            // Swap file is kept so it can be recovered next time
+            Command::System(Abort) => self.offer_recovery(self.active_view().saturating_add(1)),
+            _ => (),
+        }
+    }
+
+    /// Every source is opened in its own buffer, a file at the given location or where it was left last time
+    /// Sources that cannot be read are skipped and reported, without any buffer there is one empty buffer
+    fn load_files(
+        args: &Args,
+        mut stdin: Option<Result<String, Error>>,
+        settings: &ViewSettings,
+        session: &Session,
+    ) -> (Vec<View>, Vec<String>) {
+        let mut views = Vec::new();
+        let mut errors = Vec::new();
+
+        for source in &args.sources {
+            let mut view = View::new(2);
+            view.apply_settings(settings);
+
+            match source {
+                Source::File { name, location } => {
+                    if !Path::new(name).exists() {
+                        view.set_buffer_file(name);
+                    } else if let Err(err) = view.load(name) {
+                        errors.push(format!("Could not open '{name}': {err}"));
+                        continue;
+                    }
+
+                    if let Some(location) = location.or_else(|| session.location_of(name)) {
+                        view.go_to(location);
+                    }
+                }
+                Source::Stdin => match stdin.take() {
+                    Some(Ok(text)) => view.load_text(&text),
+                    Some(Err(err)) => {
+                        errors.push(format!("Could not read stdin: {err}"));
+                        continue;
+                    }
+                    None => continue,
+                },
+            }
+
+            view.set_read_only(args.read_only);
+            views.push(view);
+        }
+
+        if views.is_empty() {
+            let mut view = View::new(2);
+            view.apply_settings(settings);
+            view.set_recent_files(session.recent_files());
+            views.push(view);
+        }
+
+        (views, errors)
+    }
+
+    /// Every split has a status line of its own
+    fn update_status(&mut self) {
+        let buffer_count = self.views.len();
+
+        for window in self.layout.windows_mut() {
+            let mut status = self.views[window.view].get_status();
+            status.buffer_index = window.view;
+            status.buffer_count = buffer_count;
+            window.status_bar.update_status(status);
+        }
+    }
+
+    // ================================================== Session =======================================================================
+
+    /// Files of all buffers with their caret locations, the active one ends up as the most recent file
+    fn save_session(&mut self) -> Result<(), std::io::Error> {
+        let active = self.active_view();
+        let order = (0..self.views.len())
+            .filter(|idx| *idx != active)
+            .chain(std::iter::once(active));
+
+        for idx in order {
+            let view = &self.views[idx];
+            if let Some(file_name) = view.get_status().file_name
+                && view.is_file_given()
+            {
+                self.session.remember(&file_name, view.location());
+            }
         }
 
-        if let Some(name) = env_vars.get(1) {
-            let _ = Terminal::set_title(name);
-            view.load(name);
+        self.session.search_history = self.search_history.entries().to_vec();
+        self.session.command_history = self.command_history.entries().to_vec();
+        self.session.save()
+    }
+
+    fn open_recent(&mut self, idx: usize) {
+        match self.session.recent_files().get(idx) {
+            Some(file_name) => self.open_file(&file_name.clone()),
+            None => self.message_bar.update_message(&format!(
+                "There is no recent file {}",
+                idx.saturating_add(1)
+            )),
+        }
+    }
+
+    // ================================================== Buffer switching ==============================================================
+
+    fn active_view(&self) -> usize {
+        self.layout.focused().view
+    }
+
+    fn view(&self) -> &View {
+        // There is always at least one view and windows only refer to existing ones
+        &self.views[self.active_view()]
+    }
+
+    fn view_mut(&mut self) -> &mut View {
+        let idx = self.active_view();
+        &mut self.views[idx]
+    }
+
+    /// View is shown in the focused window, a view shown in another window gets that window focused instead
+    fn switch_to_view(&mut self, idx: usize) {
+        if idx >= self.views.len() {
+            return;
+        }
+
+        if let Some(window_idx) = self.layout.window_showing(idx) {
+            self.focus_window(window_idx);
+            return;
+        }
+
+        let view_size = {
+            let window = self.layout.focused_mut();
+            window.view = idx;
+            window.view_size()
+        };
+        self.view_mut().resize(view_size);
+        self.focus_window(self.layout.focused_idx());
+    }
+
+    fn next_view(&mut self) {
+        self.cycle_view(true);
+    }
+
+    fn previous_view(&mut self) {
+        self.cycle_view(false);
+    }
+
+    /// Views already shown in another window are skipped
+    fn cycle_view(&mut self, forward: bool) {
+        let count = self.views.len();
+        let current = self.active_view();
+
+        let next = (1..count)
+            .map(|step| {
+                if forward {
+                    current.saturating_add(step) % count
+                } else {
+                    current.saturating_add(count.saturating_sub(step)) % count
+                }
+            })
+            .find(|idx| self.layout.window_showing(*idx).is_none());
+
+        if let Some(next) = next {
+            self.switch_to_view(next);
+        }
+    }
+
+    fn buffer_list_prompt(&self) -> String {
+        let list = self
+            .views
+            .iter()
+            .enumerate()
+            .map(|(idx, view)| {
+                let name = view
+                    .get_status()
+                    .file_name
+                    .unwrap_or_else(|| String::from("[None]"));
+                format!("{}: {name}", idx.saturating_add(1))
+            })
+            .collect::<Vec<String>>()
+            .join(", ");
+
+        let prompt = format!("Switch to buffer ({list}): ");
+        let available = Terminal::size().unwrap_or_default().columns / 2;
+
+        if prompt.len() <= available {
+            prompt
         } else {
-            let _ = Terminal::set_title("editr");
+            format!("Switch to buffer [1-{}]: ", self.views.len())
+        }
+    }
+
+    /// Buffer is chosen by its number or by part of its file name
+    fn find_view(&self, query: &str) -> Option<usize> {
+        let query = query.trim();
+
+        if let Ok(number) = query.parse::<usize>() {
+            return (1..=self.views.len())
+                .contains(&number)
+                .then(|| number.saturating_sub(1));
+        }
+
+        if query.is_empty() {
+            return None;
         }
 
-        // For now i will ignore everything past the file name
+        self.views.iter().position(|view| {
+            view.get_status()
+                .file_name
+                .is_some_and(|name| name.contains(query))
+        })
+    }
+
+    // ================================================== Splits ========================================================================
+
+    /// View of the window may be behind edits made through another view of its buffer
+    fn focus_window(&mut self, idx: usize) {
+        self.layout.focus(idx);
+        self.view_mut().catch_up();
+        self.view_mut().mark_redraw(true);
+
+        let title = self
+            .view()
+            .get_status()
+            .file_name
+            .unwrap_or_else(|| String::from("editr"));
+        let _ = Terminal::set_title(&title);
+
+        self.update_status();
     }
 
+    /// New view of the active buffer in half of the focused window
+    fn split(&mut self, direction: Direction) {
+        let view = self.view().split();
+        self.views.push(view);
+        let idx = self.views.len().saturating_sub(1);
+
+        if !self.layout.split(direction, idx) {
+            self.views.pop();
+            self.message_bar
+                .update_message("Not enough room to split the window");
+            return;
+        }
+
+        self.arrange_windows(Terminal::size().unwrap_or_default());
+        self.focus_window(self.layout.focused_idx());
+    }
+
+    /// View of the closed window is dropped when another view shows the same buffer, otherwise it stays in the buffer list
+    fn close_split(&mut self) {
+        let Some(closed) = self.layout.close_focused() else {
+            self.message_bar
+                .update_message("Cannot close the last window");
+            return;
+        };
+
+        let is_duplicate = self
+            .views
+            .iter()
+            .enumerate()
+            .any(|(idx, view)| idx != closed && view.shares_buffer(&self.views[closed]));
+
+        if is_duplicate {
+            self.views.remove(closed);
+            self.layout.forget_view(closed);
+        }
+
+        self.arrange_windows(Terminal::size().unwrap_or_default());
+        self.focus_window(self.layout.focused_idx());
+    }
+
+    fn focus_next_split(&mut self) {
+        self.layout.focus_next();
+        self.focus_window(self.layout.focused_idx());
+    }
+
+    fn resize_split(&mut self, grow: bool) {
+        self.layout.resize_focused(grow);
+        self.arrange_windows(Terminal::size().unwrap_or_default());
+    }
+
+    /// Last row of the terminal is left for the message and command bar
+    fn arrange_windows(&mut self, terminal_size: Size) {
+        self.layout.arrange(Size {
+            columns: terminal_size.columns,
+            rows: terminal_size.rows.saturating_sub(1),
+        });
+
+        for window in self.layout.windows() {
+            self.views[window.view].resize(window.view_size());
+        }
+    }
+
+    /// Click focuses the window under the pointer and the wheel scrolls it, dragging stays in the focused one
+    fn handle_mouse(&mut self, mouse_command: Mouse) {
+        let window_idx = match mouse_command {
+            Mouse::Drag(_) => self.layout.focused_idx(),
+            _ => match self.layout.window_at(mouse_command.position()) {
+                Some(window_idx) => window_idx,
+                None => return,
+            },
+        };
+
+        if let Mouse::Click(_) = mouse_command
+            && window_idx != self.layout.focused_idx()
+        {
+            self.focus_window(window_idx);
+        }
+
+        let windows = self.layout.windows();
+        let window = windows[window_idx];
+        let (view, origin) = (window.view, window.origin);
+
+        self.views[view].handle_mouse_command(mouse_command.relative_to(origin));
+    }
+
+    /// User key bindings are looked up before the built-in ones
     fn evaluate_event(&mut self, event: Event) {
-        if let Ok(command) = Command::try_from(event) {
+        let bound = match event {
+            Event::Key(key_event) => self.key_bindings.command_for(key_event),
+            _ => None,
+        };
+
+        if let Some(command) = bound.or_else(|| Command::try_from(event).ok()) {
             self.handle_command(command);
         }
     }
 
     fn handle_command(&mut self, command: Command) {
+        if self.should_record(command) {
+            self.macros.record(command);
+        }
+
         match self.mode {
             Mode::Editing => self.handle_editing_command(command),
             Mode::SavingAs => self.handle_save_command(command),
-            Mode::Searching => self.handle_search_command(command),
+            Mode::Searching | Mode::SearchingToReplace => self.handle_search_command(command),
+            Mode::Replacing { .. } => self.handle_replace_command(command),
+            Mode::ConfirmingReplace { .. } => self.handle_confirm_replace_command(command),
+            Mode::SwitchingBuffer => self.handle_buffer_list_command(command),
+            Mode::GoingToLine => self.handle_go_to_line_command(command),
+            Mode::CommandLine { .. } => self.handle_command_line_command(command),
+            Mode::Recovering { .. } => self.handle_recovery_command(command),
+            Mode::ChoosingMacroRegister => self.handle_macro_register_command(command),
+            Mode::PlayingMacro => self.handle_play_macro_command(command),
         }
     }
 
     fn handle_editing_command(&mut self, command: Command) {
+        // Commands needing confirmation go through only when repeated right away
+        let confirmed = self.awaiting_confirmation.take();
+
         match command {
             Command::System(Quit) => self.quit_try(),
             Command::System(Resize(size)) => self.resize(size),
@@ -150,12 +677,140 @@ impl Editor {
             _ => self.reset_quit_counter(),
         }
 
+        if Self::changes_buffer(command) && self.refuse_if_read_only() {
+            return;
+        }
+
         match command {
-            Command::System(Quit | Resize(_) | Abort) => (),
+            Command::System(
+                Quit | Resize(_) | ToggleCaseInsensitive | ToggleWholeWord | ToggleRegex,
+            ) => (),
+            Command::System(Abort) => self.view_mut().clear_selection(),
             Command::System(Search) => self.handle_search(),
-            Command::System(Save) => self.handle_save(),
-            Command::Move(move_command) => self.view.handle_move_command(move_command),
-            Command::Edit(edit_command) => self.view.handle_edit_command(edit_command),
+            Command::System(Replace) => self.handle_replace(),
+            Command::System(Save) => self.handle_save(matches!(confirmed, Some(Save))),
+            Command::System(Reload) => self.handle_reload(matches!(confirmed, Some(Reload))),
+            Command::System(NextBuffer) => self.next_view(),
+            Command::System(PreviousBuffer) => self.previous_view(),
+            Command::System(BufferList) => self.enter_buffer_list_mode(),
+            Command::System(GoToLine) => self.enter_go_to_line_mode(),
+            Command::System(CommandLine) => self.enter_command_line_mode(),
+            Command::System(SplitHorizontal) => self.split(Direction::Horizontal),
+            Command::System(SplitVertical) => self.split(Direction::Vertical),
+            Command::System(CloseSplit) => self.close_split(),
+            Command::System(FocusNextSplit) => self.focus_next_split(),
+            Command::System(GrowSplit) => self.resize_split(true),
+            Command::System(ShrinkSplit) => self.resize_split(false),
+            Command::System(RecordMacro) => self.toggle_macro_recording(),
+            Command::System(PlayMacro) => self.enter_play_macro_mode(),
+            Command::System(OpenRecent(idx)) => self.open_recent(idx),
+            Command::System(ToggleSoftWrap) => self.toggle_soft_wrap(),
+            Command::System(CycleTheme) => self.switch_theme(Terminal::theme_name().next()),
+            Command::System(CycleLineNumbers) => {
+                let line_numbers = self.view_mut().cycle_line_numbers();
+                self.message_bar
+                    .update_message(&format!("Line numbers: {line_numbers}"));
+            }
+            Command::System(
+                system_command @ (ToggleLineEnding | ToggleTrailingNewline | ToggleBom),
+            ) => self.handle_file_format(system_command),
+            Command::Move(move_command) => {
+                if !self.view_mut().handle_move_command(move_command) {
+                    self.move_failed = true;
+                }
+            }
+            Command::Edit(edit_command) => self.view_mut().handle_edit_command(edit_command),
+            Command::Select(select_command) => {
+                self.view_mut().handle_select_command(select_command);
+            }
+            Command::Clipboard(clipboard_command) => self.handle_clipboard(clipboard_command),
+            Command::Mouse(mouse_command) => self.handle_mouse(mouse_command),
+        }
+    }
+
+    fn changes_buffer(command: Command) -> bool {
+        matches!(
+            command,
+            Command::Edit(_)
+                | Command::Clipboard(Clipboard::Cut | Clipboard::Paste)
+                | Command::System(Replace | ToggleLineEnding | ToggleTrailingNewline | ToggleBom)
+        )
+    }
+
+    /// Tells why nothing happened when the buffer cannot be changed
+    fn refuse_if_read_only(&mut self) -> bool {
+        if !self.view().is_read_only() {
+            return false;
+        }
+
+        self.message_bar.update_message("Buffer is read-only");
+        true
+    }
+
+    /// Writing to it would replace a file the buffer was not read from
+    fn is_other_existing_file(&self, file_name: &str) -> bool {
+        Path::new(file_name).exists()
+            && self
+                .view()
+                .get_status()
+                .file_name
+                .is_none_or(|own| !paths::is_same_file(&own, file_name))
+    }
+
+    fn handle_file_format(&mut self, system_command: System) {
+        let mut file_format = self.view().get_file_format();
+
+        match system_command {
+            ToggleLineEnding => file_format.line_ending = file_format.line_ending.toggled(),
+            ToggleTrailingNewline => file_format.trailing_newline = !file_format.trailing_newline,
+            ToggleBom => file_format.bom = !file_format.bom,
+            _ => return,
+        }
+
+        self.view_mut().set_file_format(file_format);
+        self.message_bar
+            .update_message(&format!("File format: {file_format}"));
+    }
+
+    fn toggle_soft_wrap(&mut self) {
+        let message = if self.view_mut().toggle_soft_wrap() {
+            "Soft wrap on"
+        } else {
+            "Soft wrap off"
+        };
+        self.message_bar.update_message(message);
+    }
+
+    fn switch_theme(&mut self, name: ThemeName) {
+        Terminal::switch_theme(name);
+        for window in self.layout.windows_mut() {
+            self.views[window.view].mark_redraw(true);
+            window.status_bar.mark_redraw(true);
+        }
+        self.message_bar.update_message(&format!("Theme: {name}"));
+    }
+
+    fn handle_clipboard(&mut self, clipboard_command: Clipboard) {
+        match clipboard_command {
+            Clipboard::Copy => {
+                if let Some(text) = self.view().selected_text() {
+                    self.clipboard = Some(text);
+                    self.message_bar.update_message("Copied selection");
+                }
+            }
+            Clipboard::Cut => {
+                if let Some(text) = self.view_mut().cut_selection() {
+                    self.clipboard = Some(text);
+                    self.message_bar.update_message("Cut selection");
+                }
+            }
+            Clipboard::Paste => {
+                if let Some(text) = self.clipboard.clone() {
+                    self.view_mut().paste(&text);
+                } else {
+                    self.message_bar.update_message("Clipboard is empty");
+                }
+            }
         }
     }
 
@@ -168,10 +823,10 @@ impl Editor {
         }
 
         if let Command::Edit(Edit::Enter) = command {
-            self.view
-                .set_buffer_file(&self.command_bar.get_command_line());
+            let file_name = self.command_bar.get_command_line();
+            self.view_mut().set_buffer_file(&file_name);
             self.exit_mode();
-            self.handle_save();
+            self.handle_save(false);
         }
     }
 
@@ -179,36 +834,587 @@ impl Editor {
         match command {
             Command::System(Resize(size)) => self.resize(size),
             Command::System(Abort) => self.dismiss_search(),
-            Command::Edit(command::edit::Edit::Enter) => self.exit_search(),
+            Command::System(Replace) => self.enter_replace_mode(),
+            Command::System(option @ (ToggleCaseInsensitive | ToggleWholeWord | ToggleRegex)) => {
+                self.toggle_search_option(option);
+            }
+            Command::Edit(Edit::Enter) if matches!(self.mode, Mode::SearchingToReplace) => {
+                self.enter_replace_mode();
+            }
+            Command::Edit(Edit::Enter) => self.exit_search(),
             Command::Edit(edit_command) => self.handle_edit_search(edit_command),
+            Command::Move(Move::PageUp) => {
+                let line = self.command_bar.get_command_line();
+                if let Some(entry) = self.search_history.previous(&line) {
+                    self.set_search_line(&entry);
+                }
+            }
+            Command::Move(Move::PageDown) => {
+                if let Some(entry) = self.search_history.next() {
+                    self.set_search_line(&entry);
+                }
+            }
             Command::Move(move_command) => self.handle_move_search(move_command),
-            Command::System(_) => (),
+            _ => (),
+        }
+    }
+
+    fn handle_replace_command(&mut self, command: Command) {
+        let Mode::Replacing { pattern } = &self.mode else {
+            return;
+        };
+        let pattern = pattern.clone();
+
+        match command {
+            Command::System(Resize(size)) => self.resize(size),
+            Command::System(Abort) => self.dismiss_search(),
+            Command::System(Replace) | Command::Edit(Edit::Enter) if self.view().is_read_only() => {
+                self.dismiss_search();
+                self.refuse_if_read_only();
+            }
+            Command::System(Replace) => {
+                let replacement = self.command_bar.get_command_line();
+                let count = self.view_mut().replace_all(&pattern, &replacement);
+                self.finish_replace(count);
+            }
+            Command::Edit(Edit::Enter) => {
+                let replacement = self.command_bar.get_command_line();
+                self.enter_confirm_replace_mode(pattern, replacement);
+            }
+            Command::Edit(edit_command) => self.command_bar.handle_edit_command(edit_command),
+            _ => (),
+        }
+    }
+
+    fn handle_confirm_replace_command(&mut self, command: Command) {
+        let Mode::ConfirmingReplace {
+            pattern,
+            replacement,
+            count,
+        } = &self.mode
+        else {
+            return;
+        };
+        let (pattern, replacement, mut count) = (pattern.clone(), replacement.clone(), *count);
+
+        let has_next = match command {
+            Command::System(Resize(size)) => {
+                self.resize(size);
+                true
+            }
+            Command::Edit(Edit::Input('y' | 'a')) if self.view().is_read_only() => {
+                self.view_mut().exit_search();
+                self.exit_mode();
+                self.refuse_if_read_only();
+                return;
+            }
+            Command::Edit(Edit::Input('y')) => {
+                if self.view_mut().replace_current(&pattern, &replacement) {
+                    count = count.saturating_add(1);
+                }
+                self.view_mut().next_match(&pattern, false)
+            }
+            Command::Edit(Edit::Input('n')) => self.view_mut().next_match(&pattern, true),
+            Command::Edit(Edit::Input('a')) => {
+                let replaced = self.view_mut().replace_remaining(&pattern, &replacement);
+                count = count.saturating_add(replaced);
+                false
+            }
+            Command::Edit(Edit::Input('q')) | Command::System(Abort) => false,
+            _ => true,
+        };
+
+        if has_next {
+            self.mode = Mode::ConfirmingReplace {
+                pattern,
+                replacement,
+                count,
+            };
+        } else {
+            self.finish_replace(count);
+        }
+    }
+
+    fn finish_replace(&mut self, count: usize) {
+        self.view_mut().exit_search();
+        self.exit_mode();
+        self.message_bar
+            .update_message(&format!("Replaced {count} occurrence(s)"));
+    }
+
+    fn handle_buffer_list_command(&mut self, command: Command) {
+        match command {
+            Command::System(Resize(size)) => self.resize(size),
+            Command::System(Abort) => self.exit_mode(),
+            Command::Edit(Edit::Enter) => {
+                let query = self.command_bar.get_command_line();
+                self.exit_mode();
+
+                if let Some(idx) = self.find_view(&query) {
+                    self.switch_to_view(idx);
+                } else {
+                    self.message_bar
+                        .update_message(&format!("No buffer matches '{query}'"));
+                }
+            }
+            Command::Edit(edit_command) => self.command_bar.handle_edit_command(edit_command),
+            _ => (),
+        }
+    }
+
+    fn handle_go_to_line_command(&mut self, command: Command) {
+        match command {
+            Command::System(Resize(size)) => self.resize(size),
+            Command::System(Abort) => self.exit_mode(),
+            Command::Edit(Edit::Enter) => {
+                let target = self.command_bar.get_command_line();
+                self.exit_mode();
+
+                if let Some(location) = Location::parse(&target) {
+                    self.view_mut().go_to(location);
+                } else {
+                    self.message_bar.update_message(&format!(
+                        "Invalid line '{target}', expected line[:column]"
+                    ));
+                }
+            }
+            Command::Edit(edit_command) => self.command_bar.handle_edit_command(edit_command),
+            _ => (),
+        }
+    }
+
+    // ================================================== Macros ========================================================================
+
+    /// Prompts of the macros themselves are left out, so is everything tied to the screen
+    fn should_record(&self, command: Command) -> bool {
+        self.macros.recording().is_some()
+            && !matches!(self.mode, Mode::ChoosingMacroRegister | Mode::PlayingMacro)
+            && !matches!(
+                command,
+                Command::System(RecordMacro | PlayMacro | Resize(_)) | Command::Mouse(_)
+            )
+    }
+
+    fn toggle_macro_recording(&mut self) {
+        if let Some((register, count)) = self.macros.stop() {
+            self.message_bar.update_message(&format!(
+                "Recorded {count} command(s) into register '{register}'"
+            ));
+        } else {
+            self.mode = Mode::ChoosingMacroRegister;
+            self.command_bar.set_prompt(RECORD_MACRO_PROMPT.to_string());
+        }
+    }
+
+    fn handle_macro_register_command(&mut self, command: Command) {
+        match command {
+            Command::System(Resize(size)) => self.resize(size),
+            Command::System(Abort) => self.exit_mode(),
+            Command::Edit(Edit::Input(register)) if MacroRegisters::is_valid_name(register) => {
+                self.exit_mode();
+                self.macros.start(register);
+                self.message_bar.update_message(&format!(
+                    "Recording into register '{register}', Alt-m to stop"
+                ));
+            }
+            _ => (),
+        }
+    }
+
+    fn handle_play_macro_command(&mut self, command: Command) {
+        match command {
+            Command::System(Resize(size)) => self.resize(size),
+            Command::System(Abort) => self.exit_mode(),
+            Command::Edit(Edit::Enter) => {
+                let target = self.command_bar.get_command_line();
+                self.exit_mode();
+
+                if let Some((count, register)) = MacroRegisters::parse_playback(&target) {
+                    self.play_macro(register, count);
+                } else {
+                    self.message_bar.update_message(&format!(
+                        "Invalid macro '{target}', expected [count]register with a count up to {MAX_PLAYBACK_COUNT}"
+                    ));
+                }
+            }
+            Command::Edit(edit_command) => self.command_bar.handle_edit_command(edit_command),
+            _ => (),
+        }
+    }
+
+    /// Every run goes through the commands as if they were typed
+    ///
+    /// A failed movement stops the playback, so does a run changing neither the buffer nor the caret
+    fn play_macro(&mut self, register: char, count: usize) {
+        let Some(commands) = self.macros.get(register) else {
+            self.message_bar
+                .update_message(&format!("Register '{register}' is empty"));
+            return;
+        };
+
+        for run in 1..=count {
+            let before = self.playback_state();
+
+            for command in &commands {
+                self.move_failed = false;
+                self.handle_command(*command);
+
+                if self.move_failed {
+                    self.move_failed = false;
+                    self.message_bar.update_message(&format!(
+                        "Macro '{register}' stopped in run {run} of {count}, the caret could not move further"
+                    ));
+                    return;
+                }
+            }
+
+            if run < count && self.playback_state() == before {
+                self.message_bar.update_message(&format!(
+                    "Macro '{register}' stopped after run {run} of {count}, it changed nothing"
+                ));
+                return;
+            }
+        }
+
+        self.message_bar
+            .update_message(&format!("Played macro '{register}' {count} time(s)"));
+    }
+
+    /// Focused view, revision of its buffer and caret, the same again after a run means it did nothing
+    fn playback_state(&self) -> (usize, usize, Location) {
+        (
+            self.layout.focused().view,
+            self.view().revision(),
+            self.view().location(),
+        )
+    }
+
+    // ================================================== Command line ==================================================================
+
+    fn handle_command_line_command(&mut self, command: Command) {
+        match command {
+            Command::System(Resize(size)) => self.resize(size),
+            Command::System(Abort) => {
+                self.command_history.reset();
+                self.exit_mode();
+            }
+            Command::Edit(Edit::Enter) => {
+                let line = self.command_bar.get_command_line();
+                self.command_history.push(&line);
+                self.exit_mode();
+                self.execute_command_line(&line);
+            }
+            Command::Edit(Edit::Tab) => self.complete_command_line(),
+            Command::Move(Move::Up) => {
+                let line = self.command_bar.get_command_line();
+                if let Some(entry) = self.command_history.previous(&line) {
+                    self.set_command_line(&entry);
+                }
+            }
+            Command::Move(Move::Down) => {
+                if let Some(entry) = self.command_history.next() {
+                    self.set_command_line(&entry);
+                }
+            }
+            Command::Edit(edit_command) => {
+                self.command_bar.handle_edit_command(edit_command);
+                self.set_command_line_completions(Vec::new());
+            }
+            _ => (),
+        }
+    }
+
+    fn set_command_line(&mut self, line: &str) {
+        self.command_bar.set_line(line);
+        self.set_command_line_completions(Vec::new());
+    }
+
+    fn set_command_line_completions(&mut self, new_completions: Vec<String>) {
+        if let Mode::CommandLine {
+            completions,
+            next_completion,
+        } = &mut self.mode
+        {
+            *completions = new_completions;
+            *next_completion = 0;
+        }
+    }
+
+    fn complete_command_line(&mut self) {
+        let Mode::CommandLine {
+            completions,
+            next_completion,
+        } = &mut self.mode
+        else {
+            return;
+        };
+
+        if completions.is_empty() {
+            *completions = ex_command::complete(&self.command_bar.get_line());
+            *next_completion = 0;
+        }
+
+        if let Some(completion) = completions.get(*next_completion) {
+            self.command_bar.set_line(completion);
+            *next_completion = next_completion.saturating_add(1) % completions.len();
+        }
+    }
+
+    fn execute_command_line(&mut self, line: &str) {
+        if line.trim().is_empty() {
+            return;
+        }
+
+        let command = match line.parse::<ExCommand>() {
+            Ok(command) => command,
+            Err(err) => {
+                self.message_bar.update_message(&err);
+                return;
+            }
+        };
+
+        if matches!(
+            command,
+            ExCommand::Write { .. } | ExCommand::WriteQuit | ExCommand::Substitute { .. }
+        ) && self.refuse_if_read_only()
+        {
+            return;
+        }
+
+        match command {
+            ExCommand::Write { file_name, force } => {
+                if let Some(file_name) = file_name {
+                    if !force && self.is_other_existing_file(&file_name) {
+                        self.message_bar.update_message(&format!(
+                            "File '{file_name}' exists, use w! to overwrite it"
+                        ));
+                        return;
+                    }
+                    self.view_mut().set_buffer_file(&file_name);
+                }
+                self.handle_save(force);
+            }
+            ExCommand::Quit { force } => self.quit(force),
+            ExCommand::WriteQuit => {
+                self.handle_save(false);
+                if !self.view().get_status().is_modified {
+                    self.quit(false);
+                }
+            }
+            ExCommand::Edit(file_name) => self.open_file(&file_name),
+            ExCommand::Set(option) => self.set_option(option),
+            ExCommand::GoTo(location) => self.view_mut().go_to(location),
+            ExCommand::Split(direction) => self.split(direction),
+            ExCommand::Close => self.close_split(),
+            ExCommand::Diagnostics(file_name) => self.load_diagnostics(file_name.as_deref()),
+            ExCommand::Substitute {
+                range,
+                find,
+                replacement,
+                every_match,
+                case_insensitive,
+            } => {
+                let options = SearchOptions {
+                    case_insensitive,
+                    regex: true,
+                    ..SearchOptions::default()
+                };
+                match SearchPattern::new(&find, options) {
+                    Ok(pattern) => self.substitute(range, &pattern, &replacement, every_match),
+                    Err(_) => self
+                        .message_bar
+                        .update_message(&format!("Invalid pattern '{find}'")),
+                }
+            }
+        }
+    }
+
+    fn substitute(
+        &mut self,
+        range: LineRange,
+        pattern: &SearchPattern,
+        replacement: &str,
+        every_match: bool,
+    ) {
+        let lines = range.resolve(
+            self.view().caret_line_idx(),
+            self.view().get_status().number_of_lines,
+        );
+        let count = self
+            .view_mut()
+            .substitute(pattern, replacement, lines, every_match);
+
+        self.message_bar
+            .update_message(&format!("Replaced {count} occurrence(s)"));
+    }
+
+    fn set_option(&mut self, option: SetOption) {
+        match option {
+            SetOption::SoftWrap(soft_wrap) => self.view_mut().set_soft_wrap(soft_wrap),
+            SetOption::LineNumbers(line_numbers) => self.view_mut().set_line_numbers(line_numbers),
+            SetOption::Indentation(indentation) => self.view_mut().set_indentation(indentation),
+            SetOption::Theme(name) => return self.switch_theme(name),
+            SetOption::CaseInsensitive(on) => self.search_options.case_insensitive = on,
+            SetOption::WholeWord(on) => self.search_options.whole_word = on,
+            SetOption::Regex(on) => self.search_options.regex = on,
+            SetOption::KeepBackup(on) => self.keep_backup = on,
+        }
+
+        self.message_bar.update_message(&format!("Set {option}"));
+    }
+
+    /// Replaces the signs of every buffer, a file which cannot be read leaves the old ones in place
+    fn load_diagnostics(&mut self, file_name: Option<&str>) {
+        let message = if let Some(file_name) = file_name {
+            match Diagnostics::load(file_name) {
+                Ok(diagnostics) => {
+                    self.diagnostics = diagnostics;
+                    format!(
+                        "{} diagnostic(s) from '{file_name}'",
+                        self.diagnostics.len()
+                    )
+                }
+                Err(err) => {
+                    self.message_bar
+                        .update_message(&format!("Could not read '{file_name}': {err}"));
+                    return;
+                }
+            }
+        } else {
+            self.diagnostics = Diagnostics::default();
+            String::from("Diagnostics cleared")
+        };
+
+        for idx in 0..self.views.len() {
+            self.apply_diagnostics(idx);
+        }
+        self.message_bar.update_message(&message);
+    }
+
+    fn apply_diagnostics(&mut self, idx: usize) {
+        let signs = self.views[idx]
+            .get_status()
+            .file_name
+            .map(|file_name| self.diagnostics.signs_for(&file_name))
+            .unwrap_or_default();
+
+        self.views[idx].set_diagnostics(signs);
+    }
+
+    /// Switches to the buffer of the file when it is open already, a file that does not exist yet is created on save
+    fn open_file(&mut self, file_name: &str) {
+        if let Some(idx) = self
+            .views
+            .iter()
+            .position(|view| view.get_status().file_name.as_deref() == Some(file_name))
+        {
+            self.switch_to_view(idx);
+            return;
+        }
+
+        let is_new = !Path::new(file_name).exists();
+        let mut view = View::new(2);
+        view.apply_settings(&self.view_settings);
+
+        if is_new {
+            view.set_buffer_file(file_name);
+        } else if let Err(err) = view.load(file_name) {
+            self.message_bar
+                .update_message(&format!("Could not open '{file_name}': {err}"));
+            return;
+        } else if let Some(location) = self.session.location_of(file_name) {
+            view.go_to(location);
+        }
+
+        self.views.push(view);
+        let idx = self.views.len().saturating_sub(1);
+        self.apply_diagnostics(idx);
+        self.switch_to_view(idx);
+        self.offer_recovery(idx);
+
+        if is_new {
+            self.message_bar
+                .update_message(&format!("New file '{file_name}'"));
         }
     }
 
     fn handle_move_search(&mut self, move_command: Move) {
-        let search_string = self.command_bar.get_line();
+        let Some(Ok(pattern)) = self.search_pattern() else {
+            return;
+        };
 
         match move_command {
-            Move::Right | Move::Down => self.view.search_next(&search_string),
-            Move::Left | Move::Up => self.view.search_previous(&search_string),
+            Move::Right | Move::Down => self.view_mut().search_next(&pattern),
+            Move::Left | Move::Up => self.view_mut().search_previous(&pattern),
             _ => (),
         }
     }
 
+    fn set_search_line(&mut self, line: &str) {
+        self.command_bar.set_line(line);
+        self.update_search();
+    }
+
     fn handle_edit_search(&mut self, edit_command: Edit) {
         self.command_bar.handle_edit_command(edit_command);
-        let search_string = self.command_bar.get_line();
-        self.view.search(&search_string);
+        self.update_search();
+    }
+
+    fn toggle_search_option(&mut self, option: System) {
+        match option {
+            ToggleCaseInsensitive => {
+                self.search_options.case_insensitive = !self.search_options.case_insensitive;
+            }
+            ToggleWholeWord => self.search_options.whole_word = !self.search_options.whole_word,
+            ToggleRegex => self.search_options.regex = !self.search_options.regex,
+            _ => return,
+        }
+
+        self.update_search();
+    }
+
+    /// Re-runs incremental search with current query and options, invalid patterns are reported in the prompt
+    fn update_search(&mut self) {
+        let result = self.search_pattern();
+        let pattern = result.as_ref().and_then(|result| result.as_ref().ok());
+        self.view_mut().search(pattern);
+
+        let base_prompt = if matches!(self.mode, Mode::SearchingToReplace) {
+            SEARCH_TO_REPLACE_PROMPT
+        } else {
+            SEARCH_PROMPT
+        };
+        let error = if matches!(result, Some(Err(_))) {
+            "(invalid pattern) "
+        } else {
+            ""
+        };
+
+        self.command_bar
+            .set_prompt(format!("{}{error}{base_prompt}", self.search_options));
+    }
+
+    /// `None` when the query is empty
+    fn search_pattern(&self) -> Option<Result<SearchPattern, regex::Error>> {
+        let query = self.command_bar.get_line();
+
+        if query.is_empty() {
+            return None;
+        }
+
+        Some(SearchPattern::new(&query, self.search_options))
     }
 
     fn dismiss_search(&mut self) {
-        self.view.dissmiss_search();
+        self.search_history.reset();
+        self.view_mut().dissmiss_search();
         self.exit_mode();
     }
 
     fn exit_search(&mut self) {
-        self.view.exit_search();
+        self.search_history
+            .push(&self.command_bar.get_command_line());
+        self.view_mut().exit_search();
         self.exit_mode();
         #[cfg(debug_assertions)]
         {
@@ -219,18 +1425,38 @@ impl Editor {
     // ================================================== Mode switching ================================================================
 
     fn handle_search(&mut self) {
-        self.view.enter_search();
+        self.view_mut().enter_search();
         self.enter_search_mode();
     }
 
-    fn handle_save(&mut self) {
-        if !self.view.is_file_given() {
+    fn handle_replace(&mut self) {
+        self.view_mut().enter_search();
+        self.mode = Mode::SearchingToReplace;
+        self.command_bar
+            .set_prompt(format!("{}{SEARCH_TO_REPLACE_PROMPT}", self.search_options));
+    }
+
+    fn handle_save(&mut self, overwrite_confirmed: bool) {
+        if self.refuse_if_read_only() {
+            return;
+        }
+
+        if !self.view().is_file_given() {
             self.enter_save_mode();
             return;
         }
 
+        if !overwrite_confirmed && self.view().is_changed_on_disk() {
+            self.message_bar.update_message(
+                "WARNING! File changed on disk since it was read. Press Ctrl-S again to overwrite it or F5 to reload.",
+            );
+            self.awaiting_confirmation = Some(Save);
+            return;
+        }
+
         if matches!(self.mode, Mode::Editing) {
-            if self.view.handle_save().is_err() {
+            let keep_backup = self.keep_backup;
+            if self.view_mut().handle_save(keep_backup).is_err() {
                 self.message_bar.update_message("Error saving the file");
             } else {
                 self.message_bar.update_message("File saved sucessfully!");
@@ -238,6 +1464,28 @@ impl Editor {
         }
     }
 
+    fn handle_reload(&mut self, discard_confirmed: bool) {
+        if !self.view().is_file_given() {
+            self.message_bar
+                .update_message("There is no file to reload");
+            return;
+        }
+
+        if !discard_confirmed && self.view().get_status().is_modified {
+            self.message_bar.update_message(
+                "WARNING! Buffer has unsaved changes. Press F5 again to discard them and reload.",
+            );
+            self.awaiting_confirmation = Some(Reload);
+            return;
+        }
+
+        if self.view_mut().reload().is_err() {
+            self.message_bar.update_message("Error reloading the file");
+        } else {
+            self.message_bar.update_message("File reloaded from disk");
+        }
+    }
+
     // ==================================================== Mode manipulation ========================================================
 
     fn enter_save_mode(&mut self) {
@@ -247,7 +1495,71 @@ impl Editor {
 
     fn enter_search_mode(&mut self) {
         self.mode = Mode::Searching;
-        self.command_bar.set_prompt(SEARCH_PROMPT.to_string());
+        self.command_bar
+            .set_prompt(format!("{}{SEARCH_PROMPT}", self.search_options));
+    }
+
+    fn enter_replace_mode(&mut self) {
+        // The search prompt would hide the message
+        if self.view().is_read_only() {
+            self.exit_search();
+            self.refuse_if_read_only();
+            return;
+        }
+
+        let search_string = self.command_bar.get_line();
+
+        let Some(Ok(pattern)) = self.search_pattern() else {
+            return;
+        };
+
+        self.search_history.push(&search_string);
+        self.command_bar.clear_line();
+        self.command_bar.set_prompt(format!(
+            "Replace '{search_string}' with (Enter to confirm each, Ctrl-R to replace all): "
+        ));
+        self.mode = Mode::Replacing { pattern };
+    }
+
+    fn enter_confirm_replace_mode(&mut self, pattern: SearchPattern, replacement: String) {
+        if !self.view_mut().next_match(&pattern, false) {
+            self.finish_replace(0);
+            return;
+        }
+
+        self.command_bar.clear_line();
+        self.command_bar.set_prompt(format!(
+            "Replace with '{replacement}'? (y)es (n)o (a)ll (q)uit: "
+        ));
+        self.mode = Mode::ConfirmingReplace {
+            pattern,
+            replacement,
+            count: 0,
+        };
+    }
+
+    fn enter_buffer_list_mode(&mut self) {
+        self.mode = Mode::SwitchingBuffer;
+        let prompt = self.buffer_list_prompt();
+        self.command_bar.set_prompt(prompt);
+    }
+
+    fn enter_command_line_mode(&mut self) {
+        self.mode = Mode::CommandLine {
+            completions: Vec::new(),
+            next_completion: 0,
+        };
+        self.command_bar.set_prompt(COMMAND_LINE_PROMPT.to_string());
+    }
+
+    fn enter_play_macro_mode(&mut self) {
+        self.mode = Mode::PlayingMacro;
+        self.command_bar.set_prompt(PLAY_MACRO_PROMPT.to_string());
+    }
+
+    fn enter_go_to_line_mode(&mut self) {
+        self.mode = Mode::GoingToLine;
+        self.command_bar.set_prompt(GO_TO_LINE_PROMPT.to_string());
     }
 
     fn exit_mode(&mut self) {
@@ -260,13 +1572,40 @@ impl Editor {
         self.quit_counter = QUIT_COUNTER_START;
     }
 
+    /// Buffers shown in several views are counted once
+    fn modified_buffer_count(&self) -> usize {
+        self.views
+            .iter()
+            .enumerate()
+            .filter(|(idx, view)| {
+                view.get_status().is_modified
+                    && !self.views[..*idx]
+                        .iter()
+                        .any(|other| other.shares_buffer(view))
+            })
+            .count()
+    }
+
+    /// Quits right away unless a buffer has unsaved changes, `force` discards them
+    fn quit(&mut self, force: bool) {
+        let modified_buffers = self.modified_buffer_count();
+
+        if force || modified_buffers == 0 {
+            self.should_quit = true;
+        } else {
+            self.message_bar.update_message(&format!(
+                "{modified_buffers} buffer(s) have unsaved changes, use q! to discard them"
+            ));
+        }
+    }
+
     fn quit_try(&mut self) {
-        let status = self.view.get_status();
+        let modified_buffers = self.modified_buffer_count();
 
-        if status.is_modified {
+        if modified_buffers > 0 {
             self.quit_counter = self.quit_counter.saturating_sub(1);
             self.message_bar.update_message(&format!(
-                "WARNING! File has unsaved changes. Press Ctrl-Q {} more times to quit.",
+                "WARNING! {modified_buffers} buffer(s) have unsaved changes. Press Ctrl-Q {} more times to quit.",
                 self.quit_counter
             ));
             if self.quit_counter == 0 {
@@ -288,16 +1627,7 @@ impl Editor {
             rows: 1,
         });
 
-        self.status_bar.resize(Size {
-            columns: new_terminal_size.columns,
-            rows: 1,
-        });
-
-        let new_height = new_terminal_size.rows.saturating_sub(2);
-        self.view.resize(Size {
-            columns: new_terminal_size.columns,
-            rows: new_height,
-        });
+        self.arrange_windows(new_terminal_size);
     }
 
     fn refresh_screen(&mut self) {
@@ -309,18 +1639,26 @@ impl Editor {
     }
 
     fn move_caret(&self) {
-        let position =
-            if (matches!(self.mode, Mode::Editing) || matches!(self.mode, Mode::Searching)) {
-                self.view.caret_position()
-            } else {
-                let row = Terminal::size().unwrap_or_default().rows.saturating_sub(1);
-                let caret_pos = self.command_bar.caret_position_column();
+        let position = if matches!(
+            self.mode,
+            Mode::Editing | Mode::Searching | Mode::SearchingToReplace
+        ) {
+            let origin = self.layout.focused().origin;
+            let caret = self.view().caret_position();
+
+            CaretPosition {
+                column: origin.column.saturating_add(caret.column),
+                row: origin.row.saturating_add(caret.row),
+            }
+        } else {
+            let row = Terminal::size().unwrap_or_default().rows.saturating_sub(1);
+            let caret_pos = self.command_bar.caret_position_column();
 
-                CaretPosition {
-                    row,
-                    column: caret_pos,
-                }
-            };
+            CaretPosition {
+                row,
+                column: caret_pos,
+            }
+        };
         let _ = Terminal::move_caret_to(position);
     }
 
@@ -333,21 +1671,27 @@ impl Editor {
 
         // Order of rendering here is important
 
-        if terminal_size.rows > 2 {
-            self.view.render(0);
-        }
-
         if terminal_size.rows > 1 {
-            self.status_bar.render(terminal_size.rows.saturating_sub(2));
+            for window in self.layout.windows_mut() {
+                if window.size.rows > 1 {
+                    self.views[window.view].render(window.origin);
+                }
+                if window.size.rows > 0 {
+                    window.status_bar.render(window.status_origin());
+                }
+            }
+            self.layout.render();
         }
 
+        let bottom = CaretPosition {
+            column: 0,
+            row: terminal_size.rows.saturating_sub(1),
+        };
         if matches!(self.mode, Mode::Editing) {
             self.message_bar.check_message_expired(FIVE_SECONDS);
-            self.message_bar
-                .render(terminal_size.rows.saturating_sub(1));
+            self.message_bar.render(bottom);
         } else {
-            self.command_bar
-                .render(terminal_size.rows.saturating_sub(1));
+            self.command_bar.render(bottom);
         }
     }
 }
@@ -355,7 +1699,13 @@ impl Editor {
 impl Drop for Editor {
     fn drop(&mut self) {
         let _ = Terminal::terminate();
+        let _ = self.save_session();
         if self.should_quit {
+            // Changes were deliberately discarded, there is nothing left to recover
+            for view in &self.views {
+                view.remove_swap();
+            }
+
             let _ = Terminal::print("Chaos!\r\n");
         }
     }
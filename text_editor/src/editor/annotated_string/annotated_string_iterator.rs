@@ -17,10 +17,9 @@ impl<'a> Iterator for AnnotatedStringIterator<'a> {
             .annotated_string
             .annotations
             .iter()
-            .filter(|annotation| {
+            .rfind(|annotation| {
                 annotation.start_byte <= self.current_idx && annotation.end_byte > self.current_idx
             })
-            .last()
         {
             let end_idx = std::cmp::min(annotation.end_byte, self.annotated_string.string.len());
             let start_idx = self.current_idx;
@@ -29,7 +28,7 @@ impl<'a> Iterator for AnnotatedStringIterator<'a> {
                 string: &self.annotated_string.string[start_idx..end_idx],
                 annotaion_type: Some(annotation.annotation_type),
             });
-        };
+        }
 
         let mut end_idx = self.annotated_string.string.len();
         for annotation in &self.annotated_string.annotations {
@@ -5,6 +5,7 @@ use crate::editor::line::ByteIdx;
 use super::annotation_type::AnnotationType;
 
 #[derive(Clone, Copy, Debug)]
+#[allow(clippy::struct_field_names)]
 pub struct Annotation {
     pub start_byte: ByteIdx,
     pub end_byte: ByteIdx,
@@ -28,6 +29,6 @@ impl Annotation {
 
 impl Display for Annotation {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{} {} \n", self.start_byte, self.end_byte)
+        writeln!(f, "{} {} ", self.start_byte, self.end_byte)
     }
 }
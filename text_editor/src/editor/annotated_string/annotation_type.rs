@@ -1,13 +1,23 @@
-#[derive(Clone, Copy, Debug)]
+use serde::Deserialize;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub enum AnnotationType {
     Match,
     SelectedMatch,
     Number,
     Type,
+    #[serde(rename = "keyword")]
     KeyWord,
     KnownValue,
     Char,
     Lifetime,
     Comment,
     String,
+    Selection,
+    LineNumber,
+    CurrentLineNumber,
+    ModifiedLine,
+    SearchHit,
+    Diagnostic,
 }
@@ -19,6 +19,18 @@ impl AnnotatedString {
         self.annotations.push(new_annotation);
     }
 
+    /// Puts other string in front, annotations of both are kept
+    pub fn prepend(&mut self, prefix: AnnotatedString) {
+        let shift = prefix.string.len();
+
+        for annotation in &mut self.annotations {
+            annotation.shift(shift);
+        }
+
+        self.string.insert_str(0, &prefix.string);
+        self.annotations.extend(prefix.annotations);
+    }
+
     // TODO: fix so it works with graphemes
     pub fn crop(&mut self, range: Range<usize>) {
         if range.start >= range.end {
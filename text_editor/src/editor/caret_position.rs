@@ -1,7 +1,7 @@
 pub type Col = usize;
 pub type Row = usize;
 
-#[derive(Copy, Clone, Default)]
+#[derive(Copy, Clone, Default, Debug, PartialEq, Eq)]
 pub struct CaretPosition {
     pub column: Col,
     pub row: Row,
@@ -0,0 +1,131 @@
+use std::path::Path;
+
+use super::ui_component::view::location::Location;
+
+pub const USAGE: &str = "\
+Usage: editr [OPTIONS] [+LINE[:COLUMN]] [FILE[:LINE[:COLUMN]]]...
+
+Every file is opened in a buffer of its own, files that do not exist yet are created on save.
+
+Arguments:
+  FILE:LINE[:COLUMN]   Open the file with the caret on given line and column, both counted from 1
+  +LINE[:COLUMN] FILE  The same for the file after it
+  -                    Read the text from stdin into a buffer without file
+
+Options:
+  -R, --read-only      Buffers opened from the command line cannot be changed or saved
+  -h, --help           Print this help
+  -V, --version        Print the version
+  --                   Everything after it is a file name
+";
+
+/// What one buffer given on the command line is filled with
+pub enum Source {
+    File {
+        name: String,
+        location: Option<Location>,
+    },
+    Stdin,
+}
+
+#[derive(Default)]
+pub struct Args {
+    pub sources: Vec<Source>,
+    pub read_only: bool,
+}
+
+impl Args {
+    pub fn reads_stdin(&self) -> bool {
+        self.sources
+            .iter()
+            .any(|source| matches!(source, Source::Stdin))
+    }
+}
+
+pub enum Action {
+    Edit(Args),
+    Help,
+    Version,
+}
+
+/// Arguments without the program name
+pub fn parse(arguments: impl IntoIterator<Item = String>) -> Result<Action, String> {
+    let mut args = Args::default();
+    // Location given with `+LINE`, waiting for its file
+    let mut pending: Option<(String, Location)> = None;
+    let mut options_ended = false;
+
+    for argument in arguments {
+        if !options_ended {
+            match argument.as_str() {
+                "-h" | "--help" => return Ok(Action::Help),
+                "-V" | "--version" => return Ok(Action::Version),
+                "-R" | "--read-only" => {
+                    args.read_only = true;
+                    continue;
+                }
+                "--" => {
+                    options_ended = true;
+                    continue;
+                }
+                "-" => {
+                    if args.reads_stdin() {
+                        return Err(String::from("stdin can only be read once"));
+                    }
+                    if let Some((position, _)) = pending {
+                        return Err(format!("'{position}' needs a file after it"));
+                    }
+                    args.sources.push(Source::Stdin);
+                    continue;
+                }
+                _ => (),
+            }
+
+            if let Some(position) = argument.strip_prefix('+') {
+                let location = Location::parse(position).ok_or_else(|| {
+                    format!("Invalid position '{argument}', expected +LINE[:COLUMN]")
+                })?;
+                pending = Some((argument.clone(), location));
+                continue;
+            }
+
+            if argument.starts_with('-') {
+                return Err(format!("Unknown option '{argument}'"));
+            }
+        }
+
+        let (name, location) = match pending.take() {
+            Some((_, location)) => (argument, Some(location)),
+            None => split_location(&argument),
+        };
+        args.sources.push(Source::File { name, location });
+    }
+
+    if let Some((position, _)) = pending {
+        return Err(format!("'{position}' needs a file after it"));
+    }
+
+    Ok(Action::Edit(args))
+}
+
+/// `file:LINE:COLUMN` or `file:LINE`, a file whose name really ends like that is taken as it is
+fn split_location(argument: &str) -> (String, Option<Location>) {
+    if Path::new(argument).exists() {
+        return (argument.to_string(), None);
+    }
+
+    let colons: Vec<usize> = argument.match_indices(':').map(|(idx, _)| idx).collect();
+
+    // Line and column first, so the line is not taken as part of the name
+    for idx in colons.iter().rev().take(2).rev() {
+        let (name, position) = (&argument[..*idx], &argument[idx.saturating_add(1)..]);
+
+        if !name.is_empty()
+            && let Some(location) = Location::parse(position)
+        {
+            return (name.to_string(), Some(location));
+        }
+    }
+
+    (argument.to_string(), None)
+}
@@ -0,0 +1,40 @@
+use std::str::FromStr;
+
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+#[derive(Clone, Copy)]
+pub enum Clipboard {
+    Copy,
+    Cut,
+    Paste,
+}
+
+impl TryFrom<KeyEvent> for Clipboard {
+    type Error = String;
+
+    fn try_from(event: KeyEvent) -> Result<Self, Self::Error> {
+        let KeyEvent {
+            code, modifiers, ..
+        } = event;
+
+        match (code, modifiers) {
+            (KeyCode::Char('c'), KeyModifiers::CONTROL) => Ok(Self::Copy),
+            (KeyCode::Char('x'), KeyModifiers::CONTROL) => Ok(Self::Cut),
+            (KeyCode::Char('v'), KeyModifiers::CONTROL) => Ok(Self::Paste),
+            _ => Err(format!("Clipboard key code not supported: {code:?}")),
+        }
+    }
+}
+
+impl FromStr for Clipboard {
+    type Err = String;
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        match name {
+            "copy" => Ok(Self::Copy),
+            "cut" => Ok(Self::Cut),
+            "paste" => Ok(Self::Paste),
+            _ => Err(format!("Unknown command '{name}'")),
+        }
+    }
+}
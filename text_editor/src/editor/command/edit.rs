@@ -1,3 +1,5 @@
+use std::str::FromStr;
+
 use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
 
 #[derive(Clone, Copy)]
@@ -5,8 +7,13 @@ pub enum Edit {
     Input(char),
     Delete,
     Backspace,
+    DeleteWord,
+    BackspaceWord,
     Tab,
+    Outdent,
     Enter,
+    Undo,
+    Redo,
 }
 
 impl TryFrom<KeyEvent> for Edit {
@@ -20,12 +27,69 @@ impl TryFrom<KeyEvent> for Edit {
         match (code, modifiers) {
             (KeyCode::Backspace, KeyModifiers::NONE) => Ok(Self::Backspace),
             (KeyCode::Delete, KeyModifiers::NONE) => Ok(Self::Delete),
+            (KeyCode::Delete, KeyModifiers::CONTROL) => Ok(Self::DeleteWord),
+            // Ctrl+H is left alone, terminals sending Backspace as ^H would delete words on every Backspace
+            (KeyCode::Backspace, KeyModifiers::CONTROL | KeyModifiers::ALT)
+            | (KeyCode::Char('w'), KeyModifiers::CONTROL) => Ok(Self::BackspaceWord),
 
             (KeyCode::Tab, KeyModifiers::NONE) => Ok(Self::Tab),
+            (KeyCode::BackTab, KeyModifiers::NONE | KeyModifiers::SHIFT) => Ok(Self::Outdent),
             (KeyCode::Enter, KeyModifiers::NONE) => Ok(Self::Enter),
 
+            (KeyCode::Char('z'), KeyModifiers::CONTROL) => Ok(Self::Undo),
+            (KeyCode::Char('y'), KeyModifiers::CONTROL) => Ok(Self::Redo),
+
             (KeyCode::Char(chr), KeyModifiers::NONE | KeyModifiers::SHIFT) => Ok(Self::Input(chr)),
             _ => Err(format!("Movement key code not supported: {code:?}")),
         }
     }
 }
+
+/// Typing characters can not be bound to keys
+impl FromStr for Edit {
+    type Err = String;
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        match name {
+            "delete" => Ok(Self::Delete),
+            "backspace" => Ok(Self::Backspace),
+            "delete_word" => Ok(Self::DeleteWord),
+            "backspace_word" => Ok(Self::BackspaceWord),
+            "tab" => Ok(Self::Tab),
+            "outdent" => Ok(Self::Outdent),
+            "enter" => Ok(Self::Enter),
+            "undo" => Ok(Self::Undo),
+            "redo" => Ok(Self::Redo),
+            _ => Err(format!("Unknown command '{name}'")),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn edit_for(code: KeyCode, modifiers: KeyModifiers) -> Option<Edit> {
+        Edit::try_from(KeyEvent::new(code, modifiers)).ok()
+    }
+
+    #[test]
+    fn word_backspace_is_not_bound_to_ctrl_h() {
+        assert!(edit_for(KeyCode::Char('h'), KeyModifiers::CONTROL).is_none());
+
+        for (code, modifiers) in [
+            (KeyCode::Backspace, KeyModifiers::CONTROL),
+            (KeyCode::Backspace, KeyModifiers::ALT),
+            (KeyCode::Char('w'), KeyModifiers::CONTROL),
+        ] {
+            assert!(matches!(
+                edit_for(code, modifiers),
+                Some(Edit::BackspaceWord)
+            ));
+        }
+        assert!(matches!(
+            edit_for(KeyCode::Backspace, KeyModifiers::NONE),
+            Some(Edit::Backspace)
+        ));
+    }
+}
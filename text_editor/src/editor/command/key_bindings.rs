@@ -0,0 +1,205 @@
+use std::collections::HashMap;
+
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use serde::Deserialize;
+
+use super::Command;
+
+/// Key chords mapped to commands by the user, they win over the built-in bindings
+///
+/// Chords are written like `ctrl+shift+left`, `alt+z`, `f5` or `esc`. Letters are case sensitive:
+/// `alt+N` and `alt+shift+n` both mean Alt with the shifted letter, while `alt+n` does not
+#[derive(Default, Deserialize)]
+#[serde(try_from = "HashMap<String, String>")]
+pub struct KeyBindings {
+    bindings: HashMap<(KeyCode, KeyModifiers), Command>,
+}
+
+impl KeyBindings {
+    pub fn command_for(&self, event: KeyEvent) -> Option<Command> {
+        self.bindings
+            .get(&Self::normalize(event.code, event.modifiers))
+            .copied()
+    }
+
+    fn parse_chord(chord: &str) -> Result<(KeyCode, KeyModifiers), String> {
+        let mut parts: Vec<&str> = chord.split('+').collect();
+
+        // `ctrl++` binds the plus key itself
+        if chord.ends_with("++") {
+            parts.truncate(parts.len().saturating_sub(2));
+            parts.push("+");
+        }
+
+        let Some((key, modifier_names)) = parts.split_last() else {
+            return Err(format!("Empty key chord '{chord}'"));
+        };
+
+        let mut modifiers = KeyModifiers::NONE;
+        for name in modifier_names {
+            modifiers |= match name.to_lowercase().as_str() {
+                "ctrl" | "control" => KeyModifiers::CONTROL,
+                "alt" => KeyModifiers::ALT,
+                "shift" => KeyModifiers::SHIFT,
+                _ => return Err(format!("Unknown modifier '{name}' in key chord '{chord}'")),
+            };
+        }
+
+        let code = Self::parse_key(key)
+            .ok_or_else(|| format!("Unknown key '{key}' in key chord '{chord}'"))?;
+
+        Ok(Self::normalize(code, modifiers))
+    }
+
+    fn parse_key(key: &str) -> Option<KeyCode> {
+        let mut chars = key.chars();
+        if let (Some(chr), None) = (chars.next(), chars.next()) {
+            return Some(KeyCode::Char(chr));
+        }
+
+        let lowercase = key.to_lowercase();
+        let code = match lowercase.as_str() {
+            "esc" | "escape" => KeyCode::Esc,
+            "enter" | "return" => KeyCode::Enter,
+            "tab" => KeyCode::Tab,
+            "backtab" => KeyCode::BackTab,
+            "backspace" => KeyCode::Backspace,
+            "delete" | "del" => KeyCode::Delete,
+            "insert" | "ins" => KeyCode::Insert,
+            "home" => KeyCode::Home,
+            "end" => KeyCode::End,
+            "pageup" | "page_up" => KeyCode::PageUp,
+            "pagedown" | "page_down" => KeyCode::PageDown,
+            "left" => KeyCode::Left,
+            "right" => KeyCode::Right,
+            "up" => KeyCode::Up,
+            "down" => KeyCode::Down,
+            "space" => KeyCode::Char(' '),
+            function => {
+                return function
+                    .strip_prefix('f')?
+                    .parse()
+                    .ok()
+                    .filter(|number| (1..=24).contains(number))
+                    .map(KeyCode::F);
+            }
+        };
+
+        Some(code)
+    }
+
+    /// Terminals report shifted letters in upper case, with or without Shift, so those become the
+    /// upper case letter plus Shift. Other printable characters are already shifted, Shift is dropped.
+    /// Shift-Tab is reported as `BackTab` with or without Shift
+    fn normalize(code: KeyCode, modifiers: KeyModifiers) -> (KeyCode, KeyModifiers) {
+        match code {
+            KeyCode::Char(chr) if chr.is_uppercase() || chr.is_lowercase() => {
+                let mut upper = chr.to_uppercase();
+                match (upper.next(), upper.next()) {
+                    (Some(upper), None)
+                        if chr.is_uppercase() || modifiers.contains(KeyModifiers::SHIFT) =>
+                    {
+                        (KeyCode::Char(upper), modifiers | KeyModifiers::SHIFT)
+                    }
+                    _ => (code, modifiers),
+                }
+            }
+            KeyCode::Char(chr) if !chr.is_whitespace() => {
+                (code, modifiers.difference(KeyModifiers::SHIFT))
+            }
+            KeyCode::BackTab => (KeyCode::BackTab, modifiers.difference(KeyModifiers::SHIFT)),
+            KeyCode::Tab if modifiers.contains(KeyModifiers::SHIFT) => {
+                (KeyCode::BackTab, modifiers.difference(KeyModifiers::SHIFT))
+            }
+            _ => (code, modifiers),
+        }
+    }
+}
+
+impl TryFrom<HashMap<String, String>> for KeyBindings {
+    type Error = String;
+
+    fn try_from(config: HashMap<String, String>) -> Result<Self, Self::Error> {
+        let bindings = config
+            .iter()
+            .map(|(chord, name)| Ok((Self::parse_chord(chord)?, name.parse::<Command>()?)))
+            .collect::<Result<_, String>>()?;
+
+        Ok(KeyBindings { bindings })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bindings(chords: &[&str]) -> KeyBindings {
+        let config = chords
+            .iter()
+            .map(|chord| ((*chord).to_string(), "quit".to_string()))
+            .collect::<HashMap<_, _>>();
+        KeyBindings::try_from(config).unwrap()
+    }
+
+    fn is_bound(bindings: &KeyBindings, code: KeyCode, modifiers: KeyModifiers) -> bool {
+        bindings
+            .command_for(KeyEvent::new(code, modifiers))
+            .is_some()
+    }
+
+    #[test]
+    fn shifted_letters_are_distinct_from_lowercase() {
+        let alt_shift = KeyModifiers::ALT | KeyModifiers::SHIFT;
+
+        let lowercase = bindings(&["alt+n"]);
+        assert!(is_bound(&lowercase, KeyCode::Char('n'), KeyModifiers::ALT));
+        assert!(!is_bound(&lowercase, KeyCode::Char('N'), alt_shift));
+        assert!(!is_bound(&lowercase, KeyCode::Char('N'), KeyModifiers::ALT));
+
+        for chord in ["alt+N", "alt+shift+n", "ALT+SHIFT+N"] {
+            let shifted = bindings(&[chord]);
+            assert!(
+                !is_bound(&shifted, KeyCode::Char('n'), KeyModifiers::ALT),
+                "{chord}"
+            );
+            // Terminals differ in whether Shift is reported along with the upper case letter
+            assert!(is_bound(&shifted, KeyCode::Char('N'), alt_shift), "{chord}");
+            assert!(
+                is_bound(&shifted, KeyCode::Char('N'), KeyModifiers::ALT),
+                "{chord}"
+            );
+            assert!(is_bound(&shifted, KeyCode::Char('n'), alt_shift), "{chord}");
+        }
+    }
+
+    #[test]
+    fn shifted_symbols_and_tab_match_how_terminals_report_them() {
+        let bindings = bindings(&["ctrl+!", "shift+tab", "ctrl++"]);
+
+        for (code, modifiers) in [
+            (KeyCode::Char('!'), KeyModifiers::CONTROL),
+            (
+                KeyCode::Char('!'),
+                KeyModifiers::CONTROL | KeyModifiers::SHIFT,
+            ),
+            (KeyCode::BackTab, KeyModifiers::NONE),
+            (KeyCode::BackTab, KeyModifiers::SHIFT),
+            (KeyCode::Char('+'), KeyModifiers::CONTROL),
+        ] {
+            assert!(
+                is_bound(&bindings, code, modifiers),
+                "{code:?} {modifiers:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn named_keys_and_modifiers_ignore_case() {
+        let bindings = bindings(&["Ctrl+PageUp", "F5"]);
+
+        assert!(is_bound(&bindings, KeyCode::PageUp, KeyModifiers::CONTROL));
+        assert!(is_bound(&bindings, KeyCode::F(5), KeyModifiers::NONE));
+        assert!(KeyBindings::parse_chord("ctrl+f25").is_err());
+        assert!(KeyBindings::parse_chord("hyper+a").is_err());
+    }
+}
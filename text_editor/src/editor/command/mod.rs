@@ -1,10 +1,19 @@
+pub mod clipboard;
 pub mod edit;
+pub mod key_bindings;
+pub mod mouse;
 pub mod movement;
+pub mod selection;
 pub mod system;
 
+use std::str::FromStr;
+
+use clipboard::Clipboard;
 use crossterm::event::Event;
 use edit::Edit;
+use mouse::Mouse;
 use movement::Move;
+use selection::Select;
 use system::System;
 
 use crate::editor::size::Size;
@@ -14,6 +23,9 @@ pub enum Command {
     Move(Move),
     Edit(Edit),
     System(System),
+    Select(Select),
+    Clipboard(Clipboard),
+    Mouse(Mouse),
 }
 
 impl TryFrom<Event> for Command {
@@ -24,9 +36,13 @@ impl TryFrom<Event> for Command {
             Event::Key(key_event) => System::try_from(key_event)
                 .map(Command::System)
                 .or_else(|_| Move::try_from(key_event).map(Command::Move))
+                .or_else(|_| Select::try_from(key_event).map(Command::Select))
+                .or_else(|_| Clipboard::try_from(key_event).map(Command::Clipboard))
                 .or_else(|_| Edit::try_from(key_event).map(Command::Edit))
                 .map_err(|_err| format!("Event not supported: {key_event:?}")),
 
+            Event::Mouse(mouse_event) => Mouse::try_from(mouse_event).map(Command::Mouse),
+
             Event::Resize(width_u16, heigth_16) => {
                 let height = heigth_16 as usize;
                 let width = width_u16 as usize;
@@ -41,3 +57,17 @@ impl TryFrom<Event> for Command {
         }
     }
 }
+
+/// Commands are named in snake case, e.g. `save`, `word_left` or `select_page_down`
+impl FromStr for Command {
+    type Err = String;
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        System::from_str(name)
+            .map(Command::System)
+            .or_else(|_| Move::from_str(name).map(Command::Move))
+            .or_else(|_| Select::from_str(name).map(Command::Select))
+            .or_else(|_| Clipboard::from_str(name).map(Command::Clipboard))
+            .or_else(|_| Edit::from_str(name).map(Command::Edit))
+    }
+}
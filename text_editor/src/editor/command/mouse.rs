@@ -0,0 +1,67 @@
+use crossterm::event::{KeyModifiers, MouseButton, MouseEvent, MouseEventKind};
+
+use crate::editor::caret_position::CaretPosition;
+
+/// Lines moved by a single step of the wheel
+pub const SCROLL_STEP: usize = 3;
+
+/// Positions are screen positions, the view turns them into locations in the buffer
+#[derive(Clone, Copy)]
+pub enum Mouse {
+    Click(CaretPosition),
+    /// Extends the selection, Shift+Click does the same
+    Drag(CaretPosition),
+    /// Position picks the split to scroll
+    ScrollUp(CaretPosition),
+    ScrollDown(CaretPosition),
+}
+
+impl Mouse {
+    pub fn position(self) -> CaretPosition {
+        match self {
+            Mouse::Click(position)
+            | Mouse::Drag(position)
+            | Mouse::ScrollUp(position)
+            | Mouse::ScrollDown(position) => position,
+        }
+    }
+
+    /// Same event at a position relative to the given origin
+    pub fn relative_to(self, origin: CaretPosition) -> Self {
+        match self {
+            Mouse::Click(position) => Mouse::Click(position.saturating_sub(origin)),
+            Mouse::Drag(position) => Mouse::Drag(position.saturating_sub(origin)),
+            Mouse::ScrollUp(position) => Mouse::ScrollUp(position.saturating_sub(origin)),
+            Mouse::ScrollDown(position) => Mouse::ScrollDown(position.saturating_sub(origin)),
+        }
+    }
+}
+
+impl TryFrom<MouseEvent> for Mouse {
+    type Error = String;
+
+    fn try_from(event: MouseEvent) -> Result<Self, Self::Error> {
+        let MouseEvent {
+            kind,
+            column,
+            row,
+            modifiers,
+        } = event;
+
+        let position = CaretPosition {
+            column: column as usize,
+            row: row as usize,
+        };
+
+        match kind {
+            MouseEventKind::Down(MouseButton::Left) if modifiers.contains(KeyModifiers::SHIFT) => {
+                Ok(Self::Drag(position))
+            }
+            MouseEventKind::Down(MouseButton::Left) => Ok(Self::Click(position)),
+            MouseEventKind::Drag(MouseButton::Left) => Ok(Self::Drag(position)),
+            MouseEventKind::ScrollUp => Ok(Self::ScrollUp(position)),
+            MouseEventKind::ScrollDown => Ok(Self::ScrollDown(position)),
+            _ => Err(format!("Mouse event not supported: {kind:?}")),
+        }
+    }
+}
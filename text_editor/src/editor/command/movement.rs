@@ -1,3 +1,5 @@
+use std::str::FromStr;
+
 use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
 
 #[derive(Clone, Copy)]
@@ -10,6 +12,21 @@ pub enum Move {
     End,
     PageUp,
     PageDown,
+    WordLeft,
+    WordRight,
+    DocumentStart,
+    DocumentEnd,
+    MatchingBracket,
+}
+
+impl Move {
+    /// Lands on a fixed place, so it never fails even when the caret is already there
+    pub fn is_jump(self) -> bool {
+        matches!(
+            self,
+            Self::Home | Self::End | Self::DocumentStart | Self::DocumentEnd
+        )
+    }
 }
 
 impl TryFrom<KeyEvent> for Move {
@@ -20,22 +37,49 @@ impl TryFrom<KeyEvent> for Move {
             code, modifiers, ..
         } = event;
 
-        if modifiers != KeyModifiers::NONE {
-            return Err(format!(
+        match (code, modifiers) {
+            (KeyCode::Up, KeyModifiers::NONE) => Ok(Self::Up),
+            (KeyCode::Down, KeyModifiers::NONE) => Ok(Self::Down),
+            (KeyCode::Left, KeyModifiers::NONE) => Ok(Self::Left),
+            (KeyCode::Right, KeyModifiers::NONE) => Ok(Self::Right),
+            (KeyCode::PageUp, KeyModifiers::NONE) => Ok(Self::PageUp),
+            (KeyCode::PageDown, KeyModifiers::NONE) => Ok(Self::PageDown),
+            (KeyCode::Home, KeyModifiers::NONE) => Ok(Self::Home),
+            (KeyCode::End, KeyModifiers::NONE) => Ok(Self::End),
+
+            (KeyCode::Left, KeyModifiers::CONTROL) => Ok(Self::WordLeft),
+            (KeyCode::Right, KeyModifiers::CONTROL) => Ok(Self::WordRight),
+            (KeyCode::Home, KeyModifiers::CONTROL) => Ok(Self::DocumentStart),
+            (KeyCode::End, KeyModifiers::CONTROL) => Ok(Self::DocumentEnd),
+            // Terminals send Ctrl+] as Ctrl+5
+            (KeyCode::Char(']' | '5'), KeyModifiers::CONTROL) => Ok(Self::MatchingBracket),
+
+            _ => Err(format!(
                 "Unsupported key code {code:?} or modifier {modifiers:?}"
-            ));
+            )),
         }
+    }
+}
 
-        match (code, modifiers) {
-            (KeyCode::Up, _) => Ok(Self::Up),
-            (KeyCode::Down, _) => Ok(Self::Down),
-            (KeyCode::Left, _) => Ok(Self::Left),
-            (KeyCode::Right, _) => Ok(Self::Right),
-            (KeyCode::PageUp, _) => Ok(Self::PageUp),
-            (KeyCode::PageDown, _) => Ok(Self::PageDown),
-            (KeyCode::Home, _) => Ok(Self::Home),
-            (KeyCode::End, _) => Ok(Self::End),
-            _ => Err(format!("Movement key code not supported: {code:?}")),
+impl FromStr for Move {
+    type Err = String;
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        match name {
+            "left" => Ok(Self::Left),
+            "right" => Ok(Self::Right),
+            "up" => Ok(Self::Up),
+            "down" => Ok(Self::Down),
+            "home" => Ok(Self::Home),
+            "end" => Ok(Self::End),
+            "page_up" => Ok(Self::PageUp),
+            "page_down" => Ok(Self::PageDown),
+            "word_left" => Ok(Self::WordLeft),
+            "word_right" => Ok(Self::WordRight),
+            "document_start" => Ok(Self::DocumentStart),
+            "document_end" => Ok(Self::DocumentEnd),
+            "matching_bracket" => Ok(Self::MatchingBracket),
+            _ => Err(format!("Unknown command '{name}'")),
         }
     }
 }
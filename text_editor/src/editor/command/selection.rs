@@ -0,0 +1,49 @@
+use std::str::FromStr;
+
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+use super::movement::Move;
+
+#[derive(Clone, Copy)]
+pub enum Select {
+    Extend(Move),
+    All,
+}
+
+impl TryFrom<KeyEvent> for Select {
+    type Error = String;
+
+    fn try_from(event: KeyEvent) -> Result<Self, Self::Error> {
+        let KeyEvent {
+            code, modifiers, ..
+        } = event;
+
+        match (code, modifiers) {
+            (KeyCode::Char('a'), KeyModifiers::CONTROL) => Ok(Self::All),
+            (_, modifiers) if modifiers.contains(KeyModifiers::SHIFT) => Move::try_from(KeyEvent {
+                modifiers: modifiers.difference(KeyModifiers::SHIFT),
+                ..event
+            })
+            .map(Self::Extend),
+            _ => Err(format!(
+                "Unsupported key code {code:?} or modifier {modifiers:?}"
+            )),
+        }
+    }
+}
+
+/// Any movement prefixed with `select_` extends the selection
+impl FromStr for Select {
+    type Err = String;
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        match name {
+            "select_all" => Ok(Self::All),
+            _ => name
+                .strip_prefix("select_")
+                .and_then(|movement| movement.parse().ok())
+                .map(Self::Extend)
+                .ok_or_else(|| format!("Unknown command '{name}'")),
+        }
+    }
+}
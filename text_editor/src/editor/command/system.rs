@@ -1,3 +1,5 @@
+use std::str::FromStr;
+
 use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
 
 use crate::editor::size::Size;
@@ -6,9 +8,38 @@ use crate::editor::size::Size;
 pub enum System {
     Resize(Size),
     Save,
+    Reload,
     Quit,
     Abort,
     Search,
+    Replace,
+    ToggleCaseInsensitive,
+    ToggleWholeWord,
+    ToggleRegex,
+    NextBuffer,
+    PreviousBuffer,
+    BufferList,
+    ToggleLineEnding,
+    ToggleTrailingNewline,
+    ToggleBom,
+    ToggleSoftWrap,
+    CycleLineNumbers,
+    GoToLine,
+    CycleTheme,
+    CommandLine,
+    /// Splits the focused window into one above the other
+    SplitHorizontal,
+    /// Splits the focused window into two side by side
+    SplitVertical,
+    CloseSplit,
+    FocusNextSplit,
+    GrowSplit,
+    ShrinkSplit,
+    /// Starts recording into a register, or stops the recording in progress
+    RecordMacro,
+    PlayMacro,
+    /// Index into the recent files listed on the welcome screen
+    OpenRecent(usize),
 }
 
 impl TryFrom<KeyEvent> for System {
@@ -22,10 +53,86 @@ impl TryFrom<KeyEvent> for System {
         match (code, modifiers) {
             (KeyCode::Char('q'), KeyModifiers::CONTROL) => Ok(Self::Quit),
             (KeyCode::Char('s'), KeyModifiers::CONTROL) => Ok(Self::Save),
+            (KeyCode::F(5), KeyModifiers::NONE) => Ok(Self::Reload),
             (KeyCode::Char('f'), KeyModifiers::CONTROL) => Ok(Self::Search),
+            (KeyCode::Char('r'), KeyModifiers::CONTROL) => Ok(Self::Replace),
+            (KeyCode::Char('c'), KeyModifiers::ALT) => Ok(Self::ToggleCaseInsensitive),
+            (KeyCode::Char('w'), KeyModifiers::ALT) => Ok(Self::ToggleWholeWord),
+            (KeyCode::Char('r'), KeyModifiers::ALT) => Ok(Self::ToggleRegex),
             (KeyCode::Esc, KeyModifiers::NONE) => Ok(Self::Abort),
 
+            (KeyCode::PageDown, KeyModifiers::CONTROL) => Ok(Self::NextBuffer),
+            (KeyCode::PageUp, KeyModifiers::CONTROL) => Ok(Self::PreviousBuffer),
+            (KeyCode::Char('b'), KeyModifiers::CONTROL) => Ok(Self::BufferList),
+            (KeyCode::Char('g'), KeyModifiers::CONTROL) => Ok(Self::GoToLine),
+            (KeyCode::Char('e'), KeyModifiers::CONTROL) => Ok(Self::CommandLine),
+
+            (KeyCode::Char('l'), KeyModifiers::ALT) => Ok(Self::ToggleLineEnding),
+            (KeyCode::Char('n'), KeyModifiers::ALT) => Ok(Self::ToggleTrailingNewline),
+            (KeyCode::Char('b'), KeyModifiers::ALT) => Ok(Self::ToggleBom),
+            (KeyCode::Char('z'), KeyModifiers::ALT) => Ok(Self::ToggleSoftWrap),
+            (KeyCode::Char('g'), KeyModifiers::ALT) => Ok(Self::CycleLineNumbers),
+            (KeyCode::Char('t'), KeyModifiers::ALT) => Ok(Self::CycleTheme),
+
+            (KeyCode::Char('s'), KeyModifiers::ALT) => Ok(Self::SplitHorizontal),
+            (KeyCode::Char('v'), KeyModifiers::ALT) => Ok(Self::SplitVertical),
+            (KeyCode::Char('q'), KeyModifiers::ALT) => Ok(Self::CloseSplit),
+            (KeyCode::Char('o'), KeyModifiers::ALT) => Ok(Self::FocusNextSplit),
+            (KeyCode::Char('='), KeyModifiers::ALT) => Ok(Self::GrowSplit),
+            (KeyCode::Char('-'), KeyModifiers::ALT) => Ok(Self::ShrinkSplit),
+
+            (KeyCode::Char('m'), KeyModifiers::ALT) => Ok(Self::RecordMacro),
+            (KeyCode::Char('p'), KeyModifiers::ALT) => Ok(Self::PlayMacro),
+            (KeyCode::Char(digit @ '1'..='9'), KeyModifiers::ALT) => {
+                let idx = u32::from(digit).saturating_sub(u32::from('1'));
+                Ok(Self::OpenRecent(usize::try_from(idx).unwrap_or_default()))
+            }
+
             _ => Err(format!("Movement key code not supported: {code:?}")),
         }
     }
 }
+
+impl FromStr for System {
+    type Err = String;
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        match name {
+            "save" => Ok(Self::Save),
+            "reload" => Ok(Self::Reload),
+            "quit" => Ok(Self::Quit),
+            "abort" => Ok(Self::Abort),
+            "search" => Ok(Self::Search),
+            "replace" => Ok(Self::Replace),
+            "toggle_case_insensitive" => Ok(Self::ToggleCaseInsensitive),
+            "toggle_whole_word" => Ok(Self::ToggleWholeWord),
+            "toggle_regex" => Ok(Self::ToggleRegex),
+            "next_buffer" => Ok(Self::NextBuffer),
+            "previous_buffer" => Ok(Self::PreviousBuffer),
+            "buffer_list" => Ok(Self::BufferList),
+            "toggle_line_ending" => Ok(Self::ToggleLineEnding),
+            "toggle_trailing_newline" => Ok(Self::ToggleTrailingNewline),
+            "toggle_bom" => Ok(Self::ToggleBom),
+            "toggle_soft_wrap" => Ok(Self::ToggleSoftWrap),
+            "cycle_line_numbers" => Ok(Self::CycleLineNumbers),
+            "go_to_line" => Ok(Self::GoToLine),
+            "cycle_theme" => Ok(Self::CycleTheme),
+            "command_line" => Ok(Self::CommandLine),
+            "split_horizontal" => Ok(Self::SplitHorizontal),
+            "split_vertical" => Ok(Self::SplitVertical),
+            "close_split" => Ok(Self::CloseSplit),
+            "focus_next_split" => Ok(Self::FocusNextSplit),
+            "grow_split" => Ok(Self::GrowSplit),
+            "shrink_split" => Ok(Self::ShrinkSplit),
+            "record_macro" => Ok(Self::RecordMacro),
+            "play_macro" => Ok(Self::PlayMacro),
+            // `open_recent_1` to `open_recent_9`
+            _ => name
+                .strip_prefix("open_recent_")
+                .and_then(|number| number.parse::<usize>().ok())
+                .filter(|number| (1..=9).contains(number))
+                .map(|number| Self::OpenRecent(number.saturating_sub(1)))
+                .ok_or_else(|| format!("Unknown command '{name}'")),
+        }
+    }
+}
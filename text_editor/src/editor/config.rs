@@ -0,0 +1,98 @@
+use std::collections::HashMap;
+use std::fs;
+use std::io::ErrorKind;
+use std::path::Path;
+
+use serde::Deserialize;
+
+use super::{
+    command::key_bindings::KeyBindings,
+    paths,
+    terminal::{color_support::ColorSupport, palette::ThemeName, theme::Theme},
+    ui_component::view::{
+        ViewSettings,
+        gutter::LineNumbers,
+        highlighter::file_type::FileType,
+        indentation::{Indentation, IndentationSettings},
+    },
+};
+
+const CONFIG_FILE: &str = "config.toml";
+
+/// Contents of `~/.config/editr/config.toml`, every section is optional
+#[derive(Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    pub editor: EditorConfig,
+    /// Indentation per file type, e.g. `rust = 4` or `c = "tabs"`
+    pub indentation: HashMap<FileType, Indentation>,
+    /// Key chord to command name, e.g. `"ctrl+w" = "quit"`
+    pub keys: KeyBindings,
+    /// Overrides per annotation type, e.g. `keyword = { foreground = "#6495ed", bold = true }`
+    pub theme: Theme,
+}
+
+#[derive(Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct EditorConfig {
+    pub keep_backup: bool,
+    pub soft_wrap: bool,
+    pub line_numbers: LineNumbers,
+    /// Indentation of file types without own or built-in one
+    pub indentation: Option<Indentation>,
+    pub theme: ThemeName,
+    /// Detected from the environment when missing
+    pub colors: Option<ColorSupport>,
+}
+
+impl Config {
+    /// Missing config file is not an error, the defaults are used
+    pub fn load() -> Result<Config, String> {
+        let Some(path) = paths::config_dir().map(|dir| dir.join(CONFIG_FILE)) else {
+            return Ok(Config::default());
+        };
+
+        let content = match fs::read_to_string(&path) {
+            Ok(content) => content,
+            Err(err) if err.kind() == ErrorKind::NotFound => return Ok(Config::default()),
+            Err(err) => return Err(format!("Could not read {}: {err}", path.display())),
+        };
+
+        toml::from_str(&content).map_err(|err| describe_toml_error(&path, &content, &err))
+    }
+
+    /// Named theme with the overrides of the `[theme]` section
+    pub fn take_theme(&mut self) -> Theme {
+        let mut theme = std::mem::take(&mut self.theme);
+        theme.name = self.editor.theme;
+        theme.color_support = self.editor.colors.unwrap_or_else(ColorSupport::detect);
+        theme
+    }
+
+    pub fn view_settings(&self) -> ViewSettings {
+        ViewSettings {
+            soft_wrap: self.editor.soft_wrap,
+            line_numbers: self.editor.line_numbers,
+            indentation: IndentationSettings {
+                local: None,
+                per_file_type: self.indentation.clone(),
+                default: self.editor.indentation,
+            },
+        }
+    }
+}
+
+/// `path:line: message`, the line is left out when the error has no position
+pub fn describe_toml_error(path: &Path, content: &str, err: &toml::de::Error) -> String {
+    let message = err.message().trim_end();
+    match err.span() {
+        Some(span) => {
+            let line = content
+                .get(..span.start)
+                .map_or(0, |before| before.matches('\n').count())
+                .saturating_add(1);
+            format!("{}:{line}: {message}", path.display())
+        }
+        None => format!("{}: {message}", path.display()),
+    }
+}
@@ -0,0 +1,189 @@
+use std::collections::HashMap;
+use std::fs;
+
+use super::{line::LineIdx, paths::is_same_file, ui_component::view::gutter::Sign};
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Severity {
+    Error,
+    Warning,
+    /// Explains another diagnostic, gets no sign of its own
+    Note,
+}
+
+/// Problem reported by a compiler or linter on a line of a file
+struct Diagnostic {
+    file: String,
+    line_idx: LineIdx,
+    is_error: bool,
+}
+
+/// Diagnostics read from compiler output, shown as signs in the gutter of matching buffers
+#[derive(Default)]
+pub struct Diagnostics {
+    list: Vec<Diagnostic>,
+}
+
+impl Diagnostics {
+    pub fn load(path: &str) -> Result<Self, std::io::Error> {
+        Ok(Self::parse(&fs::read_to_string(path)?))
+    }
+
+    /// Understands `file:line[:column]: severity: message` as printed by gcc, clang or `rustc --error-format=short`,
+    /// and the `--> file:line:column` lines following `error:` and `warning:` in the default rustc output
+    pub fn parse(output: &str) -> Self {
+        let mut list = Vec::new();
+        // Severity of a rustc header still waiting for its `-->` line
+        let mut pending = None;
+
+        for line in output.lines() {
+            let line = line.trim_start();
+
+            let (location, severity) = if let Some(location) = line.strip_prefix("--> ") {
+                (split_location(location), pending.take())
+            } else if let Some(location) = split_location(line) {
+                // Lines without a severity are taken as errors
+                let severity = Severity::of(location.2).unwrap_or(Severity::Error);
+                (Some(location), Some(severity))
+            } else {
+                pending = Severity::of(line).or(pending);
+                continue;
+            };
+
+            if let Some((file, line_idx, _)) = location
+                && let Some(severity @ (Severity::Error | Severity::Warning)) = severity
+            {
+                list.push(Diagnostic {
+                    file: file.to_string(),
+                    line_idx,
+                    is_error: severity == Severity::Error,
+                });
+            }
+        }
+
+        Diagnostics { list }
+    }
+
+    pub fn len(&self) -> usize {
+        self.list.len()
+    }
+
+    /// Signs for the lines of given file, an error wins over a warning on the same line
+    pub fn signs_for(&self, file_name: &str) -> HashMap<LineIdx, Sign> {
+        let mut signs = HashMap::new();
+
+        for diagnostic in self
+            .list
+            .iter()
+            .filter(|diagnostic| is_same_file(&diagnostic.file, file_name))
+        {
+            let sign = if diagnostic.is_error {
+                Sign::ERROR
+            } else {
+                Sign::WARNING
+            };
+
+            signs
+                .entry(diagnostic.line_idx)
+                .and_modify(|existing: &mut Sign| {
+                    if diagnostic.is_error {
+                        *existing = sign;
+                    }
+                })
+                .or_insert(sign);
+        }
+
+        signs
+    }
+}
+
+impl Severity {
+    /// Taken from the start of the message, e.g. `error[E0308]: ...`
+    fn of(message: &str) -> Option<Self> {
+        let message = message.trim_start().to_ascii_lowercase();
+
+        if message.starts_with("error") || message.starts_with("fatal") {
+            Some(Severity::Error)
+        } else if message.starts_with("warning") {
+            Some(Severity::Warning)
+        } else if message.starts_with("note") || message.starts_with("help") {
+            Some(Severity::Note)
+        } else {
+            None
+        }
+    }
+}
+
+/// Splits `file:line[:column][: rest]`, the line number is 1-based
+fn split_location(text: &str) -> Option<(&str, LineIdx, &str)> {
+    let (file, rest) = text.split_once(':')?;
+    let digits = rest.len()
+        - rest
+            .trim_start_matches(|chr: char| chr.is_ascii_digit())
+            .len();
+    let line_idx = rest[..digits].parse::<usize>().ok()?.checked_sub(1)?;
+
+    let rest = &rest[digits..];
+    if file.is_empty() || !(rest.is_empty() || rest.starts_with(':')) {
+        return None;
+    }
+
+    // Column is optional and not needed for a sign
+    let rest = rest.strip_prefix(':').unwrap_or(rest);
+    let rest = rest.trim_start_matches(|chr: char| chr.is_ascii_digit());
+    let message = rest.strip_prefix(':').unwrap_or(rest);
+
+    Some((file, line_idx, message))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lines_of(diagnostics: &Diagnostics, file_name: &str) -> Vec<(LineIdx, char)> {
+        let mut lines: Vec<(LineIdx, char)> = diagnostics
+            .signs_for(file_name)
+            .into_iter()
+            .map(|(line_idx, sign)| (line_idx, sign.symbol))
+            .collect();
+        lines.sort_unstable();
+        lines
+    }
+
+    #[test]
+    fn short_compiler_output_is_parsed() {
+        let diagnostics = Diagnostics::parse(
+            "main.c:3:5: error: expected ';'\n\
+             main.c:7: warning: unused variable\n\
+             main.c:7:1: note: declared here\n\
+             other.c:1:1: error: elsewhere\n\
+             In file included from main.c:2:\n\
+             2 errors generated.",
+        );
+
+        assert_eq!(lines_of(&diagnostics, "main.c"), [(2, 'E'), (6, 'W')]);
+        assert_eq!(lines_of(&diagnostics, "other.c"), [(0, 'E')]);
+    }
+
+    #[test]
+    fn rustc_output_takes_severity_from_the_header() {
+        let diagnostics = Diagnostics::parse(
+            "warning: unused import\n \
+             --> src/main.rs:1:5\n\
+             error[E0308]: mismatched types\n  \
+             --> src/main.rs:4:9\n   \
+             ::: src/lib.rs:9:1\n\
+             error: aborting due to 1 previous error",
+        );
+
+        assert_eq!(lines_of(&diagnostics, "src/main.rs"), [(0, 'W'), (3, 'E')]);
+        assert_eq!(diagnostics.len(), 2);
+    }
+
+    #[test]
+    fn error_wins_over_warning_on_the_same_line() {
+        let diagnostics =
+            Diagnostics::parse("a.c:1:1: warning: w\na.c:1:2: error: e\na.c:1:3: warning: w");
+        assert_eq!(lines_of(&diagnostics, "a.c"), [(0, 'E')]);
+    }
+}
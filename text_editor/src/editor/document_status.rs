@@ -1,4 +1,7 @@
-use super::{caret_position::CaretPosition, ui_component::view::highlighter::file_type::FileType};
+use super::{
+    caret_position::CaretPosition,
+    ui_component::view::{file_format::FileFormat, highlighter::file_type::FileType},
+};
 
 #[derive(Default)]
 pub struct DocumentStatus {
@@ -6,7 +9,11 @@ pub struct DocumentStatus {
     pub file_name: Option<String>,
     pub number_of_lines: usize,
     pub is_modified: bool,
+    pub is_read_only: bool,
     pub file_type: FileType,
+    pub file_format: FileFormat,
+    pub buffer_index: usize,
+    pub buffer_count: usize,
 }
 
 impl DocumentStatus {
@@ -18,6 +25,26 @@ impl DocumentStatus {
         }
     }
 
+    pub fn read_only_indicator_to_string(&self) -> String {
+        if self.is_read_only {
+            String::from("[read-only]")
+        } else {
+            String::new()
+        }
+    }
+
+    pub fn buffer_indicator_to_string(&self) -> String {
+        if self.buffer_count > 1 {
+            format!(
+                "[{}/{}] ",
+                self.buffer_index.saturating_add(1),
+                self.buffer_count
+            )
+        } else {
+            String::new()
+        }
+    }
+
     pub fn line_count_to_string(&self) -> String {
         format!("{} lines", self.number_of_lines)
     }
@@ -0,0 +1,554 @@
+use std::fmt::{self, Display};
+use std::fs;
+use std::ops::RangeInclusive;
+use std::str::FromStr;
+
+use serde::de::DeserializeOwned;
+
+use super::{
+    layout::Direction,
+    line::LineIdx,
+    terminal::palette::ThemeName,
+    ui_component::view::{gutter::LineNumbers, indentation::Indentation, location::Location},
+};
+
+/// Full names of the commands, completed when nothing else is typed yet
+const COMMAND_NAMES: [&str; 10] = [
+    "close",
+    "diagnostics",
+    "edit",
+    "goto",
+    "quit",
+    "set",
+    "split",
+    "vsplit",
+    "write",
+    "wq",
+];
+
+const OPTION_NAMES: [&str; 8] = [
+    "case_insensitive",
+    "indentation",
+    "keep_backup",
+    "line_numbers",
+    "regex",
+    "soft_wrap",
+    "theme",
+    "whole_word",
+];
+
+/// Line in a range of `s`, numbers are 1-based like everywhere else in the UI
+#[derive(Clone, Copy)]
+pub enum Address {
+    Current,
+    Last,
+    Number(usize),
+}
+
+impl Address {
+    fn resolve(self, current: LineIdx, number_of_lines: usize) -> LineIdx {
+        match self {
+            Address::Current => current,
+            Address::Last => number_of_lines.saturating_sub(1),
+            Address::Number(number) => number.saturating_sub(1),
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+pub struct LineRange {
+    pub start: Address,
+    pub end: Address,
+}
+
+impl LineRange {
+    pub fn resolve(self, current: LineIdx, number_of_lines: usize) -> RangeInclusive<LineIdx> {
+        let start = self.start.resolve(current, number_of_lines);
+        let end = self.end.resolve(current, number_of_lines);
+
+        start.min(end)..=start.max(end)
+    }
+}
+
+#[derive(Clone, Copy)]
+pub enum SetOption {
+    SoftWrap(bool),
+    LineNumbers(LineNumbers),
+    Indentation(Indentation),
+    Theme(ThemeName),
+    CaseInsensitive(bool),
+    WholeWord(bool),
+    Regex(bool),
+    KeepBackup(bool),
+}
+
+impl Display for SetOption {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SetOption::SoftWrap(on) => write!(f, "soft_wrap={on}"),
+            SetOption::LineNumbers(line_numbers) => write!(f, "line_numbers={line_numbers}"),
+            SetOption::Indentation(Indentation::Tabs) => write!(f, "indentation=tabs"),
+            SetOption::Indentation(Indentation::Spaces(width)) => {
+                write!(f, "indentation={width}")
+            }
+            SetOption::Theme(name) => write!(f, "theme={name}"),
+            SetOption::CaseInsensitive(on) => write!(f, "case_insensitive={on}"),
+            SetOption::WholeWord(on) => write!(f, "whole_word={on}"),
+            SetOption::Regex(on) => write!(f, "regex={on}"),
+            SetOption::KeepBackup(on) => write!(f, "keep_backup={on}"),
+        }
+    }
+}
+
+/// Command typed on the command line
+pub enum ExCommand {
+    /// Saves under a new name when one is given, `force` overwrites a file of that name
+    Write {
+        file_name: Option<String>,
+        force: bool,
+    },
+    Quit {
+        force: bool,
+    },
+    WriteQuit,
+    Edit(String),
+    Set(SetOption),
+    GoTo(Location),
+    /// Another view of the current buffer next to it
+    Split(Direction),
+    Close,
+    /// Gutter signs from a file of compiler output, without a file they are removed
+    Diagnostics(Option<String>),
+    /// `[range]s/find/replacement/[flags]`, find is a regular expression and replacement can refer to groups with `$1`
+    Substitute {
+        range: LineRange,
+        find: String,
+        replacement: String,
+        every_match: bool,
+        case_insensitive: bool,
+    },
+}
+
+impl FromStr for ExCommand {
+    type Err = String;
+
+    fn from_str(line: &str) -> Result<Self, Self::Err> {
+        let line = line.trim();
+
+        if let Some(location) = Location::parse(line) {
+            return Ok(ExCommand::GoTo(location));
+        }
+
+        let (range, rest) = Self::parse_range(line)?;
+        if let Some(substitution) = rest.strip_prefix('s')
+            && substitution
+                .chars()
+                .next()
+                .is_some_and(|delimiter| !delimiter.is_alphanumeric())
+        {
+            return Self::parse_substitute(range, substitution);
+        }
+        if range.is_some() {
+            return Err(format!("Range is only supported by s, got '{line}'"));
+        }
+
+        let (name, argument) = line
+            .split_once(char::is_whitespace)
+            .map_or((line, ""), |(name, argument)| (name, argument.trim()));
+
+        match (name, argument) {
+            ("w" | "write", "") => Ok(ExCommand::Write {
+                file_name: None,
+                force: false,
+            }),
+            ("w" | "write", file) => Ok(ExCommand::Write {
+                file_name: Some(file.to_string()),
+                force: false,
+            }),
+            ("w!" | "write!", "") => Ok(ExCommand::Write {
+                file_name: None,
+                force: true,
+            }),
+            ("w!" | "write!", file) => Ok(ExCommand::Write {
+                file_name: Some(file.to_string()),
+                force: true,
+            }),
+            ("q" | "quit", "") => Ok(ExCommand::Quit { force: false }),
+            ("q!" | "quit!", "") => Ok(ExCommand::Quit { force: true }),
+            ("wq" | "x", "") => Ok(ExCommand::WriteQuit),
+            ("e" | "edit", "") => Err(String::from("Missing file name, usage: e file")),
+            ("e" | "edit", file) => Ok(ExCommand::Edit(file.to_string())),
+            ("set", option) => Self::parse_option(option).map(ExCommand::Set),
+            ("sp" | "split", "") => Ok(ExCommand::Split(Direction::Horizontal)),
+            ("vs" | "vsplit", "") => Ok(ExCommand::Split(Direction::Vertical)),
+            ("clo" | "close", "") => Ok(ExCommand::Close),
+            ("diagnostics", "") => Ok(ExCommand::Diagnostics(None)),
+            ("diagnostics", file) => Ok(ExCommand::Diagnostics(Some(file.to_string()))),
+            ("goto", target) => Location::parse(target)
+                .map(ExCommand::GoTo)
+                .ok_or_else(|| format!("Invalid line '{target}', expected line[:column]")),
+            _ => Err(format!("Unknown command '{line}'")),
+        }
+    }
+}
+
+impl ExCommand {
+    /// `%` is the whole buffer, otherwise one or two of `.`, `$` and line numbers separated by a comma
+    fn parse_range(line: &str) -> Result<(Option<LineRange>, &str), String> {
+        if let Some(rest) = line.strip_prefix('%') {
+            let range = LineRange {
+                start: Address::Number(1),
+                end: Address::Last,
+            };
+            return Ok((Some(range), rest));
+        }
+
+        let end = line
+            .find(|chr: char| !(chr.is_ascii_digit() || matches!(chr, '.' | '$' | ',')))
+            .unwrap_or(line.len());
+        let (range, rest) = line.split_at(end);
+
+        if range.is_empty() {
+            return Ok((None, rest));
+        }
+
+        let (start, end) = range.split_once(',').unwrap_or((range, range));
+        let range = LineRange {
+            start: Self::parse_address(start)?,
+            end: Self::parse_address(end)?,
+        };
+
+        Ok((Some(range), rest))
+    }
+
+    fn parse_address(address: &str) -> Result<Address, String> {
+        match address {
+            "." => Ok(Address::Current),
+            "$" => Ok(Address::Last),
+            _ => address
+                .parse()
+                .ok()
+                .filter(|number| *number > 0)
+                .map(Address::Number)
+                .ok_or_else(|| format!("Invalid line '{address}' in range")),
+        }
+    }
+
+    /// Delimiter is the first character, it can be escaped with a backslash inside find and replacement
+    fn parse_substitute(range: Option<LineRange>, substitution: &str) -> Result<Self, String> {
+        let mut chars = substitution.chars();
+        let delimiter = chars.next().unwrap_or('/');
+
+        let mut parts = Vec::new();
+        let mut part = String::new();
+        while let Some(chr) = chars.next() {
+            match chr {
+                '\\' => match chars.next() {
+                    Some(escaped) if escaped == delimiter => part.push(escaped),
+                    Some(escaped) => {
+                        part.push('\\');
+                        part.push(escaped);
+                    }
+                    None => part.push('\\'),
+                },
+                _ if chr == delimiter => parts.push(std::mem::take(&mut part)),
+                _ => part.push(chr),
+            }
+        }
+        parts.push(part);
+
+        let mut parts = parts.into_iter();
+        let (Some(find), replacement, flags) = (parts.next(), parts.next(), parts.next()) else {
+            return Err(String::from("Usage: [range]s/find/replacement/[g][i]"));
+        };
+        if find.is_empty() || parts.next().is_some() {
+            return Err(String::from("Usage: [range]s/find/replacement/[g][i]"));
+        }
+
+        let flags = flags.unwrap_or_default();
+        if let Some(flag) = flags.chars().find(|flag| !matches!(flag, 'g' | 'i')) {
+            return Err(format!("Unknown flag '{flag}', expected g or i"));
+        }
+
+        Ok(ExCommand::Substitute {
+            range: range.unwrap_or(LineRange {
+                start: Address::Current,
+                end: Address::Current,
+            }),
+            find,
+            replacement: replacement.unwrap_or_default(),
+            every_match: flags.contains('g'),
+            case_insensitive: flags.contains('i'),
+        })
+    }
+
+    /// `name=value`, a flag can be given without value to turn it on
+    fn parse_option(option: &str) -> Result<SetOption, String> {
+        let (name, value) = option
+            .split_once('=')
+            .map_or((option, "on"), |(name, value)| (name.trim(), value.trim()));
+
+        match name {
+            "soft_wrap" | "wrap" => Self::parse_value(value).map(SetOption::SoftWrap),
+            "line_numbers" | "number" => Self::parse_value(value).map(SetOption::LineNumbers),
+            "indentation" => Self::parse_value(value).map(SetOption::Indentation),
+            "theme" => Self::parse_value(value).map(SetOption::Theme),
+            "case_insensitive" => Self::parse_value(value).map(SetOption::CaseInsensitive),
+            "whole_word" => Self::parse_value(value).map(SetOption::WholeWord),
+            "regex" => Self::parse_value(value).map(SetOption::Regex),
+            "keep_backup" => Self::parse_value(value).map(SetOption::KeepBackup),
+            "" => Err(String::from("Missing option, usage: set option=value")),
+            _ => Err(format!("Unknown option '{name}'")),
+        }
+    }
+
+    /// Values are read the same way as in the config file, `on` and `off` work for flags too
+    fn parse_value<T: DeserializeOwned>(value: &str) -> Result<T, String> {
+        let value = match value {
+            "on" | "true" => toml::Value::Boolean(true),
+            "off" | "false" => toml::Value::Boolean(false),
+            _ => value.parse().map_or_else(
+                |_| toml::Value::String(value.to_string()),
+                toml::Value::Integer,
+            ),
+        };
+
+        value
+            .try_into()
+            .map_err(|err: toml::de::Error| err.message().to_string())
+    }
+}
+
+/// Whole command lines the given one can be completed to
+///
+/// Completes command names, option names after `set` and file paths after `e`, `w` and `diagnostics`
+pub fn complete(line: &str) -> Vec<String> {
+    let Some((name, argument)) = line.split_once(' ') else {
+        return COMMAND_NAMES
+            .iter()
+            .filter(|command| command.starts_with(line))
+            .map(|command| format!("{command} "))
+            .collect();
+    };
+
+    let argument = argument.trim_start();
+    let candidates = match name {
+        "set" if !argument.contains('=') => OPTION_NAMES
+            .iter()
+            .filter(|option| option.starts_with(argument))
+            .map(|option| format!("{option}="))
+            .collect(),
+        "e" | "edit" | "w" | "write" | "diagnostics" => complete_path(argument),
+        _ => Vec::new(),
+    };
+
+    candidates
+        .into_iter()
+        .map(|candidate| format!("{name} {candidate}"))
+        .collect()
+}
+
+/// Entries of the directory part starting with the rest, hidden ones only when asked for with a dot
+fn complete_path(path: &str) -> Vec<String> {
+    let (directory, prefix) = path
+        .rfind('/')
+        .map_or(("", path), |idx| path.split_at(idx.saturating_add(1)));
+
+    let Ok(entries) = fs::read_dir(if directory.is_empty() { "." } else { directory }) else {
+        return Vec::new();
+    };
+
+    let mut candidates: Vec<String> = entries
+        .filter_map(Result::ok)
+        .filter_map(|entry| {
+            let name = entry.file_name().into_string().ok()?;
+            if !name.starts_with(prefix) || (name.starts_with('.') && !prefix.starts_with('.')) {
+                return None;
+            }
+
+            let separator = if entry.path().is_dir() { "/" } else { "" };
+            Some(format!("{directory}{name}{separator}"))
+        })
+        .collect();
+
+    candidates.sort();
+    candidates
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Range resolved on line 5 of 10, find, replacement and the g and i flags
+    fn substitute(line: &str) -> (RangeInclusive<LineIdx>, String, String, bool, bool) {
+        let Ok(ExCommand::Substitute {
+            range,
+            find,
+            replacement,
+            every_match,
+            case_insensitive,
+        }) = line.parse()
+        else {
+            panic!("'{line}' is not a substitution");
+        };
+
+        (
+            range.resolve(4, 10),
+            find,
+            replacement,
+            every_match,
+            case_insensitive,
+        )
+    }
+
+    fn error(line: &str) -> String {
+        line.parse::<ExCommand>()
+            .err()
+            .unwrap_or_else(|| panic!("'{line}' should not parse"))
+    }
+
+    #[test]
+    fn substitute_splits_on_any_delimiter() {
+        assert_eq!(
+            substitute("s/a/b/"),
+            (4..=4, "a".into(), "b".into(), false, false)
+        );
+        assert_eq!(
+            substitute("s/a/b"),
+            (4..=4, "a".into(), "b".into(), false, false)
+        );
+        assert_eq!(
+            substitute("s/a"),
+            (4..=4, "a".into(), String::new(), false, false)
+        );
+        assert_eq!(
+            substitute("s#/usr#/opt#g"),
+            (4..=4, "/usr".into(), "/opt".into(), true, false)
+        );
+    }
+
+    #[test]
+    fn substitute_delimiter_is_escaped_with_backslash() {
+        assert_eq!(
+            substitute(r"s/a\/b/c\/d/"),
+            (4..=4, "a/b".into(), "c/d".into(), false, false)
+        );
+        assert_eq!(
+            substitute(r"s#a\#b#c#g"),
+            (4..=4, "a#b".into(), "c".into(), true, false)
+        );
+        // Other escapes are left for the regex and the replacement
+        assert_eq!(
+            substitute(r"s/\d+\./\\$1/"),
+            (4..=4, r"\d+\.".into(), r"\\$1".into(), false, false)
+        );
+        assert_eq!(
+            substitute(r"s/a\"),
+            (4..=4, r"a\".into(), String::new(), false, false)
+        );
+    }
+
+    #[test]
+    fn substitute_flags() {
+        assert!(substitute("s/a/b/g").3);
+        assert!(substitute("s/a/b/i").4);
+        assert_eq!(
+            substitute("s/a/b/gi"),
+            (4..=4, "a".into(), "b".into(), true, true)
+        );
+        assert_eq!(
+            substitute("s/a/b/ig"),
+            (4..=4, "a".into(), "b".into(), true, true)
+        );
+
+        assert_eq!(error("s/a/b/x"), "Unknown flag 'x', expected g or i");
+        assert_eq!(error("s/a/b/gc"), "Unknown flag 'c', expected g or i");
+    }
+
+    #[test]
+    fn substitute_usage_errors() {
+        let usage = "Usage: [range]s/find/replacement/[g][i]";
+
+        assert_eq!(error("s/"), usage);
+        assert_eq!(error("s//b/"), usage);
+        assert_eq!(error("s/a/b/g/"), usage);
+        assert_eq!(error("%s"), "Range is only supported by s, got '%s'");
+    }
+
+    #[test]
+    fn ranges_resolve_to_line_indices() {
+        assert_eq!(substitute("%s/a/b/").0, 0..=9);
+        assert_eq!(substitute(".s/a/b/").0, 4..=4);
+        assert_eq!(substitute("$s/a/b/").0, 9..=9);
+        assert_eq!(substitute("2s/a/b/").0, 1..=1);
+        assert_eq!(substitute("2,4s/a/b/").0, 1..=3);
+        assert_eq!(substitute(".,$s/a/b/").0, 4..=9);
+        // Backwards ranges are turned around
+        assert_eq!(substitute("7,3s/a/b/").0, 2..=6);
+        assert_eq!(substitute("$,.s/a/b/").0, 4..=9);
+
+        let empty = LineRange {
+            start: Address::Number(1),
+            end: Address::Last,
+        };
+        assert_eq!(empty.resolve(0, 0), 0..=0);
+    }
+
+    #[test]
+    fn range_errors() {
+        assert_eq!(error("0s/a/b/"), "Invalid line '0' in range");
+        assert_eq!(error("1,,2s/a/b/"), "Invalid line ',2' in range");
+        assert_eq!(error("1,3w"), "Range is only supported by s, got '1,3w'");
+    }
+
+    #[test]
+    fn commands_and_options() {
+        assert!(matches!(
+            "w".parse(),
+            Ok(ExCommand::Write {
+                file_name: None,
+                force: false
+            })
+        ));
+        assert!(matches!(
+            "write out.txt".parse(),
+            Ok(ExCommand::Write { file_name: Some(file), force: false }) if file == "out.txt"
+        ));
+        assert!(matches!(
+            "w!".parse(),
+            Ok(ExCommand::Write {
+                file_name: None,
+                force: true
+            })
+        ));
+        assert!(matches!(
+            "write!  out.txt".parse(),
+            Ok(ExCommand::Write { file_name: Some(file), force: true }) if file == "out.txt"
+        ));
+        assert!(matches!("q!".parse(), Ok(ExCommand::Quit { force: true })));
+        assert!(matches!(
+            "vs".parse(),
+            Ok(ExCommand::Split(Direction::Vertical))
+        ));
+        assert!(matches!("goto 3".parse(), Ok(ExCommand::GoTo(_))));
+        assert!(matches!(
+            "set wrap".parse(),
+            Ok(ExCommand::Set(SetOption::SoftWrap(true)))
+        ));
+        assert!(matches!(
+            "set indentation = 2".parse(),
+            Ok(ExCommand::Set(SetOption::Indentation(Indentation::Spaces(
+                2
+            ))))
+        ));
+        assert!(matches!(
+            "set regex=off".parse(),
+            Ok(ExCommand::Set(SetOption::Regex(false)))
+        ));
+
+        assert_eq!(error("e"), "Missing file name, usage: e file");
+        assert_eq!(error("set"), "Missing option, usage: set option=value");
+        assert_eq!(error("set colour=red"), "Unknown option 'colour'");
+        assert_eq!(error("frobnicate"), "Unknown command 'frobnicate'");
+    }
+}
@@ -0,0 +1,162 @@
+use std::str::FromStr;
+
+use crossterm::event::{Event, KeyEvent};
+
+use super::{
+    Editor,
+    caret_position::CaretPosition,
+    cli::{self, Action, Args, Source},
+    command::{Command, edit::Edit},
+    config::Config,
+    session::Session,
+    size::Size,
+    terminal::{Terminal, memory_backend::MemoryBackend},
+    ui_component::view::location::Location,
+};
+
+/// Editor drawing into memory, driven by commands instead of terminal events
+pub struct Harness {
+    editor: Editor,
+    screen: MemoryBackend,
+}
+
+impl Harness {
+    /// Files are looked up in `test_files/`, tests run from the crate root, the default config is used
+    pub fn open(files: &[&str], size: Size) -> Self {
+        Self::with_session(files, size, Session::default())
+    }
+
+    /// The session is saved when the harness is dropped, if it was loaded from a file
+    pub fn with_session(files: &[&str], size: Size, session: Session) -> Self {
+        let args = Args {
+            sources: files
+                .iter()
+                .map(|name| Source::File {
+                    name: format!("test_files/{name}"),
+                    location: None,
+                })
+                .collect(),
+            read_only: false,
+        };
+
+        Self::start(&args, None, size, session)
+    }
+
+    /// Arguments as given on the command line, paths are not changed, `-` reads the given stdin
+    pub fn with_args(arguments: &[&str], stdin: Option<&str>, size: Size) -> Self {
+        let Ok(Action::Edit(args)) = cli::parse(arguments.iter().map(ToString::to_string)) else {
+            panic!("Arguments {arguments:?} do not start the editor");
+        };
+
+        Self::start(
+            &args,
+            stdin.map(|text| Ok(text.to_string())),
+            size,
+            Session::default(),
+        )
+    }
+
+    fn start(
+        args: &Args,
+        stdin: Option<Result<String, std::io::Error>>,
+        size: Size,
+        session: Session,
+    ) -> Self {
+        let screen = MemoryBackend::new(size);
+        Terminal::set_backend(Box::new(screen.clone()));
+
+        let mut editor = Editor::with_args(args, stdin, Config::default(), session);
+        editor.update_status();
+        editor.refresh_screen();
+
+        Harness { editor, screen }
+    }
+
+    /// Handles the command the same way as one coming from a key press, then redraws
+    pub fn command(&mut self, command: Command) -> &mut Self {
+        self.editor.handle_command(command);
+        self.redraw()
+    }
+
+    /// Goes through key bindings like a real key press
+    pub fn press(&mut self, key_event: KeyEvent) -> &mut Self {
+        self.editor.evaluate_event(Event::Key(key_event));
+        self.redraw()
+    }
+
+    /// Command names separated by whitespace, `name*N` repeats a command N times
+    pub fn run(&mut self, script: &str) -> &mut Self {
+        for step in script.split_whitespace() {
+            let (name, times) = match step.split_once('*') {
+                Some((name, times)) => (
+                    name,
+                    times
+                        .parse::<usize>()
+                        .unwrap_or_else(|_| panic!("Bad repeat count in '{step}'")),
+                ),
+                None => (step, 1),
+            };
+            let command =
+                Command::from_str(name).unwrap_or_else(|err| panic!("Bad script step: {err}"));
+
+            for _ in 0..times {
+                self.command(command);
+            }
+        }
+        self
+    }
+
+    /// Every character is typed on its own, `\n` presses Enter
+    pub fn type_text(&mut self, text: &str) -> &mut Self {
+        for chr in text.chars() {
+            let edit = if chr == '\n' {
+                Edit::Enter
+            } else {
+                Edit::Input(chr)
+            };
+            self.command(Command::Edit(edit));
+        }
+        self
+    }
+
+    /// Text of the buffer in the focused window
+    pub fn content(&self) -> String {
+        self.editor.view().get_content()
+    }
+
+    pub fn line(&self, line_idx: usize) -> String {
+        self.content()
+            .split('\n')
+            .nth(line_idx)
+            .unwrap_or_default()
+            .to_string()
+    }
+
+    pub fn location(&self) -> Location {
+        self.editor.view().location()
+    }
+
+    /// Row of the screen with trailing blanks removed
+    pub fn row(&self, row: usize) -> String {
+        self.screen.row(row)
+    }
+
+    pub fn rows(&self) -> Vec<String> {
+        self.screen.rows()
+    }
+
+    /// Caret on screen after the last redraw
+    pub fn caret(&self) -> Option<CaretPosition> {
+        self.screen.caret()
+    }
+
+    pub fn title(&self) -> String {
+        self.screen.title()
+    }
+
+    fn redraw(&mut self) -> &mut Self {
+        self.editor.update_status();
+        self.editor.refresh_screen();
+        self
+    }
+}
@@ -0,0 +1,593 @@
+use super::{
+    caret_position::CaretPosition,
+    size::Size,
+    terminal::Terminal,
+    ui_component::{UiComponent, status_bar::StatusBar},
+};
+
+/// Percentage of a split moved from one side to the other by a single resize
+const RESIZE_STEP: usize = 5;
+const MIN_SHARE: usize = 10;
+const MAX_SHARE: usize = 90;
+
+const SEPARATOR: &str = "│";
+
+#[derive(Clone, Copy)]
+pub enum Direction {
+    /// One window above the other
+    Horizontal,
+    /// Windows side by side with a separator column in between
+    Vertical,
+}
+
+/// Part of the screen showing one view, its status line takes the last row
+#[derive(Default)]
+pub struct Window {
+    /// Index into the views of the editor
+    pub view: usize,
+    pub status_bar: StatusBar,
+    pub origin: CaretPosition,
+    pub size: Size,
+}
+
+impl Window {
+    fn new(view: usize) -> Self {
+        Window {
+            view,
+            status_bar: StatusBar::new(),
+            ..Window::default()
+        }
+    }
+
+    /// Room left for the view above the status line
+    pub fn view_size(&self) -> Size {
+        Size {
+            columns: self.size.columns,
+            rows: self.size.rows.saturating_sub(1),
+        }
+    }
+
+    pub fn status_origin(&self) -> CaretPosition {
+        CaretPosition {
+            column: self.origin.column,
+            row: self
+                .origin
+                .row
+                .saturating_add(self.size.rows.saturating_sub(1)),
+        }
+    }
+
+    fn contains(&self, position: CaretPosition) -> bool {
+        (self.origin.row..self.origin.row.saturating_add(self.size.rows)).contains(&position.row)
+            && (self.origin.column..self.origin.column.saturating_add(self.size.columns))
+                .contains(&position.column)
+    }
+}
+
+enum Node {
+    Window(Window),
+    /// `share` is the percentage of the room given to the first node
+    Split {
+        direction: Direction,
+        share: usize,
+        first: Box<Node>,
+        second: Box<Node>,
+    },
+}
+
+impl Default for Node {
+    fn default() -> Self {
+        Node::Window(Window::default())
+    }
+}
+
+impl Node {
+    fn window_count(&self) -> usize {
+        match self {
+            Node::Window(_) => 1,
+            Node::Split { first, second, .. } => {
+                first.window_count().saturating_add(second.window_count())
+            }
+        }
+    }
+
+    /// Windows from top left to bottom right
+    fn collect<'a>(&'a self, windows: &mut Vec<&'a Window>) {
+        match self {
+            Node::Window(window) => windows.push(window),
+            Node::Split { first, second, .. } => {
+                first.collect(windows);
+                second.collect(windows);
+            }
+        }
+    }
+
+    fn collect_mut<'a>(&'a mut self, windows: &mut Vec<&'a mut Window>) {
+        match self {
+            Node::Window(window) => windows.push(window),
+            Node::Split { first, second, .. } => {
+                first.collect_mut(windows);
+                second.collect_mut(windows);
+            }
+        }
+    }
+
+    /// Node holding the window with given index
+    fn window_node_mut(&mut self, idx: usize) -> Option<&mut Node> {
+        match self {
+            Node::Window(_) => (idx == 0).then_some(self),
+            Node::Split { first, second, .. } => {
+                let first_count = first.window_count();
+                if idx < first_count {
+                    first.window_node_mut(idx)
+                } else {
+                    second.window_node_mut(idx.saturating_sub(first_count))
+                }
+            }
+        }
+    }
+
+    /// The other side of the split takes the place of the split, returns the removed window
+    fn remove_window(&mut self, idx: usize) -> Option<Window> {
+        let Node::Split { first, second, .. } = self else {
+            return None;
+        };
+
+        let first_count = first.window_count();
+        let (target, idx, sibling) = if idx < first_count {
+            (first, idx, second)
+        } else {
+            (second, idx.saturating_sub(first_count), first)
+        };
+
+        if !matches!(**target, Node::Window(_)) {
+            return target.remove_window(idx);
+        }
+
+        let removed = std::mem::take(&mut **target);
+        let sibling = std::mem::take(&mut **sibling);
+        *self = sibling;
+
+        match removed {
+            Node::Window(window) => Some(window),
+            Node::Split { .. } => None,
+        }
+    }
+
+    /// Moves the border of the innermost split around the window, `grow` gives the window more room
+    fn resize_window(&mut self, idx: usize, grow: bool) -> bool {
+        let Node::Split {
+            share,
+            first,
+            second,
+            ..
+        } = self
+        else {
+            return false;
+        };
+
+        let first_count = first.window_count();
+        let in_first = idx < first_count;
+        let resized_inside = if in_first {
+            first.resize_window(idx, grow)
+        } else {
+            second.resize_window(idx.saturating_sub(first_count), grow)
+        };
+
+        if !resized_inside {
+            *share = if in_first == grow {
+                share.saturating_add(RESIZE_STEP).min(MAX_SHARE)
+            } else {
+                share.saturating_sub(RESIZE_STEP).max(MIN_SHARE)
+            };
+        }
+
+        true
+    }
+
+    fn arrange(
+        &mut self,
+        origin: CaretPosition,
+        size: Size,
+        separators: &mut Vec<(CaretPosition, usize)>,
+    ) {
+        match self {
+            Node::Window(window) => {
+                window.origin = origin;
+                window.size = size;
+                window.status_bar.resize(Size {
+                    columns: size.columns,
+                    rows: 1,
+                });
+            }
+            Node::Split {
+                direction: Direction::Horizontal,
+                share,
+                first,
+                second,
+            } => {
+                let first_rows = Self::share_of(size.rows, *share);
+                let second_origin = CaretPosition {
+                    row: origin.row.saturating_add(first_rows),
+                    ..origin
+                };
+
+                first.arrange(
+                    origin,
+                    Size {
+                        rows: first_rows,
+                        ..size
+                    },
+                    separators,
+                );
+                second.arrange(
+                    second_origin,
+                    Size {
+                        rows: size.rows.saturating_sub(first_rows),
+                        ..size
+                    },
+                    separators,
+                );
+            }
+            Node::Split {
+                direction: Direction::Vertical,
+                share,
+                first,
+                second,
+            } => {
+                let available = size.columns.saturating_sub(1);
+                let first_columns = Self::share_of(available, *share);
+                let separator = CaretPosition {
+                    column: origin.column.saturating_add(first_columns),
+                    ..origin
+                };
+                let second_origin = CaretPosition {
+                    column: separator.column.saturating_add(1),
+                    ..origin
+                };
+
+                separators.push((separator, size.rows));
+                first.arrange(
+                    origin,
+                    Size {
+                        columns: first_columns,
+                        ..size
+                    },
+                    separators,
+                );
+                second.arrange(
+                    second_origin,
+                    Size {
+                        columns: available.saturating_sub(first_columns),
+                        ..size
+                    },
+                    separators,
+                );
+            }
+        }
+    }
+
+    /// Both sides keep at least one row or column as long as there is room for it
+    fn share_of(length: usize, share: usize) -> usize {
+        (length.saturating_mul(share) / 100)
+            .max(1)
+            .min(length.saturating_sub(1))
+    }
+}
+
+/// Windows the screen above the message bar is split into, one of them has the focus
+pub struct Layout {
+    root: Node,
+    focused: usize,
+    /// Columns between windows side by side, with their height
+    separators: Vec<(CaretPosition, usize)>,
+    needs_redraw: bool,
+}
+
+impl Layout {
+    pub fn new(view: usize) -> Self {
+        Layout {
+            root: Node::Window(Window::new(view)),
+            focused: 0,
+            separators: Vec::new(),
+            needs_redraw: true,
+        }
+    }
+
+    pub fn windows(&self) -> Vec<&Window> {
+        let mut windows = Vec::new();
+        self.root.collect(&mut windows);
+        windows
+    }
+
+    pub fn windows_mut(&mut self) -> Vec<&mut Window> {
+        let mut windows = Vec::new();
+        self.root.collect_mut(&mut windows);
+        windows
+    }
+
+    pub fn window_count(&self) -> usize {
+        self.root.window_count()
+    }
+
+    pub fn focused_idx(&self) -> usize {
+        self.focused
+    }
+
+    pub fn focused(&self) -> &Window {
+        // `focused` is kept in bounds and there is always at least one window
+        self.windows()[self.focused]
+    }
+
+    pub fn focused_mut(&mut self) -> &mut Window {
+        let focused = self.focused;
+        self.windows_mut().swap_remove(focused)
+    }
+
+    pub fn focus(&mut self, idx: usize) {
+        if idx >= self.window_count() {
+            return;
+        }
+
+        self.focused = idx;
+        for (window_idx, window) in self.windows_mut().into_iter().enumerate() {
+            window.status_bar.set_focused(window_idx == idx);
+        }
+    }
+
+    pub fn focus_next(&mut self) {
+        self.focus(self.focused.saturating_add(1) % self.window_count());
+    }
+
+    /// Window showing given view
+    pub fn window_showing(&self, view: usize) -> Option<usize> {
+        self.windows().iter().position(|window| window.view == view)
+    }
+
+    pub fn window_at(&self, position: CaretPosition) -> Option<usize> {
+        self.windows()
+            .iter()
+            .position(|window| window.contains(position))
+    }
+
+    /// New window showing given view takes half of the focused one and gets the focus, fails when there is no room
+    pub fn split(&mut self, direction: Direction, view: usize) -> bool {
+        let Some(node) = self.root.window_node_mut(self.focused) else {
+            return false;
+        };
+        let Node::Window(window) = node else {
+            return false;
+        };
+
+        // Each side needs a row of text above its status line, or a column next to the separator
+        let has_room = match direction {
+            Direction::Horizontal => window.size.rows >= 4,
+            Direction::Vertical => window.size.columns >= 3,
+        };
+        if !has_room {
+            return false;
+        }
+
+        let first = std::mem::take(node);
+        *node = Node::Split {
+            direction,
+            share: 50,
+            first: Box::new(first),
+            second: Box::new(Node::Window(Window::new(view))),
+        };
+
+        self.focus(self.focused.saturating_add(1));
+        true
+    }
+
+    /// Returns the view of the closed window, the last window is never closed
+    pub fn close_focused(&mut self) -> Option<usize> {
+        let removed = self.root.remove_window(self.focused)?;
+
+        self.focus(self.focused.min(self.window_count().saturating_sub(1)));
+        Some(removed.view)
+    }
+
+    pub fn resize_focused(&mut self, grow: bool) {
+        self.root.resize_window(self.focused, grow);
+    }
+
+    /// View with given index was removed from the editor, the ones after it moved down by one
+    pub fn forget_view(&mut self, removed: usize) {
+        for window in self.windows_mut() {
+            if window.view > removed {
+                window.view = window.view.saturating_sub(1);
+            }
+        }
+    }
+
+    /// Places every window within the area starting at the top left corner of the screen
+    pub fn arrange(&mut self, area: Size) {
+        self.separators.clear();
+        self.root
+            .arrange(CaretPosition::default(), area, &mut self.separators);
+        self.needs_redraw = true;
+    }
+
+    /// Separators between windows, the windows draw themselves
+    pub fn render(&mut self) {
+        if self.needs_redraw && self.draw_separators().is_ok() {
+            self.needs_redraw = false;
+        }
+    }
+
+    fn draw_separators(&self) -> Result<(), std::io::Error> {
+        for (origin, rows) in &self.separators {
+            for row in 0..*rows {
+                let position = CaretPosition {
+                    row: origin.row.saturating_add(row),
+                    ..*origin
+                };
+                Terminal::print_at(position, 1, SEPARATOR)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const AREA: Size = Size {
+        columns: 80,
+        rows: 24,
+    };
+
+    /// View, top left corner as row and column and size as rows and columns
+    type Shape = (usize, (usize, usize), (usize, usize));
+
+    fn windows(layout: &mut Layout) -> Vec<Shape> {
+        layout.arrange(AREA);
+        layout
+            .windows()
+            .iter()
+            .map(|window| {
+                (
+                    window.view,
+                    (window.origin.row, window.origin.column),
+                    (window.size.rows, window.size.columns),
+                )
+            })
+            .collect()
+    }
+
+    /// View 0 on the left, 1 above 2 on the right, with the focus on 2
+    fn nested() -> Layout {
+        let mut layout = Layout::new(0);
+        layout.arrange(AREA);
+        assert!(layout.split(Direction::Vertical, 1));
+        layout.arrange(AREA);
+        assert!(layout.split(Direction::Horizontal, 2));
+        layout
+    }
+
+    #[test]
+    fn splits_share_the_room_of_the_focused_window() {
+        let mut layout = nested();
+
+        assert_eq!(layout.focused_idx(), 2);
+        assert_eq!(
+            windows(&mut layout),
+            [
+                (0, (0, 0), (24, 39)),
+                (1, (0, 40), (12, 40)),
+                (2, (12, 40), (12, 40)),
+            ]
+        );
+        assert_eq!(
+            layout.separators,
+            [(CaretPosition { column: 39, row: 0 }, 24)]
+        );
+    }
+
+    #[test]
+    fn closing_a_nested_window_gives_its_room_to_the_sibling() {
+        let mut layout = nested();
+        layout.focus(1);
+
+        assert_eq!(layout.close_focused(), Some(1));
+        assert_eq!(
+            windows(&mut layout),
+            [(0, (0, 0), (24, 39)), (2, (0, 40), (24, 40))]
+        );
+        assert_eq!(layout.focused().view, 2);
+    }
+
+    #[test]
+    fn closing_the_outer_window_keeps_the_nested_split() {
+        let mut layout = nested();
+        layout.focus(0);
+
+        assert_eq!(layout.close_focused(), Some(0));
+        assert_eq!(
+            windows(&mut layout),
+            [(1, (0, 0), (12, 80)), (2, (12, 0), (12, 80))]
+        );
+        assert_eq!(layout.focused().view, 1);
+        assert!(layout.separators.is_empty());
+    }
+
+    #[test]
+    fn focus_stays_in_bounds_when_the_last_window_is_closed() {
+        let mut layout = nested();
+
+        assert_eq!(layout.close_focused(), Some(2));
+        assert_eq!(layout.focused_idx(), 1);
+        assert_eq!(layout.close_focused(), Some(1));
+        assert_eq!(layout.focused_idx(), 0);
+
+        // The last window stays
+        assert_eq!(layout.close_focused(), None);
+        assert_eq!(layout.window_count(), 1);
+        assert_eq!(layout.focused().view, 0);
+    }
+
+    #[test]
+    fn resizing_moves_the_innermost_border_within_limits() {
+        let mut layout = nested();
+
+        // Growing the lower window moves the border between 1 and 2 up until the upper keeps a tenth
+        for _ in 0..20 {
+            layout.resize_focused(true);
+        }
+        assert_eq!(
+            windows(&mut layout),
+            [
+                (0, (0, 0), (24, 39)),
+                (1, (0, 40), (2, 40)),
+                (2, (2, 40), (22, 40)),
+            ]
+        );
+
+        layout.focus(0);
+        for _ in 0..20 {
+            layout.resize_focused(true);
+        }
+        assert_eq!(windows(&mut layout)[0], (0, (0, 0), (24, 71)));
+
+        for _ in 0..20 {
+            layout.resize_focused(false);
+        }
+        assert_eq!(windows(&mut layout)[0], (0, (0, 0), (24, 7)));
+    }
+
+    #[test]
+    fn single_window_is_not_resized() {
+        let mut layout = Layout::new(0);
+
+        layout.resize_focused(true);
+        assert_eq!(windows(&mut layout), [(0, (0, 0), (24, 80))]);
+    }
+
+    #[test]
+    fn split_is_refused_without_room_for_both_sides() {
+        let mut layout = Layout::new(0);
+        layout.arrange(Size {
+            columns: 2,
+            rows: 3,
+        });
+
+        assert!(!layout.split(Direction::Vertical, 1));
+        assert!(!layout.split(Direction::Horizontal, 1));
+        assert_eq!(layout.window_count(), 1);
+        assert_eq!(layout.focused_idx(), 0);
+
+        let smallest = Size {
+            columns: 3,
+            rows: 4,
+        };
+        layout.arrange(smallest);
+        assert!(layout.split(Direction::Vertical, 1));
+        layout.arrange(smallest);
+        // The new window got the single column right of the separator
+        assert!(!layout.split(Direction::Vertical, 2));
+        assert!(layout.split(Direction::Horizontal, 2));
+        assert_eq!(layout.window_count(), 3);
+    }
+}
@@ -1,8 +1,12 @@
+use std::cell::OnceCell;
 use std::ops::{Deref, Range};
 
 use unicode_segmentation::UnicodeSegmentation;
 use unicode_width::UnicodeWidthStr;
 
+use super::annotated_string::{AnnotatedString, annotation::Annotation};
+use super::search_pattern::SearchPattern;
+
 pub type GraphemeIdx = usize;
 pub type LineIdx = usize;
 pub type ByteIdx = usize;
@@ -14,6 +18,13 @@ pub enum GraphemeWidth {
 }
 
 impl GraphemeWidth {
+    fn columns(self) -> usize {
+        match self {
+            GraphemeWidth::Half => 1,
+            GraphemeWidth::Full => 2,
+        }
+    }
+
     fn saturating_add(self, other: usize) -> usize {
         match self {
             GraphemeWidth::Half => other.saturating_add(1),
@@ -29,18 +40,22 @@ pub struct TextFragment {
     start_byte_idx: ByteIdx,
 }
 
+/// Grapheme fragments are computed on first use, so lines which are never shown or edited
+/// cost no more than their text
 #[derive(Default, Clone)]
 pub struct Line {
-    fragments: Vec<TextFragment>,
+    fragments: OnceCell<Vec<TextFragment>>,
     string: String,
+    /// Set by the buffer for lines edited since the file was last saved
+    modified: bool,
 }
 
 impl Line {
     pub fn from(line_str: &str) -> Self {
-        let fragments = Self::str_to_fragments(line_str);
         Line {
-            fragments,
+            fragments: OnceCell::new(),
             string: String::from(line_str),
+            modified: false,
         }
     }
 
@@ -50,8 +65,12 @@ impl Line {
     }
 
     pub fn split_off(&mut self, at: GraphemeIdx) -> Line {
-        if let Some(fragment) = self.fragments.get(at) {
-            let remainder = self.string.split_off(fragment.start_byte_idx);
+        if let Some(start) = self
+            .fragments()
+            .get(at)
+            .map(|fragment| fragment.start_byte_idx)
+        {
+            let remainder = self.string.split_off(start);
             self.rebuild_fragments();
             Self::from(&remainder)
         } else {
@@ -61,19 +80,25 @@ impl Line {
 
     // ========================================================= Builders =============================================================
 
+    /// Fragments are rebuilt lazily on next use
     fn rebuild_fragments(&mut self) {
-        self.fragments = Self::str_to_fragments(&self.to_string());
+        self.fragments = OnceCell::new();
     }
 
-    // ========================================================= Find ==================================================================
-    fn grapheme_idx_to_byte_idx(&self, grapheme_idx: GraphemeIdx) -> ByteIdx {
+    fn fragments(&self) -> &Vec<TextFragment> {
         self.fragments
+            .get_or_init(|| Self::str_to_fragments(&self.string))
+    }
+
+    // ========================================================= Find ==================================================================
+    pub fn grapheme_idx_to_byte_idx(&self, grapheme_idx: GraphemeIdx) -> ByteIdx {
+        self.fragments()
             .get(grapheme_idx)
             .map_or(0, |fragment| fragment.start_byte_idx)
     }
 
     fn byte_idx_to_grapheme_idx(&self, byte_idx: ByteIdx) -> GraphemeIdx {
-        self.fragments
+        self.fragments()
             .iter()
             .position(|fragment| fragment.start_byte_idx >= byte_idx)
             .map_or(0, |grapheme_idx| grapheme_idx)
@@ -81,66 +106,86 @@ impl Line {
 
     pub fn backward_find(
         &self,
-        search_string: &str,
-        end_point: GraphemeIdx,
-    ) -> Option<GraphemeIdx> {
-        if end_point >= self.string.len() {
-            return None;
-        }
-
-        // TODO: need to fix this
-        let slice = &self.string[..=self.grapheme_idx_to_byte_idx(end_point)];
-
-        slice
-            .rmatch_indices(search_string)
-            .next()
-            .map(|(byte_idx, _)| self.byte_idx_to_grapheme_idx(byte_idx))
+        pattern: &SearchPattern,
+        before: GraphemeIdx,
+    ) -> Option<Range<GraphemeIdx>> {
+        self.find_all(pattern)
+            .into_iter()
+            .map(|(_, graphemes)| graphemes)
+            .rfind(|graphemes| graphemes.start < before)
     }
 
     pub fn forward_find(
         &self,
-        search_string: &str,
-        start_look_point: GraphemeIdx,
-    ) -> Option<GraphemeIdx> {
-        if start_look_point >= self.string.len() {
-            return None;
-        }
-
-        let slice = &self.string[self.grapheme_idx_to_byte_idx(start_look_point)..];
+        pattern: &SearchPattern,
+        from: GraphemeIdx,
+    ) -> Option<Range<GraphemeIdx>> {
+        self.find_all(pattern)
+            .into_iter()
+            .map(|(_, graphemes)| graphemes)
+            .find(|graphemes| graphemes.start >= from)
+    }
 
-        slice
-            .match_indices(search_string)
-            .next()
-            .map(|(byte_idx, _)| {
-                self.byte_idx_to_grapheme_idx(byte_idx.saturating_add(start_look_point))
+    /// Every match as (byte range, grapheme range), matches splitting a grapheme cluster are dropped
+    pub fn find_all(&self, pattern: &SearchPattern) -> Vec<(Range<ByteIdx>, Range<GraphemeIdx>)> {
+        pattern
+            .find_matches(&self.string)
+            .into_iter()
+            .filter_map(|bytes| {
+                let start = self.grapheme_boundary(bytes.start)?;
+                let end = self.grapheme_boundary(bytes.end)?;
+                Some((bytes, start..end))
             })
+            .collect()
     }
 
-    pub fn get_next_match_idx(&self, start_idx: GraphemeIdx, search_string: &str) -> GraphemeIdx {
-        if start_idx >= self.grapheme_count() {
-            return 1;
+    /// End of the first word ending after given grapheme, `None` when there is none
+    ///
+    /// Words come from Unicode word segmentation, segments without letters or digits are skipped
+    pub fn next_word_end(&self, from: GraphemeIdx) -> Option<GraphemeIdx> {
+        if from >= self.grapheme_count() {
+            return None;
         }
 
-        let grapheme_len = Line::from(search_string).grapheme_count();
+        let from = self.grapheme_idx_to_byte_idx(from);
 
-        if (start_idx + grapheme_len) >= self.grapheme_count() {
-            return self.grapheme_count();
-        }
-
-        if start_idx + grapheme_len >= self.string.len() {}
+        self.string
+            .split_word_bound_indices()
+            .filter(|(_, segment)| Self::is_word(segment))
+            .map(|(start, segment)| start.saturating_add(segment.len()))
+            .find(|end| *end > from)
+            .and_then(|end| self.grapheme_boundary(end))
+    }
 
-        let slice =
-            &self.string[self.grapheme_idx_to_byte_idx(start_idx.saturating_add(grapheme_len))..];
+    /// Start of the last word starting before given grapheme, `None` when there is none
+    pub fn previous_word_start(&self, before: GraphemeIdx) -> Option<GraphemeIdx> {
+        let before = if before >= self.grapheme_count() {
+            self.string.len()
+        } else {
+            self.grapheme_idx_to_byte_idx(before)
+        };
+
+        self.string
+            .split_word_bound_indices()
+            .filter(|(_, segment)| Self::is_word(segment))
+            .map(|(start, _)| start)
+            .rfind(|start| *start < before)
+            .and_then(|start| self.grapheme_boundary(start))
+    }
 
-        slice.match_indices(search_string).next().map_or(1, |x| {
-            self.byte_idx_to_grapheme_idx(x.0.saturating_add(grapheme_len))
-        })
+    fn is_word(segment: &str) -> bool {
+        segment.chars().any(char::is_alphanumeric)
     }
+
     // ========================================================== String manipulation ==================================================
 
     pub fn add_character_to_line(&mut self, chr: char, at: GraphemeIdx) {
-        if let Some(fragment) = self.fragments.get(at) {
-            self.string.insert(fragment.start_byte_idx, chr);
+        if let Some(start) = self
+            .fragments()
+            .get(at)
+            .map(|fragment| fragment.start_byte_idx)
+        {
+            self.string.insert(start, chr);
         } else {
             self.string.push(chr);
         }
@@ -148,42 +193,75 @@ impl Line {
     }
 
     pub fn delete_character(&mut self, at: GraphemeIdx) {
-        if let Some(fragment) = self.fragments.get(at) {
-            let start = fragment.start_byte_idx;
-            let end = fragment
-                .start_byte_idx
-                .saturating_add(fragment.grapheme.len());
-            self.string.drain(start..end);
+        let bytes = self.fragments().get(at).map(|fragment| {
+            fragment.start_byte_idx
+                ..fragment
+                    .start_byte_idx
+                    .saturating_add(fragment.grapheme.len())
+        });
+
+        if let Some(bytes) = bytes {
+            self.string.drain(bytes);
             self.rebuild_fragments();
         }
     }
 
     // ============================================================= Getters =====================================================
-    pub fn find_all(&self, query: &str, range: Range<ByteIdx>) -> Vec<(ByteIdx, GraphemeIdx)> {
-        let end = std::cmp::min(range.end, self.string.len());
-        let start = range.start;
-        debug_assert!(start <= end);
-        debug_assert!(start <= self.string.len());
-        self.string.get(start..end).map_or_else(Vec::new, |substr| {
-            let potential_matches: Vec<ByteIdx> = substr
-                .match_indices(query) // find _potential_ matches within the substring
-                .map(|(relative_start_idx, _)| {
-                    relative_start_idx.saturating_add(start) //convert their relative indices to absolute indices
-                })
-                .collect();
-            self.match_graphme_clusters(&potential_matches, query) //convert the potential matches into matches which align with the grapheme boundaries.
-        })
+    pub fn get_visable_graphemes(&self, range: Range<GraphemeIdx>) -> String {
+        self.visible_parts(range)
+            .into_iter()
+            .map(|(_, text)| text)
+            .collect()
     }
 
-    pub fn get_visable_graphemes(&self, range: Range<GraphemeIdx>) -> String {
+    /// Visible text in given column range with annotations moved from line bytes to the visible text
+    pub fn get_annotated_graphemes(
+        &self,
+        range: Range<GraphemeIdx>,
+        annotations: &[Annotation],
+    ) -> AnnotatedString {
+        let mut string = String::new();
+        let mut spans: Vec<(Range<ByteIdx>, Range<ByteIdx>)> = Vec::new();
+
+        for (source, text) in self.visible_parts(range) {
+            let start = string.len();
+            string.push_str(&text);
+            spans.push((source, start..string.len()));
+        }
+
+        let annotations = annotations
+            .iter()
+            .filter_map(|annotation| {
+                let start = spans
+                    .iter()
+                    .find(|(source, _)| source.end > annotation.start_byte)?;
+                let end = spans
+                    .iter()
+                    .rev()
+                    .find(|(source, _)| source.start < annotation.end_byte)?;
+
+                (start.1.start < end.1.end)
+                    .then(|| Annotation::new(start.1.start, end.1.end, annotation.annotation_type))
+            })
+            .collect();
+
+        AnnotatedString {
+            string,
+            annotations,
+        }
+    }
+
+    /// Source bytes and rendered text of every grapheme at least partially in given column range
+    fn visible_parts(&self, range: Range<GraphemeIdx>) -> Vec<(Range<ByteIdx>, String)> {
+        let mut result = Vec::new();
+
         if range.start >= range.end {
-            return String::new();
+            return result;
         }
 
-        let mut result = String::new();
         let mut current_pos = 0;
 
-        for fragment in &self.fragments {
+        for fragment in self.fragments() {
             let fragment_end = fragment.rendered_width.saturating_add(current_pos);
 
             if current_pos >= range.end {
@@ -191,13 +269,19 @@ impl Line {
             }
 
             if fragment_end > range.start {
-                if fragment_end > range.end || current_pos < range.start {
-                    result.push('⋯');
+                let text = if fragment_end > range.end || current_pos < range.start {
+                    String::from('⋯')
                 } else if let Some(char) = fragment.replacement {
-                    result.push(char);
+                    String::from(char)
                 } else {
-                    result.push_str(&fragment.grapheme);
-                }
+                    fragment.grapheme.clone()
+                };
+
+                let source = fragment.start_byte_idx
+                    ..fragment
+                        .start_byte_idx
+                        .saturating_add(fragment.grapheme.len());
+                result.push((source, text));
             }
 
             current_pos = fragment_end;
@@ -206,12 +290,63 @@ impl Line {
         result
     }
 
+    /// Grapheme index where each visual row starts when the line is wrapped at given width,
+    /// a completely full last row is followed by an empty one so the caret has somewhere to go
+    pub fn wrap_starts(&self, width: usize) -> Vec<GraphemeIdx> {
+        let mut starts = vec![0];
+
+        if width == 0 {
+            return starts;
+        }
+
+        let mut row_width: usize = 0;
+        for (grapheme_idx, fragment) in self.fragments().iter().enumerate() {
+            let fragment_width = fragment.rendered_width.columns();
+
+            if row_width > 0 && row_width.saturating_add(fragment_width) > width {
+                starts.push(grapheme_idx);
+                row_width = 0;
+            }
+            row_width = row_width.saturating_add(fragment_width);
+        }
+
+        if row_width >= width {
+            starts.push(self.grapheme_count());
+        }
+
+        starts
+    }
+
+    /// Text made of graphemes in given range, range is clamped to the line
+    pub fn get_graphemes(&self, range: Range<GraphemeIdx>) -> String {
+        self.fragments()
+            .iter()
+            .skip(range.start)
+            .take(range.end.saturating_sub(range.start))
+            .map(|fragment| fragment.grapheme.as_str())
+            .collect()
+    }
+
+    pub fn get_grapheme(&self, at: GraphemeIdx) -> Option<&str> {
+        self.fragments()
+            .get(at)
+            .map(|fragment| fragment.grapheme.as_str())
+    }
+
+    pub fn is_modified(&self) -> bool {
+        self.modified
+    }
+
+    pub fn set_modified(&mut self, modified: bool) {
+        self.modified = modified;
+    }
+
     pub fn grapheme_count(&self) -> GraphemeIdx {
-        self.fragments.len()
+        self.fragments().len()
     }
 
     pub fn width_until(&self, grapheme_index: GraphemeIdx) -> GraphemeIdx {
-        self.fragments
+        self.fragments()
             .iter()
             .take(grapheme_index)
             .map(|fragment| match fragment.rendered_width {
@@ -220,6 +355,19 @@ impl Line {
             })
             .sum()
     }
+    /// Grapheme covering given rendered column, grapheme count when the line is shorter
+    pub fn grapheme_idx_at(&self, column: usize) -> GraphemeIdx {
+        let mut width: usize = 0;
+
+        self.fragments()
+            .iter()
+            .take_while(|fragment| {
+                width = fragment.rendered_width.saturating_add(width);
+                width <= column
+            })
+            .count()
+    }
+
     fn get_replacement_character(grapheme: &str) -> Option<char> {
         let width = grapheme.width();
 
@@ -229,10 +377,11 @@ impl Line {
             _ if width > 0 && grapheme.trim().is_empty() => Some('␣'),
             _ if width == 0 => {
                 let mut chars = grapheme.chars();
-                if let Some(ch) = chars.next() {
-                    if ch.is_control() && chars.next().is_none() {
-                        return Some('▯');
-                    }
+                if let Some(ch) = chars.next()
+                    && ch.is_control()
+                    && chars.next().is_none()
+                {
+                    return Some('▯');
                 }
                 Some('·')
             }
@@ -243,38 +392,18 @@ impl Line {
 
     // =====================================================  HELPER METHODS =========================================================
 
-    // Finds all matches which align with grapheme boundaries.
-    // Parameters:
-    // - query: The query to search for.
-    // - matches: A vector of byte indices of potential matches, which might or might not align with the grapheme clusters.
-    // Returns:
-    // A Vec of (byte_index, grapheme_idx) pairs for each match that alignes with the grapheme clusters, where byte_index is the byte index of the match, and grapheme_idx is the grapheme index of the match.
-    fn match_graphme_clusters(
-        &self,
-        matches: &[ByteIdx],
-        query: &str,
-    ) -> Vec<(ByteIdx, GraphemeIdx)> {
-        let grapheme_count = query.graphemes(true).count();
-        matches
-            .iter()
-            .filter_map(|&start| {
-                let grapheme_idx = self.byte_idx_to_grapheme_idx(start);
-                self.fragments
-                    .get(grapheme_idx..grapheme_idx.saturating_add(grapheme_count)) // get all fragments that should be part of the match
-                    .and_then(|fragments| {
-                        let substring = fragments
-                            .iter()
-                            .map(|fragment| fragment.grapheme.as_str())
-                            .collect::<String>(); //combine the fragments into a single string.
-                        (substring == query).then_some((start, grapheme_idx))
-                        // if the combined string matches the query, we have an actual match.
-                    })
-            })
-            .collect()
+    /// Grapheme starting exactly at given byte, `None` when byte is in the middle of a grapheme
+    fn grapheme_boundary(&self, byte_idx: ByteIdx) -> Option<GraphemeIdx> {
+        if byte_idx == self.string.len() {
+            return Some(self.grapheme_count());
+        }
+
+        let grapheme_idx = self.byte_idx_to_grapheme_idx(byte_idx);
+        (self.grapheme_idx_to_byte_idx(grapheme_idx) == byte_idx).then_some(grapheme_idx)
     }
 
     pub fn clear(&mut self) {
-        self.fragments.clear();
+        self.rebuild_fragments();
         self.string.clear();
     }
 
@@ -319,3 +448,26 @@ impl Deref for Line {
         &self.string
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn wrapped_rows_start_where_the_width_runs_out() {
+        assert_eq!(Line::from("abcde").wrap_starts(3), [0, 3]);
+        assert_eq!(Line::from("").wrap_starts(3), [0]);
+        assert_eq!(Line::from("abc").wrap_starts(0), [0]);
+    }
+
+    #[test]
+    fn full_last_row_is_followed_by_an_empty_one() {
+        assert_eq!(Line::from("abcdef").wrap_starts(3), [0, 3, 6]);
+    }
+
+    #[test]
+    fn wide_graphemes_are_not_split_between_rows() {
+        assert_eq!(Line::from("a日本").wrap_starts(3), [0, 2]);
+        assert_eq!(Line::from("日本語").wrap_starts(3), [0, 1, 2]);
+    }
+}
@@ -0,0 +1,74 @@
+use std::collections::HashMap;
+
+use super::command::Command;
+
+/// Highest count a macro can be played with, playback runs on the UI thread
+pub const MAX_PLAYBACK_COUNT: usize = 1000;
+
+/// Sequences of commands recorded into registers named by a letter
+#[derive(Default)]
+pub struct MacroRegisters {
+    registers: HashMap<char, Vec<Command>>,
+    /// Register being recorded into, with the commands so far
+    recording: Option<(char, Vec<Command>)>,
+}
+
+impl MacroRegisters {
+    pub fn is_valid_name(name: char) -> bool {
+        name.is_ascii_alphabetic()
+    }
+
+    /// Whatever the register held is replaced once recording stops
+    pub fn start(&mut self, register: char) {
+        self.recording = Some((register, Vec::new()));
+    }
+
+    /// Returns the register and how many commands went into it
+    pub fn stop(&mut self) -> Option<(char, usize)> {
+        let (register, commands) = self.recording.take()?;
+        let count = commands.len();
+        self.registers.insert(register, commands);
+
+        Some((register, count))
+    }
+
+    pub fn recording(&self) -> Option<char> {
+        self.recording.as_ref().map(|(register, _)| *register)
+    }
+
+    /// Does nothing unless recording
+    pub fn record(&mut self, command: Command) {
+        if let Some((_, commands)) = &mut self.recording {
+            commands.push(command);
+        }
+    }
+
+    /// Copy of the commands, they are replayed while the registers may change
+    pub fn get(&self, register: char) -> Option<Vec<Command>> {
+        self.registers
+            .get(&register)
+            .filter(|commands| !commands.is_empty())
+            .cloned()
+    }
+
+    /// Parses `[count]register`, e.g. `a` or `12a`, the count goes up to `MAX_PLAYBACK_COUNT`
+    pub fn parse_playback(text: &str) -> Option<(usize, char)> {
+        let text = text.trim();
+        let register = text
+            .chars()
+            .last()
+            .filter(|chr| Self::is_valid_name(*chr))?;
+        let count = &text[..text.len().saturating_sub(register.len_utf8())];
+
+        let count = if count.is_empty() {
+            1
+        } else {
+            count
+                .parse::<usize>()
+                .ok()
+                .filter(|count| (1..=MAX_PLAYBACK_COUNT).contains(count))?
+        };
+
+        Some((count, register))
+    }
+}
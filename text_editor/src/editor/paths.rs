@@ -0,0 +1,32 @@
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+const EDITOR_DIR: &str = "editr";
+
+/// `$XDG_CONFIG_HOME/editr`, falling back to `~/.config/editr`
+pub fn config_dir() -> Option<PathBuf> {
+    env::var_os("XDG_CONFIG_HOME")
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))
+        .map(|dir| dir.join(EDITOR_DIR))
+}
+
+/// `$XDG_STATE_HOME/editr`, falling back to `~/.local/state/editr`
+pub fn state_dir() -> Option<PathBuf> {
+    env::var_os("XDG_STATE_HOME")
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| {
+            env::var_os("HOME").map(|home| PathBuf::from(home).join(".local").join("state"))
+        })
+        .map(|dir| dir.join(EDITOR_DIR))
+}
+
+/// Both names lead to the same file, also when written differently
+pub fn is_same_file(left: &str, right: &str) -> bool {
+    left == right
+        || fs::canonicalize(Path::new(left))
+            .is_ok_and(|left| fs::canonicalize(Path::new(right)).is_ok_and(|right| left == right))
+}
@@ -0,0 +1,76 @@
+/// Oldest entries are dropped past this many
+const MAX_ENTRIES: usize = 100;
+
+/// Earlier entries of a prompt, browsed with Up and Down like in a shell
+#[derive(Default)]
+pub struct PromptHistory {
+    entries: Vec<String>,
+    /// Entry shown at the moment, `None` while editing a new one
+    position: Option<usize>,
+    /// Line typed before browsing started, given back when going past the newest entry
+    draft: String,
+}
+
+impl PromptHistory {
+    /// Entries from an earlier session, oldest first
+    pub fn from_entries(mut entries: Vec<String>) -> Self {
+        let excess = entries.len().saturating_sub(MAX_ENTRIES);
+        entries.drain(..excess);
+
+        PromptHistory {
+            entries,
+            ..PromptHistory::default()
+        }
+    }
+
+    pub fn entries(&self) -> &[String] {
+        &self.entries
+    }
+
+    /// Repeated entries move to the end instead of being stored twice
+    pub fn push(&mut self, entry: &str) {
+        self.reset();
+
+        if entry.trim().is_empty() {
+            return;
+        }
+
+        self.entries.retain(|existing| existing != entry);
+        self.entries.push(entry.to_string());
+
+        if self.entries.len() > MAX_ENTRIES {
+            self.entries.remove(0);
+        }
+    }
+
+    pub fn reset(&mut self) {
+        self.position = None;
+        self.draft.clear();
+    }
+
+    /// Older entry, `current` is kept as draft when browsing starts
+    pub fn previous(&mut self, current: &str) -> Option<String> {
+        let position = if let Some(position) = self.position {
+            position.checked_sub(1)?
+        } else {
+            self.draft = current.to_string();
+            self.entries.len().checked_sub(1)?
+        };
+
+        self.position = Some(position);
+        self.entries.get(position).cloned()
+    }
+
+    /// Newer entry, the draft after the newest one
+    pub fn next(&mut self) -> Option<String> {
+        let position = self.position?.saturating_add(1);
+
+        if position < self.entries.len() {
+            self.position = Some(position);
+            self.entries.get(position).cloned()
+        } else {
+            self.position = None;
+            Some(std::mem::take(&mut self.draft))
+        }
+    }
+}
@@ -0,0 +1,157 @@
+use std::fmt::Display;
+use std::ops::Range;
+
+use regex::{Regex, RegexBuilder};
+
+use super::line::ByteIdx;
+
+#[derive(Clone, Copy, Default)]
+pub struct SearchOptions {
+    pub case_insensitive: bool,
+    pub whole_word: bool,
+    pub regex: bool,
+}
+
+impl Display for SearchOptions {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let flags: Vec<&str> = [
+            (self.case_insensitive, "Aa"),
+            (self.whole_word, "\\b"),
+            (self.regex, ".*"),
+        ]
+        .iter()
+        .filter_map(|(enabled, flag)| enabled.then_some(*flag))
+        .collect();
+
+        if flags.is_empty() {
+            Ok(())
+        } else {
+            write!(f, "[{}] ", flags.join(" "))
+        }
+    }
+}
+
+/// Compiled search query, literal searches are escaped so every search goes through the same engine
+#[derive(Clone)]
+pub struct SearchPattern {
+    regex: Regex,
+    options: SearchOptions,
+}
+
+impl SearchPattern {
+    pub fn new(query: &str, options: SearchOptions) -> Result<Self, regex::Error> {
+        let pattern = if options.regex {
+            query.to_string()
+        } else {
+            regex::escape(query)
+        };
+
+        let pattern = if options.whole_word {
+            format!(r"\b(?:{pattern})\b")
+        } else {
+            pattern
+        };
+
+        let regex = RegexBuilder::new(&pattern)
+            .case_insensitive(options.case_insensitive)
+            .build()?;
+
+        Ok(SearchPattern { regex, options })
+    }
+
+    /// Byte ranges of every non empty match in the haystack
+    pub fn find_matches(&self, haystack: &str) -> Vec<Range<ByteIdx>> {
+        self.regex
+            .find_iter(haystack)
+            .filter(|found| !found.is_empty())
+            .map(|found| found.range())
+            .collect()
+    }
+
+    /// Text that should replace the match starting at given byte of the line, regular expressions can refer to groups with `$1`
+    /// Groups are taken from the whole line so anchors and lookarounds see the same text as the search did
+    pub fn replacement_for(&self, line: &str, match_start: ByteIdx, replacement: &str) -> String {
+        if !self.options.regex {
+            return replacement.to_string();
+        }
+
+        let mut expanded = String::new();
+        if let Some(captures) = self.regex.captures_at(line, match_start) {
+            captures.expand(replacement, &mut expanded);
+        }
+        expanded
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pattern(
+        query: &str,
+        case_insensitive: bool,
+        whole_word: bool,
+        regex: bool,
+    ) -> SearchPattern {
+        let options = SearchOptions {
+            case_insensitive,
+            whole_word,
+            regex,
+        };
+        SearchPattern::new(query, options).expect("pattern is valid")
+    }
+
+    #[test]
+    fn options_change_what_matches() {
+        let haystack = "Foo food foo f.o";
+
+        assert_eq!(
+            pattern("foo", false, false, false).find_matches(haystack),
+            [4..7, 9..12]
+        );
+        assert_eq!(
+            pattern("foo", true, false, false)
+                .find_matches(haystack)
+                .len(),
+            3
+        );
+        assert_eq!(
+            pattern("foo", true, true, false).find_matches(haystack),
+            [0..3, 9..12]
+        );
+        assert_eq!(
+            pattern("f.o", false, false, false).find_matches(haystack),
+            vec![13..16]
+        );
+        assert_eq!(
+            pattern("f.o", false, false, true)
+                .find_matches(haystack)
+                .len(),
+            3
+        );
+    }
+
+    #[test]
+    fn empty_matches_are_skipped() {
+        assert!(
+            pattern("x*", false, false, true)
+                .find_matches("abc")
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn replacement_groups_come_from_the_match_in_its_line() {
+        let line = "ab ab";
+        let anchored = pattern(r"^(a)b", false, false, true);
+        assert_eq!(anchored.find_matches(line), vec![0..2]);
+        assert_eq!(anchored.replacement_for(line, 0, "[$1]"), "[a]");
+
+        let second = pattern(r"(\w)(\w)$", false, false, true);
+        assert_eq!(second.find_matches(line), vec![3..5]);
+        assert_eq!(second.replacement_for(line, 3, "$2$1"), "ba");
+
+        let literal = pattern("ab", false, false, false);
+        assert_eq!(literal.replacement_for(line, 0, "$1"), "$1");
+    }
+}
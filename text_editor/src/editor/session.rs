@@ -0,0 +1,99 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use super::{
+    paths,
+    ui_component::view::{file_io, location::Location},
+};
+
+const SESSION_FILE: &str = "session.toml";
+/// Files remembered with their caret location, the least recently used are forgotten first
+const MAX_FILES: usize = 100;
+/// Recent files offered on the welcome screen, one per digit key
+pub const MAX_RECENT_FILES: usize = 9;
+
+#[derive(Serialize, Deserialize)]
+struct FileState {
+    /// Absolute, so the file is found from any working directory
+    path: String,
+    location: Location,
+}
+
+/// State kept between launches in `~/.local/state/editr/session.toml`
+#[derive(Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Session {
+    /// Most recently used first
+    files: Vec<FileState>,
+    pub search_history: Vec<String>,
+    pub command_history: Vec<String>,
+    /// Nothing is written without it, e.g. for an editor under test
+    #[serde(skip)]
+    path: Option<PathBuf>,
+}
+
+impl Session {
+    /// Missing or unreadable state starts an empty session, it is not worth bothering the user about
+    pub fn load() -> Self {
+        paths::state_dir()
+            .map(|dir| Self::load_from(dir.join(SESSION_FILE)))
+            .unwrap_or_default()
+    }
+
+    /// Session written back to given file
+    pub fn load_from(path: PathBuf) -> Self {
+        let mut session: Session = fs::read_to_string(&path)
+            .ok()
+            .and_then(|content| toml::from_str(&content).ok())
+            .unwrap_or_default();
+        session.path = Some(path);
+        session
+    }
+
+    pub fn save(&self) -> Result<(), std::io::Error> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let content = toml::to_string(self).map_err(std::io::Error::other)?;
+        // A crash halfway through must not lose the recent files and histories
+        file_io::write_atomically(path, content.as_bytes())
+    }
+
+    /// Where the caret was when the file was left last time
+    pub fn location_of(&self, file_name: &str) -> Option<Location> {
+        let path = Self::absolute(file_name);
+        self.files
+            .iter()
+            .find(|file| file.path == path)
+            .map(|file| file.location)
+    }
+
+    /// Moves the file to the front of the recent files
+    pub fn remember(&mut self, file_name: &str, location: Location) {
+        let path = Self::absolute(file_name);
+        self.files.retain(|file| file.path != path);
+        self.files.insert(0, FileState { path, location });
+        self.files.truncate(MAX_FILES);
+    }
+
+    /// Most recently used first, files gone since are skipped
+    pub fn recent_files(&self) -> Vec<String> {
+        self.files
+            .iter()
+            .filter(|file| Path::new(&file.path).is_file())
+            .take(MAX_RECENT_FILES)
+            .map(|file| file.path.clone())
+            .collect()
+    }
+
+    fn absolute(file_name: &str) -> String {
+        fs::canonicalize(file_name)
+            .map_or_else(|_| file_name.to_string(), |path| path.display().to_string())
+    }
+}
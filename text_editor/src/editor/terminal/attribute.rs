@@ -1,103 +1,62 @@
 use crossterm::style::Color;
 
-use crate::editor::annotated_string::annotation_type::AnnotationType;
+use super::color_support::ColorSupport;
 
+#[derive(Clone, Copy, Default)]
 pub struct Attribute {
     pub foreground: Option<Color>,
     pub background: Option<Color>,
+    pub bold: bool,
+    pub italic: bool,
+    pub underline: bool,
 }
 
-impl From<AnnotationType> for Attribute {
-    fn from(annotation_type: AnnotationType) -> Self {
-        match annotation_type {
-            AnnotationType::Match => Self {
-                foreground: Some(Color::Rgb {
-                    r: 255,
-                    g: 255,
-                    b: 255,
-                }),
-                background: Some(Color::Rgb {
-                    r: 100,
-                    g: 100,
-                    b: 100,
-                }),
-            },
-            AnnotationType::SelectedMatch => Self {
-                foreground: Some(Color::Rgb {
-                    r: 255,
-                    g: 255,
-                    b: 255,
-                }),
-                background: Some(Color::Rgb {
-                    r: 255,
-                    g: 251,
-                    b: 0,
-                }),
-            },
-            AnnotationType::Number => Self {
-                foreground: Some(Color::Rgb {
-                    r: 255,
-                    g: 99,
-                    b: 71,
-                }),
-                background: None,
-            },
-            AnnotationType::KeyWord => Self {
-                foreground: Some(Color::Rgb {
-                    r: 100,
-                    g: 149,
-                    b: 237,
-                }),
-                background: None,
-            },
-            AnnotationType::Type => Self {
-                foreground: Some(Color::Rgb {
-                    r: 175,
-                    g: 255,
-                    b: 175,
-                }),
-                background: None,
-            },
-            AnnotationType::KnownValue => Self {
-                foreground: Some(Color::Rgb {
-                    r: 195,
-                    g: 177,
-                    b: 225,
-                }),
-                background: None,
-            },
-            AnnotationType::Char => Self {
-                foreground: Some(Color::Rgb {
-                    r: 255,
-                    g: 191,
-                    b: 0,
-                }),
-                background: None,
-            },
-            AnnotationType::Lifetime => Self {
-                foreground: Some(Color::Rgb {
-                    r: 102,
-                    g: 205,
-                    b: 170,
-                }),
-                background: None,
-            },
-            AnnotationType::Comment => Self {
-                foreground: Some(Color::Rgb {
-                    r: 34,
-                    g: 139,
-                    b: 34,
-                }),
-                background: None,
-            },
-            AnnotationType::String => Self {
-                foreground: Some(Color::Rgb {
-                    r: 255,
-                    g: 179,
-                    b: 102,
-                }),
-                background: None,
-            },
+impl Attribute {
+    pub fn foreground(r: u8, g: u8, b: u8) -> Self {
+        Self {
+            foreground: Some(Color::Rgb { r, g, b }),
+            ..Self::default()
+        }
+    }
+
+    pub fn background(r: u8, g: u8, b: u8) -> Self {
+        Self {
+            background: Some(Color::Rgb { r, g, b }),
+            ..Self::default()
+        }
+    }
+
+    pub fn on(self, r: u8, g: u8, b: u8) -> Self {
+        Self {
+            background: Some(Color::Rgb { r, g, b }),
+            ..self
+        }
+    }
+
+    pub fn bold(self) -> Self {
+        Self { bold: true, ..self }
+    }
+
+    pub fn italic(self) -> Self {
+        Self {
+            italic: true,
+            ..self
+        }
+    }
+
+    pub fn underline(self) -> Self {
+        Self {
+            underline: true,
+            ..self
+        }
+    }
+
+    /// Same attribute with colors the terminal is able to show
+    pub fn downgraded(self, color_support: ColorSupport) -> Self {
+        Self {
+            foreground: self.foreground.map(|color| color_support.downgrade(color)),
+            background: self.background.map(|color| color_support.downgrade(color)),
+            ..self
         }
     }
 }
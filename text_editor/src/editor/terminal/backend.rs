@@ -0,0 +1,105 @@
+use std::io::{Error, Write, stdout};
+
+use crossterm::{
+    cursor::{DisableBlinking, EnableBlinking, Hide, MoveTo, Show},
+    event::{DisableMouseCapture, EnableMouseCapture},
+    queue,
+    style::{
+        Attribute, Color, Print, ResetColor, SetAttribute, SetBackgroundColor, SetForegroundColor,
+    },
+    terminal::{
+        Clear, ClearType, DisableLineWrap, EnableLineWrap, EnterAlternateScreen,
+        LeaveAlternateScreen, SetTitle, disable_raw_mode, enable_raw_mode,
+    },
+};
+
+use super::super::{caret_position::CaretPosition, size::Size};
+
+/// Everything the editor asks of the screen, queued until the next flush
+pub enum Instruction<'a> {
+    Clear(ClearType),
+    EnterAlternateScreen,
+    LeaveAlternateScreen,
+    DisableLineWrap,
+    EnableLineWrap,
+    EnableMouseCapture,
+    DisableMouseCapture,
+    SetTitle(&'a str),
+    DisableBlinking,
+    EnableBlinking,
+    HideCaret,
+    ShowCaret,
+    Print(&'a str),
+    MoveTo(CaretPosition),
+    SetAttribute(Attribute),
+    SetForegroundColor(Color),
+    SetBackgroundColor(Color),
+    ResetColor,
+}
+
+/// Where the output of the editor ends up
+pub trait Backend {
+    fn enable_raw_mode(&mut self) -> Result<(), Error>;
+    fn disable_raw_mode(&mut self) -> Result<(), Error>;
+    fn size(&self) -> Result<Size, Error>;
+    fn queue(&mut self, instruction: Instruction) -> Result<(), Error>;
+    fn flush(&mut self) -> Result<(), Error>;
+}
+
+/// The real terminal on stdout
+pub struct CrosstermBackend;
+
+impl Backend for CrosstermBackend {
+    fn enable_raw_mode(&mut self) -> Result<(), Error> {
+        enable_raw_mode()
+    }
+
+    fn disable_raw_mode(&mut self) -> Result<(), Error> {
+        disable_raw_mode()
+    }
+
+    fn size(&self) -> Result<Size, Error> {
+        let size = crossterm::terminal::size()?;
+
+        Ok(Size {
+            columns: size.0 as usize,
+            rows: size.1 as usize,
+        })
+    }
+
+    /// There could be and edge case on systems where u16 < usize
+    /// In that case check the `MoveTo` conversion
+    fn queue(&mut self, instruction: Instruction) -> Result<(), Error> {
+        let mut stdout = stdout();
+        match instruction {
+            Instruction::Clear(clear_type) => queue!(stdout, Clear(clear_type)),
+            Instruction::EnterAlternateScreen => queue!(stdout, EnterAlternateScreen),
+            Instruction::LeaveAlternateScreen => queue!(stdout, LeaveAlternateScreen),
+            Instruction::DisableLineWrap => queue!(stdout, DisableLineWrap),
+            Instruction::EnableLineWrap => queue!(stdout, EnableLineWrap),
+            Instruction::EnableMouseCapture => queue!(stdout, EnableMouseCapture),
+            Instruction::DisableMouseCapture => queue!(stdout, DisableMouseCapture),
+            Instruction::SetTitle(title) => queue!(stdout, SetTitle(title)),
+            Instruction::DisableBlinking => queue!(stdout, DisableBlinking),
+            Instruction::EnableBlinking => queue!(stdout, EnableBlinking),
+            Instruction::HideCaret => queue!(stdout, Hide),
+            Instruction::ShowCaret => queue!(stdout, Show),
+            Instruction::Print(text) => queue!(stdout, Print(text)),
+            Instruction::MoveTo(position) => {
+                #[allow(clippy::as_conversions, clippy::cast_possible_truncation)]
+                let x = position.column as u16;
+                #[allow(clippy::as_conversions, clippy::cast_possible_truncation)]
+                let y = position.row as u16;
+                queue!(stdout, MoveTo(x, y))
+            }
+            Instruction::SetAttribute(attribute) => queue!(stdout, SetAttribute(attribute)),
+            Instruction::SetForegroundColor(color) => queue!(stdout, SetForegroundColor(color)),
+            Instruction::SetBackgroundColor(color) => queue!(stdout, SetBackgroundColor(color)),
+            Instruction::ResetColor => queue!(stdout, ResetColor),
+        }
+    }
+
+    fn flush(&mut self) -> Result<(), Error> {
+        stdout().flush()
+    }
+}
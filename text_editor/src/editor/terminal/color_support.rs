@@ -0,0 +1,200 @@
+use std::env;
+
+use crossterm::style::Color;
+use serde::Deserialize;
+
+/// Levels of the 6x6x6 color cube of 256 color terminals
+const CUBE_LEVELS: [u8; 6] = [0, 95, 135, 175, 215, 255];
+/// First index of the color cube and of the grayscale ramp in the 256 color palette
+const CUBE_START: u8 = 16;
+const GRAYSCALE_START: u8 = 232;
+
+/// The basic colors with their usual xterm values, in palette order
+const BASIC_COLORS: [(Color, (u8, u8, u8)); 16] = [
+    (Color::Black, (0, 0, 0)),
+    (Color::DarkRed, (128, 0, 0)),
+    (Color::DarkGreen, (0, 128, 0)),
+    (Color::DarkYellow, (128, 128, 0)),
+    (Color::DarkBlue, (0, 0, 128)),
+    (Color::DarkMagenta, (128, 0, 128)),
+    (Color::DarkCyan, (0, 128, 128)),
+    (Color::Grey, (192, 192, 192)),
+    (Color::DarkGrey, (128, 128, 128)),
+    (Color::Red, (255, 0, 0)),
+    (Color::Green, (0, 255, 0)),
+    (Color::Yellow, (255, 255, 0)),
+    (Color::Blue, (0, 0, 255)),
+    (Color::Magenta, (255, 0, 255)),
+    (Color::Cyan, (0, 255, 255)),
+    (Color::White, (255, 255, 255)),
+];
+
+/// How many colors the terminal can show
+#[derive(Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ColorSupport {
+    #[default]
+    TrueColor,
+    Ansi256,
+    Ansi16,
+}
+
+impl ColorSupport {
+    /// Guess based on `COLORTERM` and `TERM`, the same variables other terminal programs look at
+    pub fn detect() -> Self {
+        let colorterm = env::var("COLORTERM").unwrap_or_default();
+        let term = env::var("TERM").unwrap_or_default();
+
+        if colorterm == "truecolor" || colorterm == "24bit" {
+            ColorSupport::TrueColor
+        } else if term.contains("256color") {
+            ColorSupport::Ansi256
+        } else {
+            ColorSupport::Ansi16
+        }
+    }
+
+    /// Closest color the terminal is able to show, named colors are shown by every terminal
+    pub fn downgrade(self, color: Color) -> Color {
+        match (self, color) {
+            (ColorSupport::Ansi256, Color::Rgb { r, g, b }) => {
+                Color::AnsiValue(Self::nearest_ansi_value(r, g, b))
+            }
+            (ColorSupport::Ansi16, Color::Rgb { r, g, b }) => Self::nearest_basic(r, g, b),
+            (ColorSupport::Ansi16, Color::AnsiValue(value)) => {
+                let (r, g, b) = Self::ansi_value_rgb(value);
+                Self::nearest_basic(r, g, b)
+            }
+            _ => color,
+        }
+    }
+
+    /// Better of the closest color cube entry and the closest gray
+    fn nearest_ansi_value(r: u8, g: u8, b: u8) -> u8 {
+        let cube = |channel: u8| -> u8 {
+            CUBE_LEVELS
+                .iter()
+                .enumerate()
+                .min_by_key(|(_, level)| level.abs_diff(channel))
+                .and_then(|(idx, _)| u8::try_from(idx).ok())
+                .unwrap_or(0)
+        };
+        let cube_value = CUBE_START
+            .saturating_add(cube(r).saturating_mul(36))
+            .saturating_add(cube(g).saturating_mul(6))
+            .saturating_add(cube(b));
+
+        let average = (u16::from(r) + u16::from(g) + u16::from(b)) / 3;
+        let gray_step = u8::try_from(average.saturating_sub(3) / 10)
+            .unwrap_or(u8::MAX)
+            .min(23);
+        let gray_value = GRAYSCALE_START.saturating_add(gray_step);
+
+        [cube_value, gray_value]
+            .into_iter()
+            .min_by_key(|value| Self::distance((r, g, b), Self::ansi_value_rgb(*value)))
+            .unwrap_or(cube_value)
+    }
+
+    fn nearest_basic(r: u8, g: u8, b: u8) -> Color {
+        BASIC_COLORS
+            .iter()
+            .min_by_key(|(_, rgb)| Self::distance((r, g, b), *rgb))
+            .map_or(Color::White, |(color, _)| *color)
+    }
+
+    fn ansi_value_rgb(value: u8) -> (u8, u8, u8) {
+        if let Some(cube_idx) = value.checked_sub(CUBE_START)
+            && value < GRAYSCALE_START
+        {
+            let level = |idx: u8| CUBE_LEVELS[usize::from(idx % 6)];
+            return (level(cube_idx / 36), level(cube_idx / 6), level(cube_idx));
+        }
+
+        if let Some(gray_step) = value.checked_sub(GRAYSCALE_START) {
+            let gray = gray_step.saturating_mul(10).saturating_add(8);
+            return (gray, gray, gray);
+        }
+
+        BASIC_COLORS[usize::from(value)].1
+    }
+
+    fn distance(first: (u8, u8, u8), second: (u8, u8, u8)) -> u32 {
+        let squared = |a: u8, b: u8| u32::from(a.abs_diff(b)).pow(2);
+
+        squared(first.0, second.0) + squared(first.1, second.1) + squared(first.2, second.2)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rgb(r: u8, g: u8, b: u8) -> Color {
+        Color::Rgb { r, g, b }
+    }
+
+    #[test]
+    fn rgb_is_quantized_to_the_cube_or_the_gray_ramp() {
+        for (color, value) in [
+            (rgb(0xff, 0x63, 0x47), 203),
+            (rgb(0x80, 0x80, 0x80), 244),
+            (rgb(0, 0, 0), 16),
+            (rgb(255, 255, 255), 231),
+            (rgb(8, 8, 8), 232),
+            (rgb(238, 238, 238), 255),
+            (rgb(255, 0, 0), 196),
+            (rgb(0, 95, 135), 24),
+        ] {
+            assert_eq!(
+                ColorSupport::Ansi256.downgrade(color),
+                Color::AnsiValue(value),
+                "{color:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn ansi_values_map_back_to_their_rgb() {
+        for (value, expected) in [
+            (9, (255, 0, 0)),
+            (16, (0, 0, 0)),
+            (24, (0, 95, 135)),
+            (196, (255, 0, 0)),
+            (231, (255, 255, 255)),
+            (232, (8, 8, 8)),
+            (244, (128, 128, 128)),
+            (255, (238, 238, 238)),
+        ] {
+            assert_eq!(ColorSupport::ansi_value_rgb(value), expected, "{value}");
+        }
+    }
+
+    #[test]
+    fn sixteen_colors_fall_back_to_the_nearest_basic_color() {
+        for (color, expected) in [
+            (Color::AnsiValue(244), Color::DarkGrey),
+            (Color::AnsiValue(196), Color::Red),
+            (Color::AnsiValue(255), Color::White),
+            (Color::AnsiValue(4), Color::DarkBlue),
+            (rgb(0xff, 0x63, 0x47), Color::Red),
+            (rgb(10, 10, 120), Color::DarkBlue),
+            (rgb(200, 200, 190), Color::Grey),
+        ] {
+            assert_eq!(ColorSupport::Ansi16.downgrade(color), expected, "{color:?}");
+        }
+    }
+
+    #[test]
+    fn colors_the_terminal_shows_are_kept() {
+        assert_eq!(
+            ColorSupport::TrueColor.downgrade(rgb(1, 2, 3)),
+            rgb(1, 2, 3)
+        );
+        assert_eq!(
+            ColorSupport::Ansi256.downgrade(Color::AnsiValue(42)),
+            Color::AnsiValue(42)
+        );
+        assert_eq!(ColorSupport::Ansi16.downgrade(Color::Cyan), Color::Cyan);
+    }
+}
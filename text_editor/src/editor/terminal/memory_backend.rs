@@ -0,0 +1,162 @@
+use std::{cell::RefCell, io::Error, rc::Rc};
+
+use crossterm::terminal::ClearType;
+use unicode_width::UnicodeWidthChar;
+
+use super::{
+    super::{caret_position::CaretPosition, size::Size},
+    backend::{Backend, Instruction},
+};
+
+/// Grid of cells, each holding what was printed at that column
+struct Screen {
+    size: Size,
+    /// A wide character leaves the cell right of it empty
+    cells: Vec<Vec<String>>,
+    caret: CaretPosition,
+    caret_visible: bool,
+    title: String,
+}
+
+impl Screen {
+    fn blank_row(columns: usize) -> Vec<String> {
+        vec![String::from(" "); columns]
+    }
+
+    fn print(&mut self, text: &str) {
+        let columns = self.size.columns;
+        let Some(row) = self.cells.get_mut(self.caret.row) else {
+            return;
+        };
+
+        for chr in text.chars() {
+            let width = chr.width().unwrap_or(0);
+            if width == 0 {
+                // Combining characters stick to the cell before them
+                if let Some(previous) = self
+                    .caret
+                    .column
+                    .checked_sub(1)
+                    .and_then(|column| row.get_mut(column))
+                {
+                    previous.push(chr);
+                }
+                continue;
+            }
+
+            // Line wrap is disabled, whatever does not fit is dropped
+            if self.caret.column.saturating_add(width) <= columns {
+                row[self.caret.column] = chr.to_string();
+                for continuation in 1..width {
+                    row[self.caret.column.saturating_add(continuation)] = String::new();
+                }
+            }
+            self.caret.column = self.caret.column.saturating_add(width);
+        }
+    }
+
+    fn clear(&mut self, clear_type: ClearType) {
+        let columns = self.size.columns;
+        match clear_type {
+            ClearType::All | ClearType::Purge => {
+                for row in &mut self.cells {
+                    *row = Self::blank_row(columns);
+                }
+            }
+            ClearType::CurrentLine => {
+                if let Some(row) = self.cells.get_mut(self.caret.row) {
+                    *row = Self::blank_row(columns);
+                }
+            }
+            ClearType::UntilNewLine => {
+                let column = self.caret.column;
+                if let Some(row) = self.cells.get_mut(self.caret.row) {
+                    for cell in row.iter_mut().skip(column) {
+                        *cell = String::from(" ");
+                    }
+                }
+            }
+            ClearType::FromCursorDown | ClearType::FromCursorUp => {}
+        }
+    }
+}
+
+/// Keeps the screen in memory instead of drawing it, clones look at the same screen
+#[derive(Clone)]
+pub struct MemoryBackend {
+    screen: Rc<RefCell<Screen>>,
+}
+
+impl MemoryBackend {
+    pub fn new(size: Size) -> Self {
+        let screen = Screen {
+            size,
+            cells: vec![Screen::blank_row(size.columns); size.rows],
+            caret: CaretPosition::default(),
+            caret_visible: true,
+            title: String::new(),
+        };
+
+        MemoryBackend {
+            screen: Rc::new(RefCell::new(screen)),
+        }
+    }
+
+    /// Text of the row with trailing blanks removed
+    pub fn row(&self, row: usize) -> String {
+        self.screen
+            .borrow()
+            .cells
+            .get(row)
+            .map(|cells| cells.concat().trim_end().to_string())
+            .unwrap_or_default()
+    }
+
+    pub fn rows(&self) -> Vec<String> {
+        let rows = self.screen.borrow().size.rows;
+        (0..rows).map(|row| self.row(row)).collect()
+    }
+
+    /// Where the caret was left by the last drawing, `None` while hidden
+    pub fn caret(&self) -> Option<CaretPosition> {
+        let screen = self.screen.borrow();
+        screen.caret_visible.then_some(screen.caret)
+    }
+
+    pub fn title(&self) -> String {
+        self.screen.borrow().title.clone()
+    }
+}
+
+impl Backend for MemoryBackend {
+    fn enable_raw_mode(&mut self) -> Result<(), Error> {
+        Ok(())
+    }
+
+    fn disable_raw_mode(&mut self) -> Result<(), Error> {
+        Ok(())
+    }
+
+    fn size(&self) -> Result<Size, Error> {
+        Ok(self.screen.borrow().size)
+    }
+
+    fn queue(&mut self, instruction: Instruction) -> Result<(), Error> {
+        let mut screen = self.screen.borrow_mut();
+        match instruction {
+            Instruction::Clear(clear_type) => screen.clear(clear_type),
+            Instruction::SetTitle(title) => screen.title = title.to_string(),
+            Instruction::HideCaret => screen.caret_visible = false,
+            Instruction::ShowCaret => screen.caret_visible = true,
+            Instruction::Print(text) => screen.print(text),
+            Instruction::MoveTo(position) => screen.caret = position,
+            // Modes and styles do not change the text on screen
+            _ => {}
+        }
+        Ok(())
+    }
+
+    fn flush(&mut self) -> Result<(), Error> {
+        Ok(())
+    }
+}
@@ -1,28 +1,45 @@
-use std::io::{Error, Write, stdout};
+use std::cell::RefCell;
+use std::io::{Error, stdout};
 
 mod attribute;
+pub mod backend;
+pub mod color_support;
+#[cfg(test)]
+pub mod memory_backend;
+pub mod palette;
+pub mod theme;
 
-use crossterm::{
-    Command,
-    cursor::{DisableBlinking, EnableBlinking, Hide, MoveTo, SavePosition, Show},
-    execute, queue,
-    style::{Attribute, Print, ResetColor, SetBackgroundColor, SetForegroundColor},
-    terminal::{
-        Clear, ClearType, DisableLineWrap, EnableLineWrap, EnterAlternateScreen,
-        LeaveAlternateScreen, SetTitle, disable_raw_mode, enable_raw_mode,
-    },
-};
+use crossterm::{cursor::SavePosition, execute, style::Attribute, terminal::ClearType};
+
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
 
 use super::{annotated_string::AnnotatedString, caret_position::CaretPosition, size::Size};
+use backend::{Backend, CrosstermBackend, Instruction};
+use palette::ThemeName;
+use theme::Theme;
+
+thread_local! {
+    /// Colors used for annotated text, drawing always happens on the main thread
+    static THEME: RefCell<Theme> = RefCell::new(Theme::default());
+    /// Where drawing ends up, the real terminal unless replaced
+    static BACKEND: RefCell<Box<dyn Backend>> = RefCell::new(Box::new(CrosstermBackend));
+}
 
 pub struct Terminal;
 
 impl Terminal {
+    /// Everything drawn on this thread goes to given backend from now on
+    #[cfg(test)]
+    pub fn set_backend(backend: Box<dyn Backend>) {
+        BACKEND.set(backend);
+    }
+
     pub fn init() -> Result<(), std::io::Error> {
-        enable_raw_mode()?;
+        BACKEND.with_borrow_mut(|backend| backend.enable_raw_mode())?;
         Self::enter_alternate_screen()?;
         Self::disable_line_wrap()?;
         Self::disable_blinking()?;
+        Self::enable_mouse_capture()?;
         Self::clear_screen()?;
         Self::draw()?;
         Ok(())
@@ -33,54 +50,60 @@ impl Terminal {
         Self::show_caret()?;
         Self::enable_line_wrap()?;
         Self::enable_blinking()?;
+        Self::disable_mouse_capture()?;
         Self::draw()?;
-        disable_raw_mode()?;
+        BACKEND.with_borrow_mut(|backend| backend.disable_raw_mode())?;
         Ok(())
     }
 
     pub fn clear_screen() -> Result<(), std::io::Error> {
         Self::move_caret_to(CaretPosition { column: 0, row: 0 })?;
-        Self::queue_command(Clear(ClearType::All))?;
+        Self::queue_command(Instruction::Clear(ClearType::All))?;
         Ok(())
     }
 
     pub fn clear_line() -> Result<(), std::io::Error> {
-        Self::queue_command(Clear(ClearType::CurrentLine))?;
+        Self::queue_command(Instruction::Clear(ClearType::CurrentLine))?;
         Ok(())
     }
 
     pub fn enter_alternate_screen() -> Result<(), Error> {
-        Self::queue_command(EnterAlternateScreen)?;
+        Self::queue_command(Instruction::EnterAlternateScreen)?;
         Ok(())
     }
 
     pub fn exit_alternate_screen() -> Result<(), Error> {
-        Self::queue_command(LeaveAlternateScreen)?;
+        Self::queue_command(Instruction::LeaveAlternateScreen)?;
         Ok(())
     }
 
     pub fn disable_line_wrap() -> Result<(), Error> {
-        Self::queue_command(DisableLineWrap)?;
+        Self::queue_command(Instruction::DisableLineWrap)?;
         Ok(())
     }
 
     pub fn enable_line_wrap() -> Result<(), Error> {
-        Self::queue_command(EnableLineWrap)?;
+        Self::queue_command(Instruction::EnableLineWrap)?;
+        Ok(())
+    }
+
+    pub fn enable_mouse_capture() -> Result<(), Error> {
+        Self::queue_command(Instruction::EnableMouseCapture)?;
+        Ok(())
+    }
+
+    pub fn disable_mouse_capture() -> Result<(), Error> {
+        Self::queue_command(Instruction::DisableMouseCapture)?;
         Ok(())
     }
 
     pub fn set_title(title: &str) -> Result<(), Error> {
-        Self::queue_command(SetTitle(title))?;
+        Self::queue_command(Instruction::SetTitle(title))?;
         Ok(())
     }
 
     pub fn size() -> Result<Size, std::io::Error> {
-        let size = crossterm::terminal::size()?;
-
-        Ok(Size {
-            columns: size.0 as usize,
-            rows: size.1 as usize,
-        })
+        BACKEND.with_borrow(|backend| backend.size())
     }
 
     pub fn _get_caret_position() -> Result<CaretPosition, std::io::Error> {
@@ -96,90 +119,139 @@ impl Terminal {
     }
 
     pub fn disable_blinking() -> Result<(), std::io::Error> {
-        Self::queue_command(DisableBlinking)?;
+        Self::queue_command(Instruction::DisableBlinking)?;
         Ok(())
     }
     pub fn enable_blinking() -> Result<(), std::io::Error> {
-        Self::queue_command(EnableBlinking)?;
+        Self::queue_command(Instruction::EnableBlinking)?;
         Ok(())
     }
 
     pub fn hide_caret() -> Result<(), std::io::Error> {
-        Self::queue_command(Hide)?;
+        Self::queue_command(Instruction::HideCaret)?;
         Ok(())
     }
 
     pub fn show_caret() -> Result<(), std::io::Error> {
-        Self::queue_command(Show)?;
+        Self::queue_command(Instruction::ShowCaret)?;
         Ok(())
     }
 
     pub fn print(string: &str) -> Result<(), std::io::Error> {
-        Self::queue_command(Print(string))?;
+        Self::queue_command(Instruction::Print(string))?;
         Ok(())
     }
 
     pub fn draw() -> Result<(), std::io::Error> {
-        stdout().flush()?;
-        Ok(())
+        BACKEND.with_borrow_mut(|backend| backend.flush())
     }
 
-    /// There could be and edge case on systems where u16 < usize
-    /// In that case check this piece of code
-    /// Maybe need to refactor it
     pub fn move_caret_to(caret_positon: CaretPosition) -> Result<(), std::io::Error> {
-        #[allow(clippy::as_conversions, clippy::cast_possible_truncation)]
-        let x = caret_positon.column as u16;
-        #[allow(clippy::as_conversions, clippy::cast_possible_truncation)]
-        let y = caret_positon.row as u16;
-        Self::queue_command(MoveTo(x, y))?;
+        Self::queue_command(Instruction::MoveTo(caret_positon))?;
         Ok(())
     }
 
-    pub fn print_annoted_line(
-        row: usize,
-        annotated_string: AnnotatedString,
+    pub fn set_theme(theme: Theme) {
+        THEME.set(theme);
+    }
+
+    /// Overrides from the config stay in place
+    pub fn switch_theme(name: ThemeName) {
+        THEME.with_borrow_mut(|theme| theme.name = name);
+    }
+
+    pub fn theme_name() -> ThemeName {
+        THEME.with_borrow(|theme| theme.name)
+    }
+
+    /// Only `width` columns from the position are touched, whatever is left of them after the text is cleared
+    pub fn print_annotated_at(
+        position: CaretPosition,
+        width: usize,
+        annotated_string: &AnnotatedString,
     ) -> Result<(), std::io::Error> {
-        Self::move_caret_to(CaretPosition { column: 0, row })?;
-        Self::clear_line()?;
-        for part in annotated_string.into_iter() {
+        Self::move_caret_to(position)?;
+        let mut used: usize = 0;
+        for part in annotated_string {
             if let Some(a_type) = part.annotaion_type {
-                let atr = a_type.into();
+                let atr = THEME.with_borrow(|theme| theme.attribute(a_type));
                 Self::set_attribute(atr)?;
             }
-            Self::print(&format!("{}", part.string))?;
-            Self::reset_color()?;
+            Self::print(part.string)?;
+            Self::reset_style()?;
+            used = used.saturating_add(part.string.width());
         }
+        Self::print(&" ".repeat(width.saturating_sub(used)))?;
         Ok(())
     }
 
-    fn reset_color() -> Result<(), std::io::Error> {
-        Self::queue_command(ResetColor)?;
+    fn reset_style() -> Result<(), std::io::Error> {
+        Self::queue_command(Instruction::SetAttribute(Attribute::Reset))?;
+        Self::queue_command(Instruction::ResetColor)?;
         Ok(())
     }
 
     fn set_attribute(attribute: attribute::Attribute) -> Result<(), std::io::Error> {
         if let Some(foreground) = attribute.foreground {
-            Self::queue_command(SetForegroundColor(foreground))?;
+            Self::queue_command(Instruction::SetForegroundColor(foreground))?;
         }
 
         if let Some(background) = attribute.background {
-            Self::queue_command(SetBackgroundColor(background))?;
+            Self::queue_command(Instruction::SetBackgroundColor(background))?;
+        }
+
+        if attribute.bold {
+            Self::queue_command(Instruction::SetAttribute(Attribute::Bold))?;
+        }
+
+        if attribute.italic {
+            Self::queue_command(Instruction::SetAttribute(Attribute::Italic))?;
+        }
+
+        if attribute.underline {
+            Self::queue_command(Instruction::SetAttribute(Attribute::Underlined))?;
         }
         Ok(())
     }
 
-    pub fn print_row_with_attribute(
-        row: usize,
-        attr: Attribute,
+    /// Text is cut off or padded to exactly `width` columns, so neighbouring splits are left alone
+    pub fn print_at_with_attributes(
+        position: CaretPosition,
+        width: usize,
+        attributes: &[Attribute],
         line_text: &str,
     ) -> Result<(), std::io::Error> {
-        Self::move_caret_to(CaretPosition { column: 0, row })?;
-        Self::clear_line()?;
-        Self::print(&format!("{}{}{}", attr, line_text, Attribute::Reset))?;
+        Self::move_caret_to(position)?;
+        for attribute in attributes {
+            Self::queue_command(Instruction::SetAttribute(*attribute))?;
+        }
+        Self::print(&Self::fit_to_width(line_text, width))?;
+        Self::reset_style()?;
         Ok(())
     }
 
+    pub fn print_at(
+        position: CaretPosition,
+        width: usize,
+        line_text: &str,
+    ) -> Result<(), std::io::Error> {
+        Self::print_at_with_attributes(position, width, &[], line_text)
+    }
+
+    fn fit_to_width(text: &str, width: usize) -> String {
+        let mut used: usize = 0;
+        let mut fitted: String = text
+            .chars()
+            .take_while(|chr| {
+                used = used.saturating_add(chr.width().unwrap_or(0));
+                used <= width
+            })
+            .collect();
+
+        fitted.push_str(&" ".repeat(width.saturating_sub(fitted.width())));
+        fitted
+    }
+
     pub fn print_row(row: usize, line_text: &str) -> Result<(), std::io::Error> {
         Self::move_caret_to(CaretPosition { column: 0, row })?;
         Self::clear_line()?;
@@ -187,9 +259,7 @@ impl Terminal {
         Ok(())
     }
 
-    fn queue_command<T: Command>(command: T) -> Result<(), std::io::Error> {
-        let mut stdout = stdout();
-        queue!(stdout, command)?;
-        Ok(())
+    fn queue_command(instruction: Instruction) -> Result<(), std::io::Error> {
+        BACKEND.with_borrow_mut(|backend| backend.queue(instruction))
     }
 }
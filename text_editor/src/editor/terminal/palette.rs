@@ -0,0 +1,110 @@
+use std::fmt::{self, Display};
+
+use serde::Deserialize;
+
+use crate::editor::annotated_string::annotation_type::AnnotationType;
+
+use super::attribute::Attribute;
+
+/// Built-in themes, annotation types can still be overridden one by one in the config
+#[derive(Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ThemeName {
+    #[default]
+    Dark,
+    /// Meant for terminals with a light background
+    Light,
+    HighContrast,
+}
+
+impl ThemeName {
+    pub fn next(self) -> Self {
+        match self {
+            ThemeName::Dark => ThemeName::Light,
+            ThemeName::Light => ThemeName::HighContrast,
+            ThemeName::HighContrast => ThemeName::Dark,
+        }
+    }
+
+    pub fn attribute(self, annotation_type: AnnotationType) -> Attribute {
+        match self {
+            ThemeName::Dark => dark(annotation_type),
+            ThemeName::Light => light(annotation_type),
+            ThemeName::HighContrast => high_contrast(annotation_type),
+        }
+    }
+}
+
+impl Display for ThemeName {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            ThemeName::Dark => "dark",
+            ThemeName::Light => "light",
+            ThemeName::HighContrast => "high contrast",
+        };
+        write!(f, "{name}")
+    }
+}
+
+fn dark(annotation_type: AnnotationType) -> Attribute {
+    match annotation_type {
+        AnnotationType::Match => Attribute::foreground(255, 255, 255).on(100, 100, 100),
+        AnnotationType::SelectedMatch => Attribute::foreground(255, 255, 255).on(255, 251, 0),
+        AnnotationType::Number => Attribute::foreground(255, 99, 71),
+        AnnotationType::KeyWord => Attribute::foreground(100, 149, 237),
+        AnnotationType::Type => Attribute::foreground(175, 255, 175),
+        AnnotationType::KnownValue => Attribute::foreground(195, 177, 225),
+        AnnotationType::Char => Attribute::foreground(255, 191, 0),
+        AnnotationType::Lifetime => Attribute::foreground(102, 205, 170),
+        AnnotationType::Comment => Attribute::foreground(34, 139, 34).italic(),
+        AnnotationType::String => Attribute::foreground(255, 179, 102),
+        AnnotationType::Selection => Attribute::background(38, 79, 120),
+        AnnotationType::LineNumber => Attribute::foreground(110, 110, 110),
+        AnnotationType::CurrentLineNumber => Attribute::foreground(210, 210, 210),
+        AnnotationType::ModifiedLine => Attribute::foreground(230, 180, 80),
+        AnnotationType::SearchHit => Attribute::foreground(255, 251, 0),
+        AnnotationType::Diagnostic => Attribute::foreground(240, 70, 70),
+    }
+}
+
+fn light(annotation_type: AnnotationType) -> Attribute {
+    match annotation_type {
+        AnnotationType::Match => Attribute::foreground(0, 0, 0).on(255, 236, 140),
+        AnnotationType::SelectedMatch => Attribute::foreground(0, 0, 0).on(255, 170, 0),
+        AnnotationType::Number => Attribute::foreground(9, 134, 88),
+        AnnotationType::KeyWord => Attribute::foreground(0, 0, 200),
+        AnnotationType::Type => Attribute::foreground(38, 127, 153),
+        AnnotationType::KnownValue => Attribute::foreground(128, 0, 128),
+        AnnotationType::Char => Attribute::foreground(170, 55, 0),
+        AnnotationType::Lifetime => Attribute::foreground(0, 112, 112),
+        AnnotationType::Comment => Attribute::foreground(0, 128, 0).italic(),
+        AnnotationType::String => Attribute::foreground(163, 21, 21),
+        AnnotationType::Selection => Attribute::background(173, 214, 255),
+        AnnotationType::LineNumber => Attribute::foreground(150, 150, 150),
+        AnnotationType::CurrentLineNumber => Attribute::foreground(40, 40, 40),
+        AnnotationType::ModifiedLine => Attribute::foreground(190, 120, 0),
+        AnnotationType::SearchHit => Attribute::foreground(200, 120, 0),
+        AnnotationType::Diagnostic => Attribute::foreground(205, 0, 0),
+    }
+}
+
+/// Saturated colors only, structure is carried by bold and underline as well
+fn high_contrast(annotation_type: AnnotationType) -> Attribute {
+    match annotation_type {
+        AnnotationType::Match => Attribute::foreground(0, 0, 0).on(255, 255, 255),
+        AnnotationType::SelectedMatch => Attribute::foreground(0, 0, 0).on(255, 255, 0),
+        AnnotationType::Number | AnnotationType::Char => Attribute::foreground(255, 0, 255),
+        AnnotationType::KeyWord => Attribute::foreground(0, 255, 255).bold(),
+        AnnotationType::Type => Attribute::foreground(0, 255, 0).bold(),
+        AnnotationType::KnownValue | AnnotationType::Lifetime => Attribute::foreground(255, 255, 0),
+        AnnotationType::Comment => Attribute::foreground(192, 192, 192).italic(),
+        AnnotationType::String => Attribute::foreground(0, 255, 0),
+        AnnotationType::Selection => Attribute::foreground(0, 0, 0).on(0, 255, 255),
+        AnnotationType::LineNumber => Attribute::foreground(192, 192, 192),
+        AnnotationType::CurrentLineNumber => Attribute::foreground(255, 255, 255).bold(),
+        AnnotationType::ModifiedLine | AnnotationType::SearchHit => {
+            Attribute::foreground(255, 255, 0).bold()
+        }
+        AnnotationType::Diagnostic => Attribute::foreground(255, 0, 0).bold().underline(),
+    }
+}
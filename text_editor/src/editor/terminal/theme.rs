@@ -0,0 +1,99 @@
+use std::collections::HashMap;
+
+use crossterm::style::Color;
+use serde::Deserialize;
+
+use crate::editor::annotated_string::annotation_type::AnnotationType;
+
+use super::{attribute::Attribute, color_support::ColorSupport, palette::ThemeName};
+
+/// Named theme with annotation types overridden by the user, colors are downgraded to what the terminal supports
+#[derive(Default, Deserialize)]
+#[serde(from = "HashMap<AnnotationType, AttributeSetting>")]
+pub struct Theme {
+    pub name: ThemeName,
+    pub color_support: ColorSupport,
+    overrides: HashMap<AnnotationType, Attribute>,
+}
+
+impl Theme {
+    pub fn attribute(&self, annotation_type: AnnotationType) -> Attribute {
+        self.overrides
+            .get(&annotation_type)
+            .copied()
+            .unwrap_or_else(|| self.name.attribute(annotation_type))
+            .downgraded(self.color_support)
+    }
+}
+
+/// Missing colors keep the terminal default, missing styles are off
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct AttributeSetting {
+    foreground: Option<ThemeColor>,
+    background: Option<ThemeColor>,
+    #[serde(default)]
+    bold: bool,
+    #[serde(default)]
+    italic: bool,
+    #[serde(default)]
+    underline: bool,
+}
+
+/// `#rrggbb`, an ANSI color number like `208` or a name like `dark_grey`
+#[derive(Deserialize)]
+#[serde(try_from = "String")]
+struct ThemeColor(Color);
+
+impl TryFrom<String> for ThemeColor {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        let invalid = || {
+            format!("Invalid color '{value}', expected #rrggbb, a number from 0 to 255 or a name")
+        };
+
+        if let Some(hex) = value.strip_prefix('#') {
+            let channel = |range| {
+                hex.get(range)
+                    .and_then(|digits| u8::from_str_radix(digits, 16).ok())
+            };
+
+            return match (hex.len(), channel(0..2), channel(2..4), channel(4..6)) {
+                (6, Some(r), Some(g), Some(b)) => Ok(ThemeColor(Color::Rgb { r, g, b })),
+                _ => Err(invalid()),
+            };
+        }
+
+        if let Ok(number) = value.parse::<u8>() {
+            return Ok(ThemeColor(Color::AnsiValue(number)));
+        }
+
+        Color::try_from(value.as_str())
+            .map(ThemeColor)
+            .map_err(|()| invalid())
+    }
+}
+
+impl From<HashMap<AnnotationType, AttributeSetting>> for Theme {
+    fn from(settings: HashMap<AnnotationType, AttributeSetting>) -> Self {
+        let overrides = settings
+            .into_iter()
+            .map(|(annotation_type, setting)| {
+                let attribute = Attribute {
+                    foreground: setting.foreground.map(|color| color.0),
+                    background: setting.background.map(|color| color.0),
+                    bold: setting.bold,
+                    italic: setting.italic,
+                    underline: setting.underline,
+                };
+                (annotation_type, attribute)
+            })
+            .collect();
+
+        Theme {
+            overrides,
+            ..Theme::default()
+        }
+    }
+}
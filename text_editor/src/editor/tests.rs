@@ -0,0 +1,785 @@
+use std::{fs, path::PathBuf};
+
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+use super::{
+    caret_position::CaretPosition,
+    cli::{self, Action, Source},
+    command::{Command, mouse::Mouse},
+    harness::Harness,
+    macro_registers::MacroRegisters,
+    session::Session,
+    size::Size,
+    ui_component::view::location::Location,
+};
+
+const SCREEN: Size = Size {
+    columns: 40,
+    rows: 12,
+};
+
+/// Change marker, two digit line numbers and a space in front of the text
+const GUTTER: usize = 4;
+
+fn location(line_idx: usize, grapheme_idx: usize) -> Location {
+    Location {
+        line_idx,
+        grapheme_idx,
+    }
+}
+
+/// Session file of its own for every test, tests run in parallel
+fn session_file(test: &str) -> PathBuf {
+    let path =
+        std::env::temp_dir().join(format!("editr-{test}-{}/session.toml", std::process::id()));
+    let _ = fs::remove_file(&path);
+    path
+}
+
+// ==== Movement ====
+
+#[test]
+fn up_and_down_snap_to_shorter_lines() {
+    let mut harness = Harness::open(&["movement.txt"], SCREEN);
+
+    harness.run("down*19 end");
+    assert_eq!(harness.location(), location(19, 94));
+
+    harness.run("up");
+    assert_eq!(harness.location(), location(18, 1));
+}
+
+#[test]
+fn end_of_long_line_scrolls_horizontally() {
+    let mut harness = Harness::open(&["movement.txt"], SCREEN);
+
+    harness.run("down*19 end");
+
+    let caret = harness.caret().expect("caret is shown after drawing");
+    assert!(caret.column < SCREEN.columns);
+    // The short line above is scrolled out of sight to the left, only its number is left
+    assert_eq!(harness.row(caret.row.saturating_sub(1)), " 19");
+}
+
+#[test]
+fn moving_below_the_view_scrolls_down() {
+    let mut harness = Harness::open(&["movement.txt"], SCREEN);
+    let text_rows = SCREEN.rows.saturating_sub(2);
+
+    assert!(harness.row(0).starts_with("  1 x"));
+
+    harness.run(&format!("down*{text_rows}"));
+    assert_eq!(harness.location(), location(text_rows, 0));
+    assert!(!harness.row(0).starts_with("  1 "));
+
+    harness.run("document_start");
+    assert!(harness.row(0).starts_with("  1 x"));
+    assert_eq!(
+        harness.caret(),
+        Some(CaretPosition {
+            column: GUTTER,
+            row: 0
+        })
+    );
+}
+
+#[test]
+fn document_end_goes_past_the_last_line() {
+    let mut harness = Harness::open(&["movement.txt"], SCREEN);
+    let number_of_lines = harness.content().split('\n').count();
+
+    harness.run("document_end");
+    assert!(harness.location().line_idx >= number_of_lines.saturating_sub(1));
+
+    harness.run("document_start");
+    assert_eq!(harness.location(), location(0, 0));
+}
+
+#[test]
+fn wide_characters_take_two_columns() {
+    let mut harness = Harness::open(&["search_test_file.txt"], SCREEN);
+    let prefix = "Wide Character Needle: ";
+    assert!(harness.line(11).starts_with(prefix));
+
+    harness.run(&format!("down*11 right*{}", prefix.len().saturating_add(1)));
+
+    let caret = harness.caret().expect("caret is shown after drawing");
+    assert_eq!(
+        caret.column,
+        GUTTER.saturating_add(prefix.len()).saturating_add(2)
+    );
+}
+
+#[test]
+fn matching_bracket_skips_nested_pairs_strings_and_comments() {
+    let mut harness = Harness::open(&["brackets.rs"], SCREEN);
+    let mut goto = |target: &str| {
+        harness
+            .run("command_line")
+            .type_text(&format!("goto {target}\n"))
+            .run("matching_bracket")
+            .location()
+    };
+
+    // Across lines, past a `"}"` literal and a comment with stray brackets
+    assert_eq!(goto("1:11"), location(6, 0));
+    assert_eq!(goto("7:1"), location(0, 10));
+    assert_eq!(goto("3:13"), location(5, 4));
+    assert_eq!(goto("5:13"), location(4, 26));
+    assert_eq!(goto("5:23"), location(4, 24));
+    // Right after a bracket counts as on it
+    assert_eq!(goto("5:28"), location(4, 12));
+
+    // Brackets in literals have no partner
+    assert_eq!(goto("2:17"), location(1, 16));
+    assert_eq!(goto("4:26"), location(3, 25));
+    assert_eq!(goto("5:15"), location(4, 14));
+}
+
+#[test]
+fn goto_rejects_lines_and_columns_before_the_first() {
+    let mut harness = Harness::open(&["movement.txt"], SCREEN);
+    harness.run("command_line").type_text("goto 3:2\n");
+
+    for target in ["0", "5:", "3:0", "0:4", "x", "-1"] {
+        harness
+            .run("command_line")
+            .type_text(&format!("goto {target}\n"));
+        assert_eq!(harness.location(), location(2, 1), "goto {target}");
+        assert!(
+            harness
+                .row(SCREEN.rows.saturating_sub(1))
+                .starts_with(&format!("Invalid line '{target}'")),
+            "goto {target}"
+        );
+    }
+}
+
+// ==== Editing ====
+
+#[test]
+fn typed_text_ends_up_in_buffer_and_on_screen() {
+    let mut harness = Harness::open(&[], SCREEN);
+
+    harness.type_text("hello\nworld");
+
+    assert_eq!(harness.content(), "hello\nworld");
+    assert_eq!(harness.location(), location(1, 5));
+    assert!(harness.row(0).ends_with("hello"));
+    assert!(harness.row(1).ends_with("world"));
+}
+
+#[test]
+fn enter_splits_and_backspace_joins_lines() {
+    let mut harness = Harness::open(&["small_test.txt"], SCREEN);
+    let original = harness.content();
+    assert_eq!(harness.line(1), "[package]");
+
+    harness.run("down right*4 enter");
+    assert_eq!(harness.line(1), "[pac");
+    assert_eq!(harness.line(2), "kage]");
+    assert_eq!(harness.location(), location(2, 0));
+
+    harness.run("backspace");
+    assert_eq!(harness.content(), original);
+}
+
+/// Empty buffer indented by four spaces
+fn indented_by_four() -> Harness {
+    let mut harness = Harness::open(&[], SCREEN);
+    harness.run("command_line").type_text("set indentation=4\n");
+    harness
+}
+
+#[test]
+fn enter_keeps_indentation_and_closing_brace_takes_a_level_away() {
+    let mut harness = indented_by_four();
+
+    harness.type_text("fn main() {\nif x {\ny();\n}\n}");
+    assert_eq!(
+        harness.content(),
+        "fn main() {\n    if x {\n        y();\n    }\n}"
+    );
+
+    // Only a brace typed first on the line is outdented
+    harness.run("up end").type_text(" }");
+    assert_eq!(harness.line(3), "    } }");
+}
+
+#[test]
+fn enter_between_brackets_puts_the_closing_one_on_its_own_line() {
+    let mut harness = indented_by_four();
+
+    harness
+        .type_text("    call([])")
+        .run("left*2")
+        .type_text("\n");
+    assert_eq!(harness.content(), "    call([\n        \n    ])");
+    assert_eq!(harness.location(), location(1, 8));
+}
+
+#[test]
+fn enter_replaces_blanks_after_the_caret_with_the_indentation() {
+    let mut harness = indented_by_four();
+
+    harness
+        .type_text("    if x { y(); }")
+        .run("left*2")
+        .type_text("\n");
+    assert_eq!(harness.content(), "    if x { y();\n    }");
+
+    harness
+        .run("document_end")
+        .type_text("\nz  \t w")
+        .run("left*5");
+    harness.type_text("\n");
+    assert_eq!(harness.line(3), "    w");
+    assert_eq!(harness.location(), location(3, 4));
+}
+
+#[test]
+fn tab_and_outdent_change_every_selected_line() {
+    let mut harness = indented_by_four();
+
+    harness
+        .type_text("a\n\n  b\n")
+        .run("backspace*2")
+        .type_text("c")
+        .run("document_start select_down*2 select_right");
+
+    harness.run("tab");
+    // Empty lines stay empty, the line below the selection is left alone
+    assert_eq!(harness.content(), "    a\n\n      b\nc");
+
+    harness.run("outdent*2");
+    assert_eq!(harness.content(), "a\n\nb\nc");
+
+    // Without a selection Tab inserts up to the next level and Shift+Tab outdents the caret line
+    harness.run("document_end home right").run("tab");
+    assert_eq!(harness.line(3), "c   ");
+    harness.run("home").run("tab tab outdent");
+    assert_eq!(harness.line(3), "    c   ");
+    assert_eq!(harness.location(), location(3, 4));
+}
+
+#[test]
+fn delete_word_removes_up_to_the_next_word() {
+    let mut harness = Harness::open(&["small_test.txt"], SCREEN);
+
+    harness.run("down*2 delete_word");
+    assert!(!harness.line(2).starts_with("name"));
+    assert!(harness.line(2).ends_with("\"text_editor\""));
+}
+
+#[test]
+fn undo_and_redo_restore_the_text() {
+    let mut harness = Harness::open(&["small_test.txt"], SCREEN);
+    let original = harness.content();
+
+    harness.run("down*2 end").type_text(" # edited");
+    let edited = harness.content();
+    assert_ne!(edited, original);
+
+    // Typing is undone a word at a time
+    harness.run("undo");
+    assert_eq!(harness.line(2), "name = \"text_editor\" # ");
+
+    harness.run("undo*3");
+    assert_eq!(harness.content(), original);
+    assert_eq!(harness.location(), location(2, 20));
+
+    harness.run("redo*4");
+    assert_eq!(harness.content(), edited);
+}
+
+#[test]
+fn key_presses_go_through_bindings() {
+    let mut harness = Harness::open(&[], SCREEN);
+
+    harness.press(KeyEvent::new(KeyCode::Char('a'), KeyModifiers::NONE));
+    assert_eq!(harness.content(), "a");
+
+    harness.press(KeyEvent::new(KeyCode::Char('z'), KeyModifiers::CONTROL));
+    assert_eq!(harness.content(), "");
+}
+
+// ==== Screen ====
+
+#[test]
+fn status_line_shows_file_and_modification() {
+    let screen = Size {
+        columns: 80,
+        ..SCREEN
+    };
+    let mut harness = Harness::open(&["small_test.txt"], screen);
+    let status_row = screen.rows.saturating_sub(2);
+
+    assert_eq!(harness.title(), "test_files/small_test.txt");
+    assert!(!harness.row(status_row).contains("(modified)"));
+
+    harness.type_text("x");
+    assert!(harness.row(status_row).contains("(modified)"));
+}
+
+#[test]
+fn soft_wrapped_line_taller_than_the_view_scrolls_within_itself() {
+    let text = "0123456789".repeat(100);
+    let mut harness = Harness::with_args(&["-"], Some(&text), SCREEN);
+    // Message and status line take the two rows below the view
+    let view_rows = SCREEN.rows.saturating_sub(2);
+    // A single line needs a one digit line number
+    let gutter = GUTTER - 1;
+    let width = SCREEN.columns.saturating_sub(gutter);
+    let last_row_start = text.len() / width * width;
+
+    harness.run("toggle_soft_wrap end");
+    let caret = harness.caret().expect("caret is shown after drawing");
+    assert_eq!(caret.row, view_rows.saturating_sub(1));
+    assert_eq!(caret.column, gutter + text.len() - last_row_start);
+    assert!(harness.row(caret.row).ends_with(&text[last_row_start..]));
+
+    harness.type_text("x");
+    assert!(harness.content().ends_with("0123456789x"));
+    assert!(
+        harness
+            .row(caret.row)
+            .ends_with(&format!("{}x", &text[last_row_start..]))
+    );
+
+    // Clicks land on the rows shown, not on the first rows of the line
+    harness.command(Command::Mouse(Mouse::Click(CaretPosition {
+        column: gutter,
+        row: 0,
+    })));
+    let first_shown_row = last_row_start / width + 1 - view_rows;
+    assert_eq!(harness.location(), location(0, first_shown_row * width));
+
+    // Going up past the top row scrolls one row at a time
+    harness.run("up");
+    assert_eq!(harness.caret().map(|caret| caret.row), Some(0));
+    assert!(
+        harness
+            .row(0)
+            .ends_with(&text[(first_shown_row - 1) * width..first_shown_row * width])
+    );
+
+    harness.run("document_start");
+    assert_eq!(harness.caret().map(|caret| caret.row), Some(0));
+    assert!(harness.row(0).ends_with(&text[..width]));
+}
+
+#[test]
+fn splits_show_the_same_buffer_twice() {
+    let mut harness = Harness::open(&["small_test.txt"], SCREEN);
+
+    harness.run("split_horizontal");
+    let rows = harness.rows();
+    let with_package = rows.iter().filter(|row| row == &"   2 [package]").count();
+    assert_eq!(with_package, 2);
+
+    harness.type_text("shared");
+    // Changed lines are marked in the gutter of both windows
+    let rows = harness.rows();
+    let with_edit = rows.iter().filter(|row| row == &"│  1 shared").count();
+    assert_eq!(with_edit, 2);
+}
+
+#[test]
+fn diagnostics_are_shown_as_gutter_signs() {
+    let path = session_file("diagnostics").with_file_name("errors.txt");
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).expect("temp dir can be created");
+    }
+    fs::write(
+        &path,
+        "test_files/small_test.txt:2:1: error: broken\ntest_files/small_test.txt:4: warning: odd\n",
+    )
+    .expect("diagnostics can be written");
+    let mut harness = Harness::open(&["small_test.txt"], SCREEN);
+
+    harness
+        .run("command_line")
+        .type_text(&format!("diagnostics {}\n", path.display()));
+    assert_eq!(harness.row(1), "E  2 [package]");
+    assert!(harness.row(3).starts_with('W'));
+
+    harness.run("command_line").type_text("diagnostics\n");
+    assert_eq!(harness.row(1), "   2 [package]");
+}
+
+#[test]
+fn write_refuses_to_replace_another_file_unless_forced() {
+    let path = session_file("write_other").with_file_name("other.txt");
+    let dir = path.parent().expect("temp file has a directory");
+    fs::create_dir_all(dir).unwrap();
+    fs::write(&path, "keep me\n").unwrap();
+    let other = path.display();
+
+    let mut harness = Harness::with_args(&["test_files/small_test.txt"], None, SCREEN);
+    harness.type_text("x");
+    harness
+        .run("command_line")
+        .type_text(&format!("w {other}\n"));
+
+    assert_eq!(fs::read_to_string(&path).unwrap(), "keep me\n");
+    assert!(
+        harness
+            .row(SCREEN.rows.saturating_sub(2))
+            .starts_with("test_files/small_test.txt")
+    );
+    assert!(
+        harness
+            .row(SCREEN.rows.saturating_sub(1))
+            .starts_with("File '")
+    );
+
+    harness
+        .run("command_line")
+        .type_text(&format!("w! {other}\n"));
+    assert!(
+        fs::read_to_string(&path)
+            .unwrap()
+            .starts_with("x\n[package]")
+    );
+    assert!(
+        harness
+            .row(SCREEN.rows.saturating_sub(2))
+            .starts_with(&other.to_string())
+    );
+
+    // The buffer's own file is written without asking, also under another name leading to it
+    let same = dir
+        .join("..")
+        .join(dir.file_name().unwrap())
+        .join("other.txt");
+    harness.type_text("y");
+    harness
+        .run("command_line")
+        .type_text(&format!("w {}\n", same.display()));
+    assert!(
+        fs::read_to_string(&path)
+            .unwrap()
+            .starts_with("xy\n[package]")
+    );
+
+    let _ = fs::remove_file(&path);
+}
+
+// ==== Macros ====
+
+#[test]
+fn macro_is_replayed_count_times() {
+    let mut harness = Harness::open(&["small_test.txt"], SCREEN);
+
+    harness.run("down record_macro").type_text("a");
+    harness.run("home").type_text("# ").run("down record_macro");
+    assert_eq!(harness.line(1), "# [package]");
+
+    harness.run("play_macro").type_text("2a\n");
+    assert_eq!(harness.line(2), "# name = \"text_editor\"");
+    assert_eq!(harness.line(3), "# version = \"0.1.0\"");
+    assert_eq!(harness.line(4), "edition = \"2024\"");
+    assert_eq!(harness.location(), location(4, 2));
+}
+
+#[test]
+fn macro_playback_stops_when_a_movement_fails() {
+    let mut harness = Harness::open(&[], SCREEN);
+    let message_row = SCREEN.rows.saturating_sub(1);
+
+    harness
+        .type_text("x\ny\nz")
+        .run("document_start record_macro");
+    harness
+        .type_text("a")
+        .run("end")
+        .type_text("!")
+        .run("down record_macro");
+
+    harness.run("play_macro").type_text("10a\n");
+    assert_eq!(harness.content(), "x!\ny!\nz!");
+    assert!(
+        harness
+            .row(message_row)
+            .starts_with("Macro 'a' stopped in run 2 of 10")
+    );
+}
+
+#[test]
+fn macro_recording_leaves_out_its_own_prompts() {
+    let mut harness = Harness::open(&[], SCREEN);
+
+    harness.run("record_macro").type_text("b");
+    harness.type_text("ab").run("record_macro");
+    harness.run("play_macro").type_text("3b\n");
+    assert_eq!(harness.content(), "abababab");
+
+    // Playing an empty register changes nothing
+    harness.run("play_macro").type_text("c\n");
+    assert_eq!(harness.content(), "abababab");
+}
+
+#[test]
+fn macro_playback_is_parsed_as_count_and_register() {
+    assert_eq!(MacroRegisters::parse_playback("a"), Some((1, 'a')));
+    assert_eq!(MacroRegisters::parse_playback(" 12q "), Some((12, 'q')));
+    assert_eq!(MacroRegisters::parse_playback("0a"), None);
+    assert_eq!(MacroRegisters::parse_playback("3"), None);
+    assert_eq!(MacroRegisters::parse_playback("x3a"), None);
+    assert_eq!(MacroRegisters::parse_playback(""), None);
+    assert_eq!(MacroRegisters::parse_playback("1000a"), Some((1000, 'a')));
+    assert_eq!(MacroRegisters::parse_playback("1001a"), None);
+    assert_eq!(
+        MacroRegisters::parse_playback("999999999999999999999a"),
+        None
+    );
+}
+
+#[test]
+fn macro_count_is_limited() {
+    let mut harness = Harness::open(&[], SCREEN);
+
+    harness.run("record_macro").type_text("a");
+    harness.type_text("x").run("record_macro");
+    harness.run("play_macro").type_text("999999999a\n");
+
+    assert_eq!(harness.content(), "x");
+    assert!(
+        harness
+            .row(SCREEN.rows.saturating_sub(1))
+            .starts_with("Invalid macro '999999999a'")
+    );
+}
+
+#[test]
+fn macro_playback_stops_when_a_run_changes_nothing() {
+    let mut harness = Harness::open(&[], SCREEN);
+
+    harness.type_text("abc").run("home");
+    harness.run("record_macro").type_text("a");
+    harness.run("right left record_macro");
+    harness.run("play_macro").type_text("1000a\n");
+
+    assert_eq!(harness.location(), location(0, 0));
+    assert!(
+        harness
+            .row(SCREEN.rows.saturating_sub(1))
+            .starts_with("Macro 'a' stopped after run 1 of 1000")
+    );
+}
+
+// ==== Session ====
+
+#[test]
+fn session_round_trips_through_its_file() {
+    let path = session_file("round_trip");
+
+    let mut session = Session::load_from(path.clone());
+    session.remember("test_files/movement.txt", location(3, 1));
+    session.remember("test_files/small_test.txt", location(2, 4));
+    session.search_history = vec![String::from("needle")];
+    session.save().expect("session is written");
+
+    let session = Session::load_from(path);
+    assert_eq!(
+        session.location_of("test_files/small_test.txt"),
+        Some(location(2, 4))
+    );
+    assert_eq!(session.search_history, ["needle"]);
+
+    let recent = session.recent_files();
+    assert_eq!(recent.len(), 2);
+    assert!(recent[0].ends_with("small_test.txt"));
+    assert!(recent[1].ends_with("movement.txt"));
+}
+
+#[test]
+fn caret_goes_back_where_the_file_was_left() {
+    let path = session_file("caret");
+
+    let mut harness = Harness::with_session(
+        &["small_test.txt"],
+        SCREEN,
+        Session::load_from(path.clone()),
+    );
+    harness.run("down*3 right*2");
+    drop(harness);
+
+    let harness = Harness::with_session(&["small_test.txt"], SCREEN, Session::load_from(path));
+    assert_eq!(harness.location(), location(3, 2));
+}
+
+#[test]
+fn histories_are_kept_between_sessions() {
+    let path = session_file("histories");
+    let screen = Size {
+        columns: 100,
+        ..SCREEN
+    };
+
+    let mut harness = Harness::with_session(
+        &["search_test_file.txt"],
+        screen,
+        Session::load_from(path.clone()),
+    );
+    harness.run("search").type_text("haystack\n");
+    harness.run("command_line").type_text("goto 3\n");
+    drop(harness);
+
+    let session = Session::load_from(path.clone());
+    assert_eq!(session.search_history, ["haystack"]);
+    assert_eq!(session.command_history, ["goto 3"]);
+
+    let mut harness =
+        Harness::with_session(&["search_test_file.txt"], screen, Session::load_from(path));
+    harness.run("document_start search page_up");
+    assert!(
+        harness
+            .row(screen.rows.saturating_sub(1))
+            .ends_with("haystack")
+    );
+}
+
+#[test]
+fn recent_files_are_opened_from_the_welcome_screen() {
+    let path = session_file("recent");
+    let screen = Size {
+        columns: 120,
+        rows: 24,
+    };
+
+    let mut session = Session::load_from(path);
+    session.remember("test_files/small_test.txt", location(4, 3));
+    let mut harness = Harness::with_session(&[], screen, session);
+
+    let entry = harness
+        .rows()
+        .into_iter()
+        .find(|row| row.contains("Alt-1"))
+        .expect("recent file is listed");
+    assert!(entry.contains("test_files/small_test.txt"));
+
+    harness.press(KeyEvent::new(KeyCode::Char('1'), KeyModifiers::ALT));
+    assert!(harness.title().ends_with("test_files/small_test.txt"));
+    assert_eq!(harness.location(), location(4, 3));
+}
+
+// ==== Command line ====
+
+fn parse(arguments: &[&str]) -> Result<Action, String> {
+    cli::parse(arguments.iter().map(ToString::to_string))
+}
+
+#[test]
+fn positions_are_parsed_from_the_command_line() {
+    let Ok(Action::Edit(args)) =
+        parse(&["-R", "missing.rs:3:2", "+7", "other.rs", "-", "--", "-R"])
+    else {
+        panic!("arguments are valid");
+    };
+    assert!(args.read_only);
+
+    let sources: Vec<(String, Option<Location>)> = args
+        .sources
+        .into_iter()
+        .map(|source| match source {
+            Source::File { name, location } => (name, location),
+            Source::Stdin => (String::from("-"), None),
+        })
+        .collect();
+    assert_eq!(
+        sources,
+        [
+            (String::from("missing.rs"), Some(location(2, 1))),
+            (String::from("other.rs"), Some(location(6, 0))),
+            (String::from("-"), None),
+            (String::from("-R"), None),
+        ]
+    );
+
+    assert!(matches!(parse(&["--help", "file"]), Ok(Action::Help)));
+    assert!(matches!(parse(&["-V"]), Ok(Action::Version)));
+    assert!(parse(&["--bogus"]).is_err());
+    assert!(parse(&["file", "+3"]).is_err());
+    assert!(parse(&["-", "-"]).is_err());
+}
+
+#[test]
+fn file_is_opened_at_the_given_position() {
+    let harness = Harness::with_args(&["test_files/small_test.txt:3:4"], None, SCREEN);
+    assert!(harness.title().ends_with("test_files/small_test.txt"));
+    assert_eq!(harness.location(), location(2, 3));
+
+    let harness = Harness::with_args(&["+5", "test_files/small_test.txt"], None, SCREEN);
+    assert_eq!(harness.location(), location(4, 0));
+}
+
+#[test]
+fn stdin_is_read_into_a_buffer_without_file() {
+    let piped = (1..=10)
+        .map(|line| format!("piped {line}"))
+        .collect::<Vec<_>>()
+        .join("\n");
+    let mut harness = Harness::with_args(&["-"], Some(&piped), SCREEN);
+    assert_eq!(harness.content(), piped);
+    assert!(harness.row(4).ends_with("piped 5"));
+
+    harness.type_text("!");
+    assert!(harness.content().starts_with("!piped 1"));
+}
+
+#[test]
+fn read_only_buffers_refuse_changes() {
+    let screen = Size {
+        columns: 80,
+        ..SCREEN
+    };
+    let mut harness = Harness::with_args(&["-R", "test_files/small_test.txt"], None, screen);
+    let content = harness.content();
+
+    harness.type_text("x").run("save");
+    assert_eq!(harness.content(), content);
+    assert_eq!(
+        harness.row(screen.rows.saturating_sub(1)),
+        "Buffer is read-only"
+    );
+    assert!(
+        harness
+            .row(screen.rows.saturating_sub(2))
+            .contains("[read-only]")
+    );
+}
+
+#[test]
+fn read_only_buffers_refuse_replace_from_search() {
+    let screen = Size {
+        columns: 80,
+        ..SCREEN
+    };
+    let mut harness = Harness::with_args(&["-R", "test_files/small_test.txt"], None, screen);
+    let content = harness.content();
+    let ctrl_r = KeyEvent::new(KeyCode::Char('r'), KeyModifiers::CONTROL);
+
+    harness.run("search").type_text("name");
+    harness.press(ctrl_r);
+    assert_eq!(
+        harness.row(screen.rows.saturating_sub(1)),
+        "Buffer is read-only"
+    );
+
+    harness.type_text("label").press(ctrl_r);
+    harness.type_text("\n");
+    assert_eq!(harness.content(), content);
+}
+
+#[test]
+fn unreadable_file_is_reported() {
+    let harness = Harness::with_args(&["test_files", "test_files/small_test.txt"], None, SCREEN);
+
+    assert!(harness.title().ends_with("test_files/small_test.txt"));
+    assert!(
+        harness
+            .row(SCREEN.rows.saturating_sub(1))
+            .starts_with("Could not open 'test_files'")
+    );
+}
@@ -1,4 +1,6 @@
-use crate::editor::{command::edit::Edit, line::Line, size::Size, terminal::Terminal};
+use crate::editor::{
+    caret_position::CaretPosition, command::edit::Edit, line::Line, size::Size, terminal::Terminal,
+};
 
 use super::UiComponent;
 
@@ -45,6 +47,11 @@ impl CommandBar {
         self.command_line.to_string()
     }
 
+    pub fn set_line(&mut self, text: &str) {
+        self.command_line = Line::from(text);
+        self.mark_redraw(true);
+    }
+
     pub fn clear_line(&mut self) {
         self.command_line.clear();
         // self.command_line = Line::default();
@@ -87,7 +94,7 @@ impl UiComponent for CommandBar {
     }
 
     /// Method to actually draw the component, must be implemented by each component
-    fn draw(&mut self, origin_y: usize) -> Result<(), std::io::Error> {
+    fn draw(&mut self, origin: CaretPosition) -> Result<(), std::io::Error> {
         let area_for_value = self.size.columns.saturating_sub(self.prompt.len());
         let value_end = self.command_line.grapheme_count();
         let value_start = value_end.saturating_sub(area_for_value);
@@ -102,6 +109,6 @@ impl UiComponent for CommandBar {
         } else {
             String::new()
         };
-        Terminal::print_row(origin_y, &to_print)
+        Terminal::print_row(origin.row, &to_print)
     }
 }
@@ -1,5 +1,6 @@
 use std::time::{Duration, Instant};
 
+use crate::editor::caret_position::CaretPosition;
 use crate::editor::size::Size;
 use crate::editor::terminal::Terminal;
 
@@ -77,7 +78,7 @@ impl UiComponent for MessageBar {
     }
 
     /// Method to actually draw the component, must be implemented by each component
-    fn draw(&mut self, origin_y: usize) -> Result<(), std::io::Error> {
-        Terminal::print_row(origin_y, &self.message_string)
+    fn draw(&mut self, origin: CaretPosition) -> Result<(), std::io::Error> {
+        Terminal::print_row(origin.row, &self.message_string)
     }
 }
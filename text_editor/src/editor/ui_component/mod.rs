@@ -3,7 +3,7 @@ pub mod message_bar;
 pub mod status_bar;
 pub mod view;
 
-use crate::editor::size::Size;
+use crate::editor::{caret_position::CaretPosition, size::Size};
 
 pub trait UiComponent {
     /// Marks if ui component need to be redrawn
@@ -21,10 +21,10 @@ pub trait UiComponent {
     /// Set the size of the component
     fn set_size(&mut self, new_size: Size);
 
-    /// Draw this component if it's visible and in need of redrawing
-    fn render(&mut self, origin_y: usize) {
+    /// Draw this component if it's visible and in need of redrawing, origin is its top left corner
+    fn render(&mut self, origin: CaretPosition) {
         if self.needs_redraw() {
-            match self.draw(origin_y) {
+            match self.draw(origin) {
                 Ok(()) => self.mark_redraw(false),
                 Err(err) => {
                     #[cfg(debug_assertions)]
@@ -37,5 +37,5 @@ pub trait UiComponent {
     }
 
     /// Method to actually draw the component, must be implemented by each component
-    fn draw(&mut self, origin_y: usize) -> Result<(), std::io::Error>;
+    fn draw(&mut self, origin: CaretPosition) -> Result<(), std::io::Error>;
 }
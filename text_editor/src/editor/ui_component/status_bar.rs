@@ -1,4 +1,8 @@
-use crate::editor::{document_status::DocumentStatus, size::Size, terminal::Terminal};
+use crossterm::style::Attribute;
+
+use crate::editor::{
+    caret_position::CaretPosition, document_status::DocumentStatus, size::Size, terminal::Terminal,
+};
 
 use super::UiComponent;
 
@@ -7,6 +11,8 @@ pub struct StatusBar {
     status: DocumentStatus,
     needs_redraw: bool,
     size: Size,
+    /// Status line of the split being edited stands out from the others
+    focused: bool,
 }
 
 /// It is assumed for this component to be of size 1 vertically
@@ -19,6 +25,14 @@ impl StatusBar {
             status: DocumentStatus::default(),
             needs_redraw: true,
             size,
+            focused: true,
+        }
+    }
+
+    pub fn set_focused(&mut self, focused: bool) {
+        if self.focused != focused {
+            self.focused = focused;
+            self.mark_redraw(true);
         }
     }
 
@@ -37,11 +51,18 @@ impl StatusBar {
             .unwrap_or("[None]".to_string());
 
         let number_of_lines = self.status.line_count_to_string();
-        let position =
-            self.status.file_type.to_string() + " | " + &self.status.position_indicator_to_string();
+        let position = format!(
+            "{} | {} | {}",
+            self.status.file_type,
+            self.status.file_format,
+            self.status.position_indicator_to_string()
+        );
         let modification = self.status.modified_indicator_to_string();
+        let read_only = self.status.read_only_indicator_to_string();
+        let buffer = self.status.buffer_indicator_to_string();
 
-        let line = format!("{file_name:<.50} - {number_of_lines} lines {modification}",);
+        let line =
+            format!("{buffer}{file_name:<.50} - {number_of_lines} lines {modification}{read_only}");
 
         let padding_left = self
             .size
@@ -76,8 +97,13 @@ impl UiComponent for StatusBar {
     }
 
     /// Method to actually draw the component, must be implemented by each component
-    fn draw(&mut self, origin_y: usize) -> Result<(), std::io::Error> {
+    fn draw(&mut self, origin: CaretPosition) -> Result<(), std::io::Error> {
         let msg = self.build_document_status_string();
-        Terminal::print_row_with_attribute(origin_y, crossterm::style::Attribute::Reverse, &msg)
+        let attributes: &[Attribute] = if self.focused {
+            &[Attribute::Reverse, Attribute::Bold]
+        } else {
+            &[Attribute::Reverse]
+        };
+        Terminal::print_at_with_attributes(origin, self.size.columns, attributes, &msg)
     }
 }
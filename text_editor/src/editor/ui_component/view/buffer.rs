@@ -1,20 +1,37 @@
-use std::fs::OpenOptions;
-use std::io::Write;
-use std::ops::Range;
+use std::collections::VecDeque;
+use std::ops::{Range, RangeInclusive};
+use std::path::Path;
 
 use crate::editor::annotated_string::AnnotatedString;
-use crate::editor::line::Line;
+use crate::editor::line::{Line, LineIdx};
+use crate::editor::search_pattern::SearchPattern;
 
 use super::Location;
+use super::file_format::FileFormat;
+use super::file_io::{self, DiskState};
 use super::highlighter::Highlighter;
+use super::history::{GroupKind, History, Operation};
+use super::line_rope::LineRope;
 
 #[derive(Default)]
 pub struct Buffer {
-    pub data: Vec<Line>,
+    pub data: LineRope,
     file_name: Option<String>,
-    is_modified: bool,
+    file_format: FileFormat,
+    history: History,
+    changed_since_swap: bool,
+    disk_state: Option<DiskState>,
+    /// Counts every change, views showing the buffer compare it with the last one they have seen
+    revision: usize,
+    /// First changed line of the most recent revisions, oldest first
+    changed_lines: VecDeque<LineIdx>,
+    /// Kept by the editor, the buffer itself does not refuse changes
+    read_only: bool,
 }
 
+/// Revisions remembered for views which have not caught up yet, views further behind start over
+const MAX_CHANGES: usize = 256;
+
 impl Buffer {
     // ==================================================== Simple Manipulation Methods ================================================
 
@@ -29,146 +46,80 @@ impl Buffer {
 
     pub fn set_file(&mut self, file_name: &str) {
         self.file_name = Some(file_name.to_string());
+        self.disk_state = None;
     }
 
     // =========================================================== Search ===============================================================
 
+    /// Closest match starting before given location, loops around the end of the buffer
     pub fn backward_find(
         &self,
-        search_string: &str,
-        current_location: Location,
-    ) -> Option<Location> {
-        let current_line = self.data.get(current_location.line_idx);
-
-        let lines_after = self
-            .data
-            .iter()
-            .enumerate()
-            .filter(|(idx, _)| *idx >= current_location.line_idx)
-            .rev();
-
-        let lines_before = self
-            .data
-            .iter()
-            .enumerate()
-            .filter(|(idx, _)| *idx < current_location.line_idx)
-            .rev();
-
+        pattern: &SearchPattern,
+        before: Location,
+    ) -> Option<Range<Location>> {
         // Try to find on current line
-        if let Some(line) = current_line {
-            if let Some(graph_idx) =
-                line.backward_find(search_string, current_location.grapheme_idx)
-            {
-                return Some(Location {
-                    line_idx: current_location.line_idx,
-                    grapheme_idx: graph_idx,
-                });
-            }
-        }
-
-        // Try to look backward
-        if let Some(found) = Self::search_backward(search_string, lines_before) {
-            return Some(found);
-        }
-
-        // Loop around and search
-        if let Some(found) = Self::search_backward(search_string, lines_after) {
-            return Some(found);
+        if let Some(line) = self.data.get(before.line_idx)
+            && let Some(graphemes) = line.backward_find(pattern, before.grapheme_idx)
+        {
+            return Some(Self::to_locations(before.line_idx, graphemes));
         }
 
-        None
-    }
+        let lines_before = (0..before.line_idx).rev();
+        let lines_after = (before.line_idx..self.data.len()).rev();
 
-    pub fn next_valid_search_location(&self, loc: Location, search_string: &str) -> Location {
-        let value = self
-            .data
-            .get(loc.line_idx)
-            .map_or(Line::from(search_string).grapheme_count(), |x| {
-                x.get_next_match_idx(loc.grapheme_idx, search_string)
-            });
-
-        Location {
-            grapheme_idx: loc.grapheme_idx.saturating_add(value),
-            line_idx: loc.line_idx,
-        }
+        // Try to look backward and loop around
+        lines_before.chain(lines_after).find_map(|line_idx| {
+            self.data
+                .get(line_idx)?
+                .backward_find(pattern, usize::MAX)
+                .map(|graphemes| Self::to_locations(line_idx, graphemes))
+        })
     }
 
-    pub fn forward_find(
-        &self,
-        search_string: &str,
-        current_location: Location,
-    ) -> Option<Location> {
-        let current_line = self.data.get(current_location.line_idx);
-
-        let lines_after = self
-            .data
-            .iter()
-            .enumerate()
-            .filter(|(idx, _)| *idx > current_location.line_idx);
-
-        let lines_before = self
-            .data
-            .iter()
-            .enumerate()
-            .filter(|(idx, _)| *idx <= current_location.line_idx);
-
+    /// Closest match starting at or after given location, loops around the end of the buffer
+    pub fn forward_find(&self, pattern: &SearchPattern, from: Location) -> Option<Range<Location>> {
         // Try to find on current line
-        if let Some(line) = current_line {
-            if let Some(graph_idx) = line.forward_find(search_string, current_location.grapheme_idx)
-            {
-                return Some(Location {
-                    line_idx: current_location.line_idx,
-                    grapheme_idx: graph_idx,
-                });
-            }
+        if let Some(line) = self.data.get(from.line_idx)
+            && let Some(graphemes) = line.forward_find(pattern, from.grapheme_idx)
+        {
+            return Some(Self::to_locations(from.line_idx, graphemes));
         }
 
-        // Try to look forward
-        if let Some(found) = Self::search_forward(search_string, lines_after) {
-            return Some(found);
-        }
+        let lines_after = from.line_idx.saturating_add(1)..self.data.len();
+        let lines_before = 0..=from.line_idx;
 
-        // Loop around and search
-        if let Some(found) = Self::search_forward(search_string, lines_before) {
-            return Some(found);
-        }
+        // Try to look forward and loop around
+        lines_after.chain(lines_before).find_map(|line_idx| {
+            self.data
+                .get(line_idx)?
+                .forward_find(pattern, 0)
+                .map(|graphemes| Self::to_locations(line_idx, graphemes))
+        })
+    }
 
-        None
+    /// Every match starting at or after given location, without looping around
+    pub fn find_all_after(&self, pattern: &SearchPattern, from: Location) -> Vec<Range<Location>> {
+        (from.line_idx..self.data.len())
+            .filter_map(|line_idx| Some((line_idx, self.data.get(line_idx)?)))
+            .flat_map(|(line_idx, line)| {
+                line.find_all(pattern)
+                    .into_iter()
+                    .map(move |(_, graphemes)| Self::to_locations(line_idx, graphemes))
+            })
+            .filter(|found| found.start >= from)
+            .collect()
     }
 
-    fn search_forward<'a, I>(search_string: &'a str, line_pile: I) -> Option<Location>
-    where
-        I: Iterator<Item = (usize, &'a Line)>,
-    {
-        // Try to look forward
-        for (line_idx, line) in line_pile {
-            if let Some(graph_idx) = line.forward_find(search_string, 0) {
-                return Some(Location {
-                    line_idx,
-                    grapheme_idx: graph_idx,
-                });
-            }
-        }
-        None
-    }
-
-    fn search_backward<'a, I>(search_string: &'a str, line_pile: I) -> Option<Location>
-    where
-        I: Iterator<Item = (usize, &'a Line)>,
-    {
-        // Try to look forward
-        for (line_idx, line) in line_pile {
-            if let Some(graph_idx) =
-                line.backward_find(search_string, line.grapheme_count().saturating_sub(1))
-            {
-                return Some(Location {
-                    line_idx,
-                    grapheme_idx: graph_idx,
-                });
-            }
+    fn to_locations(line_idx: usize, graphemes: Range<usize>) -> Range<Location> {
+        Location {
+            line_idx,
+            grapheme_idx: graphemes.start,
+        }..Location {
+            line_idx,
+            grapheme_idx: graphemes.end,
         }
-        None
     }
+
     // ============================================================= Getters ==========================================================
 
     pub fn get_highlighted_line(
@@ -179,17 +130,39 @@ impl Buffer {
     ) -> Option<AnnotatedString> {
         let annotations = highlighter.get_annotations(row);
 
-        let line_string = match self.get_line(row) {
-            Some(s) => s.get_visable_graphemes(range),
-            None => return None,
-        };
+        self.get_line(row)
+            .map(|line| line.get_annotated_graphemes(range, &annotations))
+    }
 
-        let s = AnnotatedString {
-            string: line_string,
-            annotations,
-        };
+    /// Text between two locations, lines are joined with `\n`
+    pub fn get_text(&self, start: Location, end: Location) -> String {
+        let mut result = String::new();
+
+        for line_idx in start.line_idx..=end.line_idx {
+            let Some(line) = self.data.get(line_idx) else {
+                break;
+            };
+
+            if line_idx > start.line_idx {
+                result.push('\n');
+            }
+
+            let from = if line_idx == start.line_idx {
+                start.grapheme_idx
+            } else {
+                0
+            };
+
+            let to = if line_idx == end.line_idx {
+                end.grapheme_idx
+            } else {
+                line.grapheme_count()
+            };
 
-        Some(s)
+            result.push_str(&line.get_graphemes(from..to));
+        }
+
+        result
     }
 
     pub fn get_line(&self, row: usize) -> Option<&Line> {
@@ -200,8 +173,54 @@ impl Buffer {
         self.file_name.clone()
     }
 
+    pub fn get_file_format(&self) -> FileFormat {
+        self.file_format
+    }
+
+    pub fn revision(&self) -> usize {
+        self.revision
+    }
+
+    /// First line changed after given revision, everything below it may have changed as well
+    pub fn first_changed_since(&self, revision: usize) -> Option<LineIdx> {
+        let missed = self
+            .revision
+            .checked_sub(revision)
+            .filter(|missed| *missed > 0)?;
+
+        if missed > self.changed_lines.len() {
+            return Some(0);
+        }
+
+        self.changed_lines
+            .iter()
+            .skip(self.changed_lines.len().saturating_sub(missed))
+            .min()
+            .copied()
+    }
+
     pub fn is_modified(&self) -> bool {
-        self.is_modified
+        self.history.is_modified()
+    }
+
+    pub fn is_line_modified(&self, line_idx: LineIdx) -> bool {
+        self.data.get(line_idx).is_some_and(Line::is_modified)
+    }
+
+    /// File was modified by someone else since it was loaded or saved
+    pub fn is_changed_on_disk(&self) -> bool {
+        match (&self.file_name, &self.disk_state) {
+            (Some(file_name), Some(disk_state)) => disk_state.is_changed(Path::new(file_name)),
+            _ => false,
+        }
+    }
+
+    pub fn is_read_only(&self) -> bool {
+        self.read_only
+    }
+
+    pub fn set_read_only(&mut self, read_only: bool) {
+        self.read_only = read_only;
     }
 
     pub fn is_file_given(&self) -> bool {
@@ -219,95 +238,407 @@ impl Buffer {
             return;
         }
 
-        // TODO: maybe should fix this and move this piece of code inside Some block
         if location.line_idx == self.data.len() {
-            self.data.push(Line::from(""));
+            self.perform(Operation::PushLine);
         }
 
-        if let Some(selected_line) = self.data.get_mut(location.line_idx) {
-            selected_line.add_character_to_line(chr, location.grapheme_idx);
-            self.is_modified = true;
-        };
+        self.perform(Operation::Insert {
+            at: location,
+            text: chr.to_string(),
+        });
     }
 
     pub fn delete_character_at(&mut self, location: Location) {
-        // Safeguard, should not happen
-        if location.line_idx > self.data.len() {
-            return;
-        }
-
         // Backspace already transalted into delete so it only one case now
-        // Delete when caret is at the end of the line
-
-        if self.data.get_mut(location.line_idx).is_none() {
+        let Some(selected_line) = self.data.get(location.line_idx) else {
             return;
-        }
+        };
 
         let number_of_lines = self.get_number_of_lines();
-        let selected_line = self.data.get_mut(location.line_idx).unwrap();
         let line_length = selected_line.grapheme_count();
 
-        // Deletion at non specific point
-        if location.grapheme_idx != line_length {
-            selected_line.delete_character(location.grapheme_idx);
-            self.is_modified = true;
-        } else if location.grapheme_idx == line_length
-            && location.line_idx.saturating_add(1) < number_of_lines
-        {
+        if location.grapheme_idx < line_length {
+            // Deletion at non specific point
+            let text = selected_line
+                .get_grapheme(location.grapheme_idx)
+                .unwrap_or_default()
+                .to_string();
+            self.perform(Operation::Remove { at: location, text });
+        } else if location.line_idx.saturating_add(1) < number_of_lines {
             // Deletion at the end of the line
-            let next_line = self.data.remove(location.line_idx.saturating_add(1));
-            // This is the same code for `selected_line` had to be written this way so the reference from before would be dropped
-            let selected_line = self.data.get_mut(location.line_idx).unwrap();
-            selected_line.concat(&next_line);
-            self.is_modified = true;
+            self.perform(Operation::Remove {
+                at: location,
+                text: String::from("\n"),
+            });
         }
     }
 
     pub fn insert_newline(&mut self, location: Location) {
         if location.line_idx == self.get_number_of_lines() {
-            self.data.push(Line::default());
+            self.perform(Operation::PushLine);
             return;
         }
 
-        if let Some(working_line) = self.data.get_mut(location.line_idx) {
-            let cut_off = working_line.split_off(location.grapheme_idx);
+        if location.line_idx < self.get_number_of_lines() {
+            self.perform(Operation::Insert {
+                at: location,
+                text: String::from("\n"),
+            });
+        }
+    }
+
+    /// Inserts text which can span multiple lines, returns location right after it
+    pub fn insert_text_at(&mut self, text: &str, location: Location) -> Location {
+        if location.line_idx > self.data.len() || text.is_empty() {
+            return location;
+        }
+
+        if location.line_idx == self.data.len() {
+            self.perform(Operation::PushLine);
+        }
+
+        self.perform(Operation::Insert {
+            at: location,
+            text: text.to_string(),
+        });
+
+        Self::end_of_text(location, text)
+    }
+
+    /// Changes how the buffer is written to disk, recorded in history like any other edit
+    pub fn set_file_format(&mut self, file_format: FileFormat) {
+        if file_format != self.file_format {
+            self.perform(Operation::SetFormat {
+                from: self.file_format,
+                to: file_format,
+            });
+        }
+    }
+
+    pub fn delete_range(&mut self, start: Location, end: Location) {
+        let text = self.get_text(start, end);
+
+        if !text.is_empty() {
+            self.perform(Operation::Remove { at: start, text });
+        }
+    }
+
+    // ======================================================= Undo/Redo =========================================================
+
+    /// Everything performed between `begin_edit` and `end_edit` is undone as a single step
+    pub fn begin_edit(&mut self, caret: Location, kind: GroupKind) {
+        self.history.begin(caret, kind);
+    }
+
+    pub fn end_edit(&mut self, caret: Location) {
+        self.history.end(caret);
+    }
+
+    pub fn break_edit_group(&mut self) {
+        self.history.seal();
+    }
+
+    /// Returns where the caret was before the undone edit
+    pub fn undo(&mut self) -> Option<Location> {
+        let group = self.history.pop_undo()?;
+        let caret = group.caret_before;
+        let operations: Vec<Operation> = group
+            .operations
+            .iter()
+            .rev()
+            .map(Operation::inverse)
+            .collect();
+
+        for operation in &operations {
+            self.apply(operation);
+        }
+        self.forget_modified_lines_if_saved();
+
+        Some(caret)
+    }
+
+    /// Returns where the caret was after the redone edit
+    pub fn redo(&mut self) -> Option<Location> {
+        let group = self.history.pop_redo()?;
+        let caret = group.caret_after;
+        let operations = group.operations.clone();
+
+        for operation in &operations {
+            self.apply(operation);
+        }
+        self.forget_modified_lines_if_saved();
+
+        Some(caret)
+    }
+
+    fn perform(&mut self, operation: Operation) {
+        self.apply(&operation);
+        self.history.record(operation);
+    }
+
+    fn apply(&mut self, operation: &Operation) {
+        self.changed_since_swap = true;
+
+        match operation {
+            Operation::Insert { at, .. } | Operation::Remove { at, .. } => {
+                self.mark_changed(at.line_idx);
+            }
+            Operation::PushLine => self.mark_changed(self.data.len()),
+            Operation::PopLine => self.mark_changed(self.data.len().saturating_sub(1)),
+            Operation::SetFormat { .. } => (),
+        }
+
+        match operation {
+            Operation::Insert { at, text } => {
+                let end = self.insert_text(*at, text);
+                self.mark_modified(at.line_idx..=end.line_idx);
+            }
+            Operation::Remove { at, text } => {
+                let end = Self::end_of_text(*at, text);
+                self.remove_text(*at, end);
+                self.mark_modified(at.line_idx..=at.line_idx);
+            }
+            Operation::PushLine => {
+                self.data.push(Line::default());
+                let last = self.data.len().saturating_sub(1);
+                self.mark_modified(last..=last);
+            }
+            Operation::PopLine => {
+                self.data.pop();
+            }
+            Operation::SetFormat { to, .. } => self.file_format = *to,
+        }
+    }
+
+    fn mark_changed(&mut self, line_idx: LineIdx) {
+        self.revision = self.revision.saturating_add(1);
+        self.changed_lines.push_back(line_idx);
+
+        if self.changed_lines.len() > MAX_CHANGES {
+            self.changed_lines.pop_front();
+        }
+    }
+
+    fn mark_modified(&mut self, lines: RangeInclusive<LineIdx>) {
+        for line_idx in lines {
+            if let Some(line) = self.data.get_mut(line_idx) {
+                line.set_modified(true);
+            }
+        }
+    }
+
+    /// Undoing back to the saved state leaves nothing to mark
+    fn forget_modified_lines_if_saved(&mut self) {
+        if !self.is_modified() {
             self.data
-                .insert(location.line_idx.saturating_add(1), cut_off);
+                .iter_mut()
+                .for_each(|line| line.set_modified(false));
         }
+    }
+
+    /// Inserts text which can span multiple lines, returns location right after inserted text
+    fn insert_text(&mut self, at: Location, text: &str) -> Location {
+        let Some(line) = self.data.get_mut(at.line_idx) else {
+            return at;
+        };
 
-        self.is_modified = true;
+        let tail = line.split_off(at.grapheme_idx);
+        let mut parts = text.split('\n');
+
+        line.concat(&Line::from(parts.next().unwrap_or_default()));
+
+        let new_lines: Vec<Line> = parts.map(Line::from).collect();
+        let line_idx = at.line_idx.saturating_add(new_lines.len());
+        self.data
+            .insert_many(at.line_idx.saturating_add(1), new_lines);
+
+        let Some(last_line) = self.data.get_mut(line_idx) else {
+            return at;
+        };
+
+        let end = Location {
+            line_idx,
+            grapheme_idx: last_line.grapheme_count(),
+        };
+        last_line.concat(&tail);
+
+        end
     }
 
-    // =================================================== Loading/Saving File ======================================================
+    /// Removes text between two locations and returns it
+    fn remove_text(&mut self, start: Location, end: Location) -> String {
+        if start.line_idx == end.line_idx {
+            let Some(line) = self.data.get_mut(start.line_idx) else {
+                return String::new();
+            };
+
+            let tail = line.split_off(end.grapheme_idx);
+            let removed = line.split_off(start.grapheme_idx);
+            line.concat(&tail);
 
-    pub fn load(&mut self, file_name: &str) {
-        if let Ok(context) = std::fs::read_to_string(file_name) {
-            self.clear();
-            self.file_name = Some(file_name.to_string());
+            return removed.to_string();
+        }
+
+        let last_line_idx = std::cmp::min(end.line_idx, self.data.len().saturating_sub(1));
+        let mut removed_lines = self
+            .data
+            .remove_range(start.line_idx.saturating_add(1)..last_line_idx.saturating_add(1));
 
-            for line in context.lines() {
-                self.data.push(Line::from(line));
+        let tail = removed_lines
+            .last_mut()
+            .map(|line| line.split_off(end.grapheme_idx))
+            .unwrap_or_default();
+
+        let Some(line) = self.data.get_mut(start.line_idx) else {
+            return String::new();
+        };
+
+        let mut removed = line.split_off(start.grapheme_idx).to_string();
+        line.concat(&tail);
+
+        for removed_line in removed_lines {
+            removed.push('\n');
+            removed.push_str(&removed_line);
+        }
+
+        removed
+    }
+
+    fn end_of_text(at: Location, text: &str) -> Location {
+        let line_breaks = text.matches('\n').count();
+        let last_part = text.rsplit('\n').next().unwrap_or_default();
+        let last_part_len = Line::from(last_part).grapheme_count();
+
+        if line_breaks == 0 {
+            Location {
+                line_idx: at.line_idx,
+                grapheme_idx: at.grapheme_idx.saturating_add(last_part_len),
+            }
+        } else {
+            Location {
+                line_idx: at.line_idx.saturating_add(line_breaks),
+                grapheme_idx: last_part_len,
             }
         }
     }
 
-    pub fn save(&mut self) -> Result<(), std::io::Error> {
-        if let Some(file_name) = self.file_name.clone() {
-            let mut file = OpenOptions::new()
-                .write(true)
-                .create(true)
-                .truncate(true)
-                .open(&file_name)?;
+    // =================================================== Loading/Saving File ======================================================
+
+    pub fn load(&mut self, file_name: &str) -> Result<(), std::io::Error> {
+        self.read_file(file_name)
+    }
+
+    /// Text without a file behind it, e.g. read from stdin
+    pub fn load_text(&mut self, text: &str) {
+        self.set_content(text);
+        self.file_name = None;
+        self.disk_state = None;
+    }
+
+    /// Discards everything in the buffer and reads the file again
+    pub fn reload(&mut self) -> Result<(), std::io::Error> {
+        let Some(file_name) = self.file_name.clone() else {
+            return Ok(());
+        };
+
+        self.read_file(&file_name)?;
+        file_io::remove_swap(Path::new(&file_name));
+
+        Ok(())
+    }
+
+    fn read_file(&mut self, file_name: &str) -> Result<(), std::io::Error> {
+        let context = std::fs::read_to_string(file_name)?;
+
+        self.set_content(&context);
+        self.file_name = Some(file_name.to_string());
+        self.disk_state = Some(DiskState::new(Path::new(file_name), context.as_bytes()));
 
-            for line in &self.data {
-                let string = line.to_string();
+        Ok(())
+    }
+
+    /// Replaces everything in the buffer, undo history included
+    fn set_content(&mut self, content: &str) {
+        self.clear();
+        self.history.clear();
+        self.changed_since_swap = false;
+        self.file_format = FileFormat::detect(content);
+        self.mark_changed(0);
+
+        self.data = self
+            .file_format
+            .split_lines(content)
+            .into_iter()
+            .map(Line::from)
+            .collect();
+    }
 
-                writeln!(file, "{string}")?;
+    /// Written to a temporary file first and renamed over the original
+    pub fn save(&mut self, keep_backup: bool) -> Result<(), std::io::Error> {
+        if let Some(file_name) = self.file_name.clone() {
+            let path = Path::new(&file_name);
+            let content = self
+                .file_format
+                .join_lines(self.data.iter().map(|line| &**line));
+
+            if keep_backup {
+                file_io::write_backup(path)?;
             }
 
-            self.is_modified = false;
+            file_io::write_atomically(path, content.as_bytes())?;
+            self.disk_state = Some(DiskState::new(path, content.as_bytes()));
+
+            self.history.mark_saved();
+            self.forget_modified_lines_if_saved();
+            self.changed_since_swap = false;
+            file_io::remove_swap(path);
         }
 
         Ok(())
     }
+
+    // ====================================================== Swap File ==============================================================
+
+    /// Keeps the swap file in sync with unsaved changes, does nothing when nothing changed since last call
+    pub fn write_swap(&mut self) -> Result<(), std::io::Error> {
+        let Some(file_name) = self.file_name.clone() else {
+            return Ok(());
+        };
+
+        if !self.changed_since_swap {
+            return Ok(());
+        }
+
+        let path = Path::new(&file_name);
+        if self.is_modified() {
+            file_io::write_swap(path, &self.get_content())?;
+        } else {
+            file_io::remove_swap(path);
+        }
+
+        self.changed_since_swap = false;
+        Ok(())
+    }
+
+    pub fn remove_swap(&self) {
+        if let Some(file_name) = &self.file_name {
+            file_io::remove_swap(Path::new(file_name));
+        }
+    }
+
+    /// Content of a swap file left behind by a session that did not save its changes
+    pub fn get_swap_content(&self) -> Option<String> {
+        self.file_name
+            .as_ref()
+            .and_then(|file_name| file_io::read_newer_swap(Path::new(file_name)))
+    }
+
+    /// Whole text, lines are joined with `\n`
+    pub fn get_content(&self) -> String {
+        self.data
+            .iter()
+            .map(|line| &**line)
+            .collect::<Vec<&str>>()
+            .join("\n")
+    }
 }
@@ -0,0 +1,187 @@
+use std::fmt::Display;
+
+const BOM: char = '\u{feff}';
+
+#[derive(Clone, Copy, Default, PartialEq, Eq)]
+pub enum LineEnding {
+    #[default]
+    Lf,
+    CrLf,
+}
+
+impl LineEnding {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            LineEnding::Lf => "\n",
+            LineEnding::CrLf => "\r\n",
+        }
+    }
+
+    pub fn toggled(self) -> Self {
+        match self {
+            LineEnding::Lf => LineEnding::CrLf,
+            LineEnding::CrLf => LineEnding::Lf,
+        }
+    }
+}
+
+impl Display for LineEnding {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LineEnding::Lf => write!(f, "LF"),
+            LineEnding::CrLf => write!(f, "CRLF"),
+        }
+    }
+}
+
+/// Everything about the file on disk that is not part of the lines themselves
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub struct FileFormat {
+    pub line_ending: LineEnding,
+    pub trailing_newline: bool,
+    pub bom: bool,
+}
+
+impl Default for FileFormat {
+    fn default() -> Self {
+        FileFormat {
+            line_ending: LineEnding::Lf,
+            trailing_newline: true,
+            bom: false,
+        }
+    }
+}
+
+impl FileFormat {
+    /// Only files using CRLF for every line break count as CRLF, files with mixed endings are
+    /// treated as LF and keep their `\r` characters as line content so they round-trip unchanged
+    pub fn detect(content: &str) -> Self {
+        let crlf_count = content.matches("\r\n").count();
+        let lf_count = content.matches('\n').count().saturating_sub(crlf_count);
+
+        FileFormat {
+            line_ending: if crlf_count > 0 && lf_count == 0 {
+                LineEnding::CrLf
+            } else {
+                LineEnding::Lf
+            },
+            trailing_newline: content.is_empty() || content.ends_with('\n'),
+            bom: content.starts_with(BOM),
+        }
+    }
+
+    /// Splits file content into lines according to this format
+    pub fn split_lines(self, content: &str) -> Vec<&str> {
+        let content = content.strip_prefix(BOM).unwrap_or(content);
+
+        if content.is_empty() {
+            return Vec::new();
+        }
+
+        let body = if self.trailing_newline {
+            content.strip_suffix('\n').unwrap_or(content)
+        } else {
+            content
+        };
+
+        body.split('\n')
+            .map(|line| match self.line_ending {
+                LineEnding::CrLf => line.strip_suffix('\r').unwrap_or(line),
+                LineEnding::Lf => line,
+            })
+            .collect()
+    }
+
+    /// Inverse of `split_lines`
+    pub fn join_lines<'a, I>(self, lines: I) -> String
+    where
+        I: Iterator<Item = &'a str>,
+    {
+        let mut result = String::new();
+
+        if self.bom {
+            result.push(BOM);
+        }
+
+        let mut line_count: usize = 0;
+        for line in lines {
+            if line_count > 0 {
+                result.push_str(self.line_ending.as_str());
+            }
+            result.push_str(line);
+            line_count = line_count.saturating_add(1);
+        }
+
+        if self.trailing_newline && line_count > 0 {
+            result.push_str(self.line_ending.as_str());
+        }
+
+        result
+    }
+}
+
+impl Display for FileFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let bom = if self.bom { " BOM" } else { "" };
+        let eol = if self.trailing_newline { "" } else { " noeol" };
+
+        write!(f, "UTF-8{bom} {}{eol}", self.line_ending)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn round_trip(content: &str) -> (FileFormat, Vec<String>, String) {
+        let format = FileFormat::detect(content);
+        let lines: Vec<String> = format
+            .split_lines(content)
+            .into_iter()
+            .map(str::to_string)
+            .collect();
+        let joined = format.join_lines(lines.iter().map(String::as_str));
+
+        (format, lines, joined)
+    }
+
+    #[test]
+    fn crlf_with_bom_round_trips() {
+        let content = "\u{feff}first\r\nsecond\r\n";
+        let (format, lines, joined) = round_trip(content);
+
+        assert!(format.bom);
+        assert!(format.trailing_newline);
+        assert!(format.line_ending == LineEnding::CrLf);
+        assert_eq!(lines, ["first", "second"]);
+        assert_eq!(joined, content);
+    }
+
+    #[test]
+    fn missing_trailing_newline_round_trips() {
+        let content = "first\nsecond";
+        let (format, lines, joined) = round_trip(content);
+
+        assert!(!format.trailing_newline);
+        assert!(format.line_ending == LineEnding::Lf);
+        assert_eq!(lines, ["first", "second"]);
+        assert_eq!(joined, content);
+    }
+
+    #[test]
+    fn mixed_endings_keep_their_carriage_returns() {
+        let content = "first\r\nsecond\nthird";
+        let (format, lines, joined) = round_trip(content);
+
+        assert!(format.line_ending == LineEnding::Lf);
+        assert_eq!(lines, ["first\r", "second", "third"]);
+        assert_eq!(joined, content);
+    }
+
+    #[test]
+    fn empty_content_and_empty_lines_round_trip() {
+        for content in ["", "\n", "\n\n", "\r\n", "\u{feff}"] {
+            assert_eq!(round_trip(content).2, content);
+        }
+    }
+}
@@ -0,0 +1,194 @@
+use std::fs::{self, File, OpenOptions};
+use std::hash::{DefaultHasher, Hash, Hasher};
+use std::io::{Error, ErrorKind, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::SystemTime;
+
+/// Names tried for a temporary file before giving up
+const TEMP_ATTEMPTS: usize = 100;
+
+/// Makes temporary names unique within the process, the pid keeps them apart between processes
+static TEMP_COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+/// What the file looked like when it was last read or written by the editor
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub struct DiskState {
+    modified: Option<SystemTime>,
+    len: u64,
+    hash: u64,
+}
+
+impl DiskState {
+    /// State of a file whose content was just read or written
+    pub fn new(path: &Path, content: &[u8]) -> Self {
+        DiskState {
+            modified: fs::metadata(path)
+                .and_then(|metadata| metadata.modified())
+                .ok(),
+            len: content.len() as u64,
+            hash: hash(content),
+        }
+    }
+
+    /// Unchanged size and modification time are trusted, otherwise the content decides,
+    /// a file which disappeared does not count as changed since there is nothing to overwrite
+    pub fn is_changed(&self, path: &Path) -> bool {
+        let Ok(metadata) = fs::metadata(path) else {
+            return false;
+        };
+
+        if metadata.len() == self.len && metadata.modified().ok() == self.modified {
+            return false;
+        }
+
+        fs::read(path).is_ok_and(|content| hash(&content) != self.hash)
+    }
+}
+
+/// Writes content next to the target and renames it over, so the target is never half written
+pub fn write_atomically(path: &Path, content: &[u8]) -> Result<(), Error> {
+    // Follow symlinks so the link itself is not replaced by a regular file
+    let target = fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
+    let (file, temp_path) = create_temp(&target)?;
+
+    let result = write_and_rename(file, &target, &temp_path, content);
+
+    if result.is_err() {
+        let _ = fs::remove_file(&temp_path);
+    }
+
+    result
+}
+
+/// Copies current content of the file to `<file>.bak`, missing file has nothing to back up
+pub fn write_backup(path: &Path) -> Result<(), Error> {
+    if path.exists() {
+        let mut backup = path.as_os_str().to_owned();
+        backup.push(".bak");
+        fs::copy(path, backup)?;
+    }
+
+    Ok(())
+}
+
+// ==================================================== Swap files ==================================================================
+
+pub fn write_swap(path: &Path, content: &str) -> Result<(), Error> {
+    write_atomically(&swap_path(path), content.as_bytes())
+}
+
+pub fn remove_swap(path: &Path) {
+    let _ = fs::remove_file(swap_path(path));
+}
+
+/// Content of the swap file when it was written after the file itself was last modified
+pub fn read_newer_swap(path: &Path) -> Option<String> {
+    let swap_path = swap_path(path);
+    let swap_modified = fs::metadata(&swap_path).ok()?.modified().ok()?;
+
+    let is_newer = fs::metadata(path)
+        .and_then(|metadata| metadata.modified())
+        .map_or(true, |file_modified| swap_modified > file_modified);
+
+    if is_newer {
+        fs::read_to_string(swap_path).ok()
+    } else {
+        None
+    }
+}
+
+// ==================================================== HELPER METHODS ==============================================================
+
+fn hash(content: &[u8]) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    content.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// New file `dir/.name.<pid>.<n>.tmp`, an existing file is never opened so it cannot be truncated
+fn create_temp(target: &Path) -> Result<(File, PathBuf), Error> {
+    let pid = std::process::id();
+
+    for _ in 0..TEMP_ATTEMPTS {
+        let count = TEMP_COUNTER.fetch_add(1, Ordering::Relaxed);
+        let temp_path = sibling_path(target, ".", &format!(".{pid}.{count}.tmp"));
+
+        match OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(&temp_path)
+        {
+            Ok(file) => return Ok((file, temp_path)),
+            Err(err) if err.kind() == ErrorKind::AlreadyExists => (),
+            Err(err) => return Err(err),
+        }
+    }
+
+    Err(Error::new(
+        ErrorKind::AlreadyExists,
+        "no free name for a temporary file",
+    ))
+}
+
+fn write_and_rename(
+    mut file: File,
+    target: &Path,
+    temp_path: &Path,
+    content: &[u8],
+) -> Result<(), Error> {
+    file.write_all(content)?;
+    file.sync_all()?;
+
+    if let Ok(metadata) = fs::metadata(target) {
+        fs::set_permissions(temp_path, metadata.permissions())?;
+    }
+
+    fs::rename(temp_path, target)
+}
+
+/// `dir/name` becomes `dir/.name.swp`, hidden next to the file like vim does
+fn swap_path(path: &Path) -> PathBuf {
+    sibling_path(path, ".", ".swp")
+}
+
+fn sibling_path(path: &Path, prefix: &str, suffix: &str) -> PathBuf {
+    let file_name = path
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default();
+
+    path.with_file_name(format!("{prefix}{file_name}{suffix}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_dir(test: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("editr-{test}-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).expect("temp dir can be created");
+        dir
+    }
+
+    #[test]
+    fn files_named_like_the_temporary_file_are_left_alone() {
+        let dir = temp_dir("atomic");
+        let target = dir.join("foo");
+        let lookalike = dir.join(".foo.tmp");
+        fs::write(&lookalike, "mine").expect("file can be written");
+
+        write_atomically(&target, b"saved").expect("save succeeds");
+        write_atomically(&target, b"saved again").expect("second save succeeds");
+
+        assert_eq!(
+            fs::read_to_string(&target).ok().as_deref(),
+            Some("saved again")
+        );
+        assert_eq!(fs::read_to_string(&lookalike).ok().as_deref(), Some("mine"));
+        assert_eq!(fs::read_dir(&dir).map(Iterator::count).ok(), Some(2));
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,155 @@
+use std::collections::HashMap;
+use std::fmt::{self, Display};
+
+use serde::Deserialize;
+
+use crate::editor::{
+    annotated_string::{AnnotatedString, annotation::Annotation, annotation_type::AnnotationType},
+    line::LineIdx,
+};
+
+/// Space between line numbers and the text
+const SEPARATOR: &str = " ";
+
+#[derive(Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LineNumbers {
+    /// No gutter at all
+    Off,
+    #[default]
+    Absolute,
+    /// Distance from the caret line, the caret line itself shows its absolute number
+    Relative,
+}
+
+impl LineNumbers {
+    pub fn next(self) -> Self {
+        match self {
+            LineNumbers::Off => LineNumbers::Absolute,
+            LineNumbers::Absolute => LineNumbers::Relative,
+            LineNumbers::Relative => LineNumbers::Off,
+        }
+    }
+}
+
+impl Display for LineNumbers {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            LineNumbers::Off => "off",
+            LineNumbers::Absolute => "absolute",
+            LineNumbers::Relative => "relative",
+        };
+        write!(f, "{name}")
+    }
+}
+
+/// Marker shown in front of a line number, symbol has to be a single column wide
+#[derive(Clone, Copy)]
+pub struct Sign {
+    pub symbol: char,
+    pub annotation_type: AnnotationType,
+}
+
+impl Sign {
+    pub const MODIFIED: Sign = Sign {
+        symbol: '│',
+        annotation_type: AnnotationType::ModifiedLine,
+    };
+
+    pub const SEARCH_HIT: Sign = Sign {
+        symbol: '•',
+        annotation_type: AnnotationType::SearchHit,
+    };
+
+    pub const ERROR: Sign = Sign {
+        symbol: 'E',
+        annotation_type: AnnotationType::Diagnostic,
+    };
+
+    pub const WARNING: Sign = Sign {
+        symbol: 'W',
+        annotation_type: AnnotationType::Diagnostic,
+    };
+}
+
+/// Column of signs followed by line numbers on the left of the text
+#[derive(Clone, Default)]
+pub struct Gutter {
+    pub line_numbers: LineNumbers,
+    /// Signs supplied from outside the editor, e.g. compiler diagnostics, win over built-in ones
+    diagnostics: HashMap<LineIdx, Sign>,
+}
+
+impl Gutter {
+    /// Width grows with the number of digits of the last line number
+    pub fn width(&self, number_of_lines: usize) -> usize {
+        if self.line_numbers == LineNumbers::Off {
+            return 0;
+        }
+
+        Self::digits(number_of_lines)
+            .saturating_add(1)
+            .saturating_add(SEPARATOR.len())
+    }
+
+    pub fn set_diagnostics(&mut self, diagnostics: HashMap<LineIdx, Sign>) {
+        self.diagnostics = diagnostics;
+    }
+
+    pub fn diagnostic(&self, line_idx: LineIdx) -> Option<Sign> {
+        self.diagnostics.get(&line_idx).copied()
+    }
+
+    /// Gutter of a single row, `None` as line is a continuation of a wrapped line and stays blank
+    pub fn render(
+        &self,
+        line_idx: Option<LineIdx>,
+        caret_line_idx: LineIdx,
+        number_of_lines: usize,
+        sign: Option<Sign>,
+    ) -> AnnotatedString {
+        let width = self.width(number_of_lines);
+
+        let Some(line_idx) = line_idx.filter(|_| width > 0) else {
+            return AnnotatedString {
+                string: " ".repeat(width),
+                annotations: Vec::new(),
+            };
+        };
+
+        let number = match self.line_numbers {
+            LineNumbers::Relative if line_idx != caret_line_idx => {
+                line_idx.abs_diff(caret_line_idx)
+            }
+            _ => line_idx.saturating_add(1),
+        };
+        let number_type = if line_idx == caret_line_idx {
+            AnnotationType::CurrentLineNumber
+        } else {
+            AnnotationType::LineNumber
+        };
+
+        let symbol = sign.map_or(' ', |sign| sign.symbol);
+        let digits = Self::digits(number_of_lines);
+        let string = format!("{symbol}{number:>digits$}{SEPARATOR}");
+
+        let number_start = symbol.len_utf8();
+        let mut annotations = vec![Annotation::new(
+            number_start,
+            number_start.saturating_add(digits),
+            number_type,
+        )];
+        if let Some(sign) = sign {
+            annotations.push(Annotation::new(0, number_start, sign.annotation_type));
+        }
+
+        AnnotatedString {
+            string,
+            annotations,
+        }
+    }
+
+    fn digits(number_of_lines: usize) -> usize {
+        number_of_lines.max(1).to_string().len()
+    }
+}
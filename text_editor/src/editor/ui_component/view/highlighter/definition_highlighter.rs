@@ -0,0 +1,279 @@
+use unicode_segmentation::UnicodeSegmentation;
+
+use crate::editor::{
+    annotated_string::{annotation::Annotation, annotation_type::AnnotationType},
+    line::{ByteIdx, Line, LineIdx},
+};
+
+use super::syntax_definition::SyntaxDefinition;
+use super::syntax_highlihter::SyntaxHighlighter;
+
+/// Comment or string which did not end on the line where it started
+#[derive(Clone, Copy)]
+enum OpenBlock {
+    Comment { rule: usize, depth: usize },
+    String { rule: usize },
+}
+
+/// Highlights any language described by a `SyntaxDefinition`
+pub struct DefinitionHighlighter {
+    definition: &'static SyntaxDefinition,
+    highlights: Vec<Vec<Annotation>>,
+    /// Block still open at the end of each line
+    end_states: Vec<Option<OpenBlock>>,
+    open_block: Option<OpenBlock>,
+}
+
+impl DefinitionHighlighter {
+    pub fn new(definition: &'static SyntaxDefinition) -> Self {
+        DefinitionHighlighter {
+            definition,
+            highlights: Vec::new(),
+            end_states: Vec::new(),
+            open_block: None,
+        }
+    }
+
+    /// Annotates whatever starts at given byte, returns where the next token starts
+    fn annotate_at(&mut self, line: &str, start: ByteIdx, result: &mut Vec<Annotation>) -> ByteIdx {
+        let rest = &line[start..];
+
+        if self
+            .definition
+            .line_comments
+            .iter()
+            .any(|comment| rest.starts_with(comment.as_str()))
+        {
+            result.push(Annotation::new(start, line.len(), AnnotationType::Comment));
+            return line.len();
+        }
+
+        if let Some((rule, comment)) = self
+            .definition
+            .block_comments
+            .iter()
+            .enumerate()
+            .find(|(_, comment)| rest.starts_with(comment.start.as_str()))
+        {
+            let from = start.saturating_add(comment.start.len());
+            return self.close_block(
+                OpenBlock::Comment { rule, depth: 1 },
+                line,
+                from,
+                start,
+                result,
+            );
+        }
+
+        if let Some((rule, string)) = self
+            .definition
+            .strings
+            .iter()
+            .enumerate()
+            .find(|(_, string)| rest.starts_with(string.start.as_str()))
+        {
+            let from = start.saturating_add(string.start.len());
+            return self.close_block(OpenBlock::String { rule }, line, from, start, result);
+        }
+
+        let word = rest.split_word_bounds().next().unwrap_or(rest);
+        if let Some(annotation_type) = self.classify_word(word) {
+            result.push(Annotation::new(
+                start,
+                start.saturating_add(word.len()),
+                annotation_type,
+            ));
+        }
+
+        start.saturating_add(word.len().max(1))
+    }
+
+    /// Looks for the end of a block starting at `from`, the block stays open when the line ends first
+    fn close_block(
+        &mut self,
+        mut block: OpenBlock,
+        line: &str,
+        from: ByteIdx,
+        annotation_start: ByteIdx,
+        result: &mut Vec<Annotation>,
+    ) -> ByteIdx {
+        let mut pos = from;
+
+        while let Some(rest) = line.get(pos..)
+            && let Some(chr) = rest.chars().next()
+        {
+            match &mut block {
+                OpenBlock::Comment { rule, depth } => {
+                    let comment = &self.definition.block_comments[*rule];
+
+                    if self.definition.nested_comments && rest.starts_with(comment.start.as_str()) {
+                        *depth = depth.saturating_add(1);
+                        pos = pos.saturating_add(comment.start.len());
+                        continue;
+                    }
+
+                    if rest.starts_with(comment.end.as_str()) {
+                        *depth = depth.saturating_sub(1);
+                        pos = pos.saturating_add(comment.end.len());
+
+                        if *depth == 0 {
+                            result.push(Annotation::new(
+                                annotation_start,
+                                pos,
+                                AnnotationType::Comment,
+                            ));
+                            return pos;
+                        }
+                        continue;
+                    }
+                }
+                OpenBlock::String { rule } => {
+                    let string = &self.definition.strings[*rule];
+
+                    if let Some(escape) = &string.escape
+                        && rest.starts_with(escape.as_str())
+                    {
+                        // Skip the escape sequence together with the escaped character
+                        pos = pos.saturating_add(escape.len());
+                        let escaped = line[pos..].chars().next().map_or(0, char::len_utf8);
+                        pos = pos.saturating_add(escaped);
+                        continue;
+                    }
+
+                    if rest.starts_with(string.end()) {
+                        pos = pos.saturating_add(string.end().len());
+                        result.push(Annotation::new(annotation_start, pos, string.annotation));
+                        return pos;
+                    }
+                }
+            }
+
+            pos = pos.saturating_add(chr.len_utf8());
+        }
+
+        let annotation_type = match block {
+            OpenBlock::Comment { .. } => {
+                self.open_block = Some(block);
+                AnnotationType::Comment
+            }
+            OpenBlock::String { rule } => {
+                let string = &self.definition.strings[rule];
+                if string.multiline {
+                    self.open_block = Some(block);
+                }
+                string.annotation
+            }
+        };
+
+        result.push(Annotation::new(
+            annotation_start,
+            line.len(),
+            annotation_type,
+        ));
+        line.len()
+    }
+
+    fn annotate_line_prefix(&self, line: &str) -> Option<Annotation> {
+        let trimmed = line.trim_start();
+
+        self.definition
+            .line_prefixes
+            .iter()
+            .find(|rule| trimmed.starts_with(rule.prefix.as_str()))
+            .map(|rule| Annotation::new(0, line.len(), rule.annotation))
+    }
+
+    fn classify_word(&self, word: &str) -> Option<AnnotationType> {
+        let contains = |words: &[String]| words.iter().any(|known| known == word);
+
+        if self.is_a_number(word) {
+            Some(AnnotationType::Number)
+        } else if contains(&self.definition.types) {
+            Some(AnnotationType::Type)
+        } else if contains(&self.definition.known_values) {
+            Some(AnnotationType::KnownValue)
+        } else if contains(&self.definition.keywords) {
+            Some(AnnotationType::KeyWord)
+        } else {
+            None
+        }
+    }
+
+    fn is_a_number(&self, word: &str) -> bool {
+        let Some(rules) = &self.definition.numbers else {
+            return false;
+        };
+
+        let are_digits = |digits: &str, radix: u32| {
+            digits.starts_with(|chr: char| chr.is_digit(radix))
+                && digits
+                    .chars()
+                    .all(|chr| chr.is_digit(radix) || (rules.underscores && chr == '_'))
+        };
+
+        for prefix in &rules.prefixes {
+            if word
+                .get(..prefix.len())
+                .is_some_and(|start| start.eq_ignore_ascii_case(prefix))
+            {
+                let radix = match prefix.chars().next_back() {
+                    Some('x' | 'X') => 16,
+                    Some('o' | 'O') => 8,
+                    Some('b' | 'B') => 2,
+                    _ => 10,
+                };
+                return are_digits(&word[prefix.len()..], radix);
+            }
+        }
+
+        let (mantissa, exponent) = match word.split_once(['e', 'E']) {
+            Some((mantissa, exponent)) => (mantissa, Some(exponent)),
+            None => (word, None),
+        };
+
+        let (integer, fraction) = match mantissa.split_once('.') {
+            Some((integer, fraction)) => (integer, Some(fraction)),
+            None => (mantissa, None),
+        };
+
+        are_digits(integer, 10)
+            && fraction.is_none_or(|fraction| are_digits(fraction, 10))
+            && exponent.is_none_or(|exponent| are_digits(exponent, 10))
+    }
+}
+
+impl SyntaxHighlighter for DefinitionHighlighter {
+    fn highlight(&mut self, idx: LineIdx, line: &Line) {
+        if idx > self.highlights.len() {
+            return;
+        }
+
+        self.highlights.truncate(idx);
+        self.end_states.truncate(idx);
+        self.open_block = idx
+            .checked_sub(1)
+            .and_then(|previous| self.end_states.get(previous).copied())
+            .flatten();
+
+        let mut result = Vec::new();
+        let mut pos = 0;
+
+        if let Some(block) = self.open_block.take() {
+            pos = self.close_block(block, line, 0, 0, &mut result);
+        } else if let Some(annotation) = self.annotate_line_prefix(line) {
+            result.push(annotation);
+            pos = line.len();
+        }
+
+        while pos < line.len() {
+            pos = self.annotate_at(line, pos, &mut result);
+        }
+
+        self.highlights.push(result);
+        self.end_states.push(self.open_block);
+    }
+
+    fn get_annotations(&self, idx: LineIdx) -> Option<&Vec<Annotation>> {
+        self.highlights.get(idx)
+    }
+}
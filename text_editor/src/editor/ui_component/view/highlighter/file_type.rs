@@ -1,22 +1,55 @@
 use std::fmt::Display;
 
-#[derive(Clone, Copy, Default)]
+use serde::Deserialize;
+
+use super::syntax_definition::SyntaxDefinition;
+
+/// Named in the config as `rust`, `txt` or the name of a syntax definition, e.g. `python`
+#[derive(Clone, Copy, Default, PartialEq, Eq, Hash, Deserialize)]
+#[serde(try_from = "String")]
 pub enum FileType {
     Rust,
+    /// Language described by the syntax definition with this index
+    Defined(usize),
     Txt,
     #[default]
     None,
 }
 
-impl From<String> for FileType {
-    fn from(value: String) -> Self {
-        let last = value.split('.').last();
+impl FileType {
+    /// Decided by the extension, syntax definitions list the extensions they are used for
+    pub fn from_file_name(file_name: &str) -> Self {
+        let Some((_, extension)) = file_name.rsplit_once('.') else {
+            return FileType::None;
+        };
+
+        match extension {
+            "rs" => FileType::Rust,
+            "txt" => FileType::Txt,
+            _ => {
+                SyntaxDefinition::for_extension(extension).map_or(FileType::None, FileType::Defined)
+            }
+        }
+    }
+
+    pub fn syntax_definition(self) -> Option<&'static SyntaxDefinition> {
+        match self {
+            FileType::Defined(idx) => SyntaxDefinition::get(idx),
+            FileType::Rust | FileType::Txt | FileType::None => None,
+        }
+    }
+}
 
-        match last {
-            Some("rs") => FileType::Rust,
-            Some("txt") => FileType::Txt,
+impl TryFrom<String> for FileType {
+    type Error = String;
 
-            _ => FileType::None,
+    fn try_from(name: String) -> Result<Self, Self::Error> {
+        match name.as_str() {
+            "rust" => Ok(FileType::Rust),
+            "txt" => Ok(FileType::Txt),
+            _ => SyntaxDefinition::for_name(&name)
+                .map(FileType::Defined)
+                .ok_or_else(|| format!("Unknown file type '{name}'")),
         }
     }
 }
@@ -25,8 +58,14 @@ impl Display for FileType {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             FileType::Rust => write!(f, "Rust"),
+            FileType::Defined(_) => {
+                let name = self
+                    .syntax_definition()
+                    .map_or("", |definition| definition.name.as_str());
+                write!(f, "{name}")
+            }
             FileType::Txt => write!(f, "Text"),
-            _ => write!(f, ""),
+            FileType::None => write!(f, ""),
         }
     }
 }
@@ -1,81 +1,163 @@
+mod definition_highlighter;
 pub mod file_type;
 mod rust_highlighter;
 mod search_highlighter;
+mod selection_highlighter;
+mod syntax_definition;
 mod syntax_highlihter;
 
+use std::ops::Range;
+
+use definition_highlighter::DefinitionHighlighter;
 use file_type::FileType;
 use rust_highlighter::RustSyntaxHighlighter;
 use search_highlighter::SearchHighlighter;
+use selection_highlighter::SelectionHighlighter;
+use syntax_definition::SyntaxDefinition;
 use syntax_highlihter::SyntaxHighlighter;
 
-use crate::editor::{annotated_string::annotation::Annotation, line::Line};
+use crate::editor::{
+    annotated_string::{annotation::Annotation, annotation_type::AnnotationType},
+    line::{ByteIdx, Line, LineIdx},
+    search_pattern::SearchPattern,
+};
 
 use super::location::Location;
 
+/// Syntax highlighting is kept between redraws and redone only from the first changed line,
+/// search and selection highlighting is cheap and redone for visible lines on every redraw
+#[allow(clippy::struct_field_names)]
+#[derive(Default)]
 pub struct Highlighter {
+    file_type: Option<FileType>,
     syntax_highlighter: Option<Box<dyn SyntaxHighlighter>>,
+    syntax_highlighted_lines: LineIdx,
     search_highlighter: Option<Box<dyn SyntaxHighlighter>>,
+    selection_highlighter: Option<Box<dyn SyntaxHighlighter>>,
 }
 
 impl Highlighter {
-    pub fn new(
-        matched_word: Option<String>,
-        selected_match: Option<Location>,
-        file_type: Option<FileType>,
-    ) -> Self {
-        Highlighter {
-            syntax_highlighter: Self::create_syntax_highlighter(file_type),
-            search_highlighter: Self::create_search_highlighter(matched_word, selected_match),
+    /// Syntax definitions which could not be loaded, the built-in ones are used in their place
+    pub fn definition_errors() -> &'static [String] {
+        SyntaxDefinition::load_errors()
+    }
+
+    /// Syntax highlighting starts over only when file type actually changes
+    pub fn set_file_type(&mut self, file_type: Option<FileType>) {
+        if self.file_type == file_type {
+            return;
         }
+
+        self.file_type = file_type;
+        self.syntax_highlighter = Self::create_syntax_highlighter(file_type);
+        self.syntax_highlighted_lines = 0;
+    }
+
+    pub fn set_overlays(
+        &mut self,
+        pattern: Option<SearchPattern>,
+        selected_match: Option<Location>,
+        selection: Option<Range<Location>>,
+    ) {
+        self.search_highlighter = Some(Self::create_search_highlighter(pattern, selected_match));
+        self.selection_highlighter = Self::create_selection_highlighter(selection);
+    }
+
+    /// Cached syntax highlighting of given line and every line below it is thrown away
+    pub fn invalidate_from(&mut self, line_idx: LineIdx) {
+        self.syntax_highlighted_lines = self.syntax_highlighted_lines.min(line_idx);
+    }
+
+    /// Lines before this one have up to date syntax highlighting
+    pub fn first_stale_line(&self) -> LineIdx {
+        self.syntax_highlighted_lines
     }
 
     fn create_syntax_highlighter(
         file_type: Option<FileType>,
     ) -> Option<Box<dyn SyntaxHighlighter>> {
-        if let Some(file_type) = file_type {
-            match file_type {
-                FileType::Rust => return Some(Box::new(RustSyntaxHighlighter::new())),
-                _ => return None,
-            }
+        match file_type? {
+            FileType::Rust => Some(Box::new(RustSyntaxHighlighter::new())),
+            file_type => file_type.syntax_definition().map(|definition| {
+                Box::new(DefinitionHighlighter::new(definition)) as Box<dyn SyntaxHighlighter>
+            }),
         }
-        None
     }
 
     fn create_search_highlighter(
-        matched_word: Option<String>,
+        pattern: Option<SearchPattern>,
         selected_match: Option<Location>,
+    ) -> Box<dyn SyntaxHighlighter> {
+        Box::new(SearchHighlighter::new(pattern, selected_match))
+    }
+
+    fn create_selection_highlighter(
+        selection: Option<Range<Location>>,
     ) -> Option<Box<dyn SyntaxHighlighter>> {
-        Some(Box::new(SearchHighlighter::new(
-            matched_word,
-            selected_match,
-        )))
+        selection.map(|selection| {
+            Box::new(SelectionHighlighter::new(selection)) as Box<dyn SyntaxHighlighter>
+        })
     }
 
     pub fn get_annotations(&self, line_idx: usize) -> Vec<Annotation> {
         let mut result = Vec::new();
 
-        if let Some(syntax) = &self.syntax_highlighter {
-            if let Some(annotations) = syntax.get_annotations(line_idx) {
-                result.extend(annotations.iter().copied());
-            }
+        if let Some(syntax) = &self.syntax_highlighter
+            && let Some(annotations) = syntax.get_annotations(line_idx)
+        {
+            result.extend(annotations.iter().copied());
+        }
+
+        if let Some(search) = &self.search_highlighter
+            && let Some(annotations) = search.get_annotations(line_idx)
+        {
+            result.extend(annotations.iter().copied());
         }
 
-        if let Some(search) = &self.search_highlighter {
-            if let Some(annotations) = search.get_annotations(line_idx) {
-                result.extend(annotations.iter().copied());
-            }
+        if let Some(selection) = &self.selection_highlighter
+            && let Some(annotations) = selection.get_annotations(line_idx)
+        {
+            result.extend(annotations.iter().copied());
         }
 
         result
     }
 
-    pub fn highlight(&mut self, idx: usize, line: &Line) {
+    /// Byte is inside a comment, string or character literal, only lines with syntax highlighting are known
+    pub fn is_in_literal(&self, line_idx: LineIdx, byte_idx: ByteIdx) -> bool {
+        self.syntax_highlighter
+            .as_ref()
+            .and_then(|syntax| syntax.get_annotations(line_idx))
+            .is_some_and(|annotations| {
+                annotations.iter().any(|annotation| {
+                    matches!(
+                        annotation.annotation_type,
+                        AnnotationType::Comment | AnnotationType::String | AnnotationType::Char
+                    ) && (annotation.start_byte..annotation.end_byte).contains(&byte_idx)
+                })
+            })
+    }
+
+    /// Has to be called for every line from `first_stale_line` on, in order
+    pub fn highlight_syntax(&mut self, idx: LineIdx, line: &Line) {
+        if idx != self.syntax_highlighted_lines {
+            return;
+        }
+
         if let Some(just_syntax) = &mut self.syntax_highlighter {
             just_syntax.as_mut().highlight(idx, line);
         }
 
+        self.syntax_highlighted_lines = idx.saturating_add(1);
+    }
+
+    pub fn highlight_overlays(&mut self, idx: LineIdx, line: &Line) {
         if let Some(search) = &mut self.search_highlighter {
             search.as_mut().highlight(idx, line);
         }
+
+        if let Some(selection) = &mut self.selection_highlighter {
+            selection.as_mut().highlight(idx, line);
+        }
     }
 }
@@ -1,5 +1,3 @@
-use std::collections::HashMap;
-
 use unicode_segmentation::UnicodeSegmentation;
 
 use crate::editor::{
@@ -71,8 +69,16 @@ const TYPES: [&str; 22] = [
 
 const KNOWN_VALUES: [&str; 6] = ["Some", "None", "true", "false", "Ok", "Err"];
 
+/// Multi-line comment and string state at the end of a line
+#[derive(Clone, Copy, Default)]
+struct LineState {
+    ml_comment_balance: usize,
+    in_ml_string: bool,
+}
+
 pub struct RustSyntaxHighlighter {
-    highlights: HashMap<LineIdx, Vec<Annotation>>,
+    highlights: Vec<Vec<Annotation>>,
+    end_states: Vec<LineState>,
     ml_comment_balance: usize,
     in_ml_string: bool,
 }
@@ -80,12 +86,28 @@ pub struct RustSyntaxHighlighter {
 impl RustSyntaxHighlighter {
     pub fn new() -> Self {
         RustSyntaxHighlighter {
-            highlights: HashMap::new(),
+            highlights: Vec::new(),
+            end_states: Vec::new(),
             ml_comment_balance: 0,
             in_ml_string: false,
         }
     }
 
+    /// Forgets given line and everything below it, continuing from the state of the line above
+    fn restart_at(&mut self, idx: LineIdx) {
+        self.highlights.truncate(idx);
+        self.end_states.truncate(idx);
+
+        let state = idx
+            .checked_sub(1)
+            .and_then(|previous| self.end_states.get(previous))
+            .copied()
+            .unwrap_or_default();
+
+        self.ml_comment_balance = state.ml_comment_balance;
+        self.in_ml_string = state.in_ml_string;
+    }
+
     fn is_numeric_literal(word: &str) -> bool {
         if word.len() < 3 {
             return false;
@@ -130,10 +152,10 @@ impl RustSyntaxHighlighter {
 
         let mut chars = word.chars();
 
-        if let Some(first_character) = chars.next() {
-            if !first_character.is_ascii_digit() {
-                return false;
-            }
+        if let Some(first_character) = chars.next()
+            && !first_character.is_ascii_digit()
+        {
+            return false;
         }
 
         let mut seen_dot = false;
@@ -172,7 +194,7 @@ impl RustSyntaxHighlighter {
                 _ => {
                     return false;
                 }
-            };
+            }
         }
         prev_was_digit
     }
@@ -185,14 +207,14 @@ impl RustSyntaxHighlighter {
     where
         T: Fn(&str) -> bool,
     {
-        if let Some(word) = string.split_word_bounds().next() {
-            if validator(word) {
-                return Some(Annotation {
-                    start_byte: 0,
-                    end_byte: word.len(),
-                    annotation_type,
-                });
-            }
+        if let Some(word) = string.split_word_bounds().next()
+            && validator(word)
+        {
+            return Some(Annotation {
+                start_byte: 0,
+                end_byte: word.len(),
+                annotation_type,
+            });
         }
 
         None
@@ -234,14 +256,14 @@ impl RustSyntaxHighlighter {
 
     fn annotate_lifetime(string: &str) -> Option<Annotation> {
         let mut iter = string.split_word_bound_indices().peekable();
-        if let Some((_, "\'")) = iter.next() {
-            if let Some((idx, next_word)) = iter.next() {
-                return Some(Annotation {
-                    annotation_type: AnnotationType::Lifetime,
-                    start_byte: 0,
-                    end_byte: idx.saturating_add(next_word.len()), //Include the closing quote in the annotation
-                });
-            }
+        if let Some((_, "\'")) = iter.next()
+            && let Some((idx, next_word)) = iter.next()
+        {
+            return Some(Annotation {
+                annotation_type: AnnotationType::Lifetime,
+                start_byte: 0,
+                end_byte: idx.saturating_add(next_word.len()), //Include the closing quote in the annotation
+            });
         }
         None
     }
@@ -269,18 +291,18 @@ impl RustSyntaxHighlighter {
                 }
             } else if self.ml_comment_balance == 0 {
                 return None; // We saw no opener, and we are not currently in a ML comment, returning None
-            } else if char == '*' {
-                if let Some((idx, '/')) = chars.peek() {
-                    self.ml_comment_balance = self.ml_comment_balance.saturating_sub(1);
-                    if self.ml_comment_balance == 0 {
-                        return Some(Annotation {
-                            annotation_type: AnnotationType::Comment,
-                            start_byte: 0,
-                            end_byte: idx.saturating_add(1),
-                        });
-                    }
-                    chars.next();
+            } else if char == '*'
+                && let Some((idx, '/')) = chars.peek()
+            {
+                self.ml_comment_balance = self.ml_comment_balance.saturating_sub(1);
+                if self.ml_comment_balance == 0 {
+                    return Some(Annotation {
+                        annotation_type: AnnotationType::Comment,
+                        start_byte: 0,
+                        end_byte: idx.saturating_add(1),
+                    });
                 }
+                chars.next();
             }
         }
         (self.ml_comment_balance > 0).then_some(Annotation {
@@ -333,10 +355,12 @@ impl RustSyntaxHighlighter {
     }
 
     fn annotate_remainder(&mut self, remainder: &str) -> Option<Annotation> {
+        // Characters go before lifetimes, otherwise `'a'` would be taken for the lifetime `'a`
         self.annotate_multi_line_comment(remainder)
             .or_else(|| Self::annotate_single_line_comment(remainder))
-            .or_else(|| Self::annotate_lifetime(remainder))
+            .or_else(|| self.annotate_string(remainder))
             .or_else(|| Self::annotate_char(remainder))
+            .or_else(|| Self::annotate_lifetime(remainder))
             .or_else(|| Self::annotate_number(remainder))
             .or_else(|| Self::annotate_type(remainder))
             .or_else(|| Self::annotate_known_value(remainder))
@@ -346,6 +370,11 @@ impl RustSyntaxHighlighter {
 
 impl SyntaxHighlighter for RustSyntaxHighlighter {
     fn highlight(&mut self, idx: LineIdx, line: &Line) {
+        if idx > self.highlights.len() {
+            return;
+        }
+        self.restart_at(idx);
+
         let mut result = Vec::new();
 
         let mut iterator = line.split_word_bound_indices().peekable();
@@ -373,12 +402,16 @@ impl SyntaxHighlighter for RustSyntaxHighlighter {
                     }
                     iterator.next();
                 }
-            };
+            }
         }
-        self.highlights.insert(idx, result);
+        self.highlights.push(result);
+        self.end_states.push(LineState {
+            ml_comment_balance: self.ml_comment_balance,
+            in_ml_string: self.in_ml_string,
+        });
     }
 
     fn get_annotations(&self, idx: LineIdx) -> Option<&Vec<Annotation>> {
-        self.highlights.get(&idx)
+        self.highlights.get(idx)
     }
 }
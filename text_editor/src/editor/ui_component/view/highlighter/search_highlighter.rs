@@ -2,74 +2,57 @@ use std::collections::HashMap;
 
 use crate::editor::{
     annotated_string::{annotation::Annotation, annotation_type::AnnotationType},
-    line::Line,
+    line::{Line, LineIdx},
+    search_pattern::SearchPattern,
     ui_component::view::location::Location,
 };
 
 use super::syntax_highlihter::SyntaxHighlighter;
 
 pub struct SearchHighlighter {
-    matched_word: Option<String>,
+    pattern: Option<SearchPattern>,
     selected_match: Option<Location>,
     highlights: HashMap<usize, Vec<Annotation>>,
 }
 
 impl SearchHighlighter {
-    pub fn new(matched_word: Option<String>, selected_match: Option<Location>) -> Self {
+    pub fn new(pattern: Option<SearchPattern>, selected_match: Option<Location>) -> Self {
         SearchHighlighter {
-            matched_word,
+            pattern,
             selected_match,
             highlights: HashMap::new(),
         }
     }
 
-    fn highlight_matched_words(&self, line: &Line, result: &mut Vec<Annotation>) {
-        if let Some(matched_word) = &self.matched_word {
-            if matched_word.is_empty() {
-                return;
-            }
-            line.find_all(&matched_word, 0..line.len())
-                .iter()
-                .for_each(|(start, _)| {
-                    result.push(Annotation {
-                        annotation_type: AnnotationType::Match,
-                        start_byte: *start,
-                        end_byte: start.saturating_add(matched_word.len()),
-                    });
-                });
-        }
-    }
+    fn highlight_matches(&self, idx: LineIdx, line: &Line, result: &mut Vec<Annotation>) {
+        let Some(pattern) = &self.pattern else {
+            return;
+        };
+
+        for (bytes, graphemes) in line.find_all(pattern) {
+            let is_selected = self.selected_match.is_some_and(|selected| {
+                selected.line_idx == idx && selected.grapheme_idx == graphemes.start
+            });
 
-    fn highlight_selected_match(&self, result: &mut Vec<Annotation>) {
-        if let Some(selected_match) = self.selected_match {
-            if let Some(matched_word) = &self.matched_word {
-                if matched_word.is_empty() {
-                    return;
-                }
-                let start = selected_match.grapheme_idx;
-                result.push(Annotation {
-                    annotation_type: AnnotationType::SelectedMatch,
-                    start_byte: start,
-                    end_byte: start.saturating_add(matched_word.len()),
-                });
-            }
+            let annotation_type = if is_selected {
+                AnnotationType::SelectedMatch
+            } else {
+                AnnotationType::Match
+            };
+
+            result.push(Annotation::new(bytes.start, bytes.end, annotation_type));
         }
     }
 }
 
 impl SyntaxHighlighter for SearchHighlighter {
-    fn highlight(&mut self, idx: crate::editor::line::LineIdx, line: &crate::editor::line::Line) {
+    fn highlight(&mut self, idx: LineIdx, line: &Line) {
         let mut result = Vec::new();
-        self.highlight_matched_words(line, &mut result);
-        if let Some(selected_match) = self.selected_match {
-            if selected_match.line_idx == idx {
-                self.highlight_selected_match(&mut result);
-            }
-        }
+        self.highlight_matches(idx, line, &mut result);
         self.highlights.insert(idx, result);
     }
 
-    fn get_annotations(&self, idx: crate::editor::line::LineIdx) -> Option<&Vec<Annotation>> {
+    fn get_annotations(&self, idx: LineIdx) -> Option<&Vec<Annotation>> {
         self.highlights.get(&idx)
     }
 }
@@ -0,0 +1,74 @@
+use std::collections::HashMap;
+use std::ops::Range;
+
+use crate::editor::{
+    annotated_string::{annotation::Annotation, annotation_type::AnnotationType},
+    line::{Line, LineIdx},
+    ui_component::view::location::Location,
+};
+
+use super::syntax_highlihter::SyntaxHighlighter;
+
+pub struct SelectionHighlighter {
+    selection: Range<Location>,
+    highlights: HashMap<LineIdx, Vec<Annotation>>,
+}
+
+impl SelectionHighlighter {
+    pub fn new(selection: Range<Location>) -> Self {
+        SelectionHighlighter {
+            selection,
+            highlights: HashMap::new(),
+        }
+    }
+
+    fn selected_bytes(&self, idx: LineIdx, line: &Line) -> Option<Range<usize>> {
+        let Range { start, end } = self.selection;
+
+        if idx < start.line_idx || idx > end.line_idx {
+            return None;
+        }
+
+        let start_byte = if idx == start.line_idx {
+            Self::byte_idx(line, start.grapheme_idx)
+        } else {
+            0
+        };
+
+        let end_byte = if idx == end.line_idx {
+            Self::byte_idx(line, end.grapheme_idx)
+        } else {
+            line.len()
+        };
+
+        (start_byte < end_byte).then_some(start_byte..end_byte)
+    }
+
+    fn byte_idx(line: &Line, grapheme_idx: usize) -> usize {
+        if grapheme_idx >= line.grapheme_count() {
+            line.len()
+        } else {
+            line.grapheme_idx_to_byte_idx(grapheme_idx)
+        }
+    }
+}
+
+impl SyntaxHighlighter for SelectionHighlighter {
+    fn highlight(&mut self, idx: LineIdx, line: &Line) {
+        let mut result = Vec::new();
+
+        if let Some(bytes) = self.selected_bytes(idx, line) {
+            result.push(Annotation::new(
+                bytes.start,
+                bytes.end,
+                AnnotationType::Selection,
+            ));
+        }
+
+        self.highlights.insert(idx, result);
+    }
+
+    fn get_annotations(&self, idx: LineIdx) -> Option<&Vec<Annotation>> {
+        self.highlights.get(&idx)
+    }
+}
@@ -0,0 +1,313 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::OnceLock;
+
+use serde::{Deserialize, Deserializer, de::Error};
+
+use crate::editor::{
+    annotated_string::annotation_type::AnnotationType, config, paths,
+    ui_component::view::indentation::Indentation,
+};
+
+const SYNTAX_DIR: &str = "syntax";
+
+/// Every `syntax/*.toml` of the crate as file name and content, embedded by the build script
+const BUILT_IN: &[(&str, &str)] = include!(concat!(env!("OUT_DIR"), "/built_in_syntax.rs"));
+
+/// Definitions with the problems found while loading them, read once and kept for the whole session
+#[derive(Default)]
+struct Definitions {
+    list: Vec<SyntaxDefinition>,
+    errors: Vec<String>,
+}
+
+/// Declarative description of a language used by `DefinitionHighlighter`
+#[derive(Deserialize, Default)]
+#[serde(default, deny_unknown_fields)]
+pub struct SyntaxDefinition {
+    pub name: String,
+    /// File extensions without the dot, the first definition listing an extension is used
+    pub extensions: Vec<String>,
+    /// Used unless the config sets one for the file type
+    pub indentation: Option<Indentation>,
+    pub keywords: Vec<String>,
+    pub types: Vec<String>,
+    pub known_values: Vec<String>,
+    #[serde(deserialize_with = "all_non_empty")]
+    pub line_comments: Vec<String>,
+    pub block_comments: Vec<BlockComment>,
+    pub nested_comments: bool,
+    /// Checked in order, so longer delimiters (`"""`) have to come before shorter ones (`"`)
+    pub strings: Vec<StringRule>,
+    /// Lines starting with prefix (after indentation) are annotated as a whole
+    pub line_prefixes: Vec<LinePrefix>,
+    /// Without this section numbers are not highlighted
+    pub numbers: Option<NumberRules>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct BlockComment {
+    #[serde(deserialize_with = "non_empty")]
+    pub start: String,
+    #[serde(deserialize_with = "non_empty")]
+    pub end: String,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct StringRule {
+    #[serde(deserialize_with = "non_empty")]
+    pub start: String,
+    /// Same as start when missing
+    #[serde(default, deserialize_with = "optional_non_empty")]
+    pub end: Option<String>,
+    #[serde(default, deserialize_with = "optional_non_empty")]
+    pub escape: Option<String>,
+    #[serde(default)]
+    pub multiline: bool,
+    #[serde(default = "StringRule::default_annotation")]
+    pub annotation: AnnotationType,
+}
+
+impl StringRule {
+    fn default_annotation() -> AnnotationType {
+        AnnotationType::String
+    }
+
+    pub fn end(&self) -> &str {
+        self.end.as_deref().unwrap_or(&self.start)
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct LinePrefix {
+    #[serde(deserialize_with = "non_empty")]
+    pub prefix: String,
+    pub annotation: AnnotationType,
+}
+
+#[derive(Deserialize, Default)]
+#[serde(default, deny_unknown_fields)]
+pub struct NumberRules {
+    /// Radix is taken from the last letter of the prefix, e.g. `0x` is hexadecimal
+    #[serde(deserialize_with = "all_non_empty")]
+    pub prefixes: Vec<String>,
+    /// Allows `_` as digit separator
+    pub underscores: bool,
+}
+
+impl SyntaxDefinition {
+    pub fn parse(content: &str) -> Result<Self, toml::de::Error> {
+        toml::from_str(content)
+    }
+
+    pub fn get(idx: usize) -> Option<&'static SyntaxDefinition> {
+        Self::definitions().list.get(idx)
+    }
+
+    /// Index of the definition used for files with given extension
+    pub fn for_extension(extension: &str) -> Option<usize> {
+        Self::definitions()
+            .list
+            .iter()
+            .position(|definition| definition.extensions.iter().any(|known| known == extension))
+    }
+
+    /// Index of the definition with given name, case is ignored so the config can use `python` for `Python`
+    pub fn for_name(name: &str) -> Option<usize> {
+        Self::definitions()
+            .list
+            .iter()
+            .position(|definition| definition.name.eq_ignore_ascii_case(name))
+    }
+
+    /// Definitions which could not be read or parsed, described as `path:line: message`
+    pub fn load_errors() -> &'static [String] {
+        &Self::definitions().errors
+    }
+
+    fn definitions() -> &'static Definitions {
+        static DEFINITIONS: OnceLock<Definitions> = OnceLock::new();
+
+        DEFINITIONS.get_or_init(|| {
+            let user_dir = paths::config_dir().map(|dir| dir.join(SYNTAX_DIR));
+            Self::load_all(user_dir)
+        })
+    }
+
+    /// Every `*.toml` of the user directory is loaded next to the built-in ones,
+    /// one with the name of a built-in definition replaces it unless it is broken
+    fn load_all(user_dir: Option<PathBuf>) -> Definitions {
+        let mut definitions = Definitions::default();
+        let mut by_file_name: BTreeMap<String, SyntaxDefinition> = BTreeMap::new();
+
+        for (file_name, content) in BUILT_IN {
+            match Self::parse(content) {
+                Ok(definition) => {
+                    by_file_name.insert((*file_name).to_string(), definition);
+                }
+                Err(err) => definitions.errors.push(config::describe_toml_error(
+                    &PathBuf::from(SYNTAX_DIR).join(file_name),
+                    content,
+                    &err,
+                )),
+            }
+        }
+
+        let mut user_files: Vec<PathBuf> = user_dir
+            .and_then(|dir| fs::read_dir(dir).ok())
+            .into_iter()
+            .flatten()
+            .filter_map(|entry| Some(entry.ok()?.path()))
+            .filter(|path| {
+                path.extension()
+                    .is_some_and(|extension| extension == "toml")
+            })
+            .collect();
+        user_files.sort();
+
+        for path in user_files {
+            let file_name = path
+                .file_name()
+                .unwrap_or_default()
+                .to_string_lossy()
+                .into_owned();
+
+            let content = match fs::read_to_string(&path) {
+                Ok(content) => content,
+                Err(err) => {
+                    definitions
+                        .errors
+                        .push(format!("Could not read {}: {err}", path.display()));
+                    continue;
+                }
+            };
+
+            match Self::parse(&content) {
+                Ok(definition) => {
+                    by_file_name.insert(file_name, definition);
+                }
+                Err(err) => definitions
+                    .errors
+                    .push(config::describe_toml_error(&path, &content, &err)),
+            }
+        }
+
+        definitions.list = by_file_name.into_values().collect();
+        definitions
+    }
+}
+
+// ==== Validation ====
+// Empty delimiters match everywhere, the highlighter would not make any progress on a line
+
+fn non_empty<'de, D: Deserializer<'de>>(deserializer: D) -> Result<String, D::Error> {
+    let value = String::deserialize(deserializer)?;
+
+    if value.is_empty() {
+        Err(D::Error::custom("delimiter cannot be empty"))
+    } else {
+        Ok(value)
+    }
+}
+
+fn optional_non_empty<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Option<String>, D::Error> {
+    non_empty(deserializer).map(Some)
+}
+
+fn all_non_empty<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<String>, D::Error> {
+    let values = Vec::<String>::deserialize(deserializer)?;
+
+    if values.iter().any(String::is_empty) {
+        Err(D::Error::custom("delimiter cannot be empty"))
+    } else {
+        Ok(values)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn user_dir(test: &str, files: &[(&str, &str)]) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("editr-{test}-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).expect("temp dir can be created");
+        for (name, content) in files {
+            fs::write(dir.join(name), content).expect("definition can be written");
+        }
+        dir
+    }
+
+    #[test]
+    fn built_in_definitions_parse() {
+        for (file_name, content) in BUILT_IN {
+            let definition =
+                SyntaxDefinition::parse(content).unwrap_or_else(|err| panic!("{file_name}: {err}"));
+            assert!(
+                !definition.extensions.is_empty(),
+                "{file_name} has no extensions"
+            );
+        }
+    }
+
+    #[test]
+    fn empty_delimiters_are_rejected() {
+        for content in [
+            "line_comments = [\"\"]",
+            "block_comments = [{ start = \"/*\", end = \"\" }]",
+            "strings = [{ start = \"\" }]",
+            "strings = [{ start = \"'\", end = \"\" }]",
+            "line_prefixes = [{ prefix = \"\", annotation = \"comment\" }]",
+            "[numbers]\nprefixes = [\"\"]",
+        ] {
+            assert!(SyntaxDefinition::parse(content).is_err(), "{content}");
+        }
+    }
+
+    #[test]
+    fn user_definitions_add_languages_and_report_errors() {
+        let dir = user_dir(
+            "syntax",
+            &[
+                (
+                    "lua.toml",
+                    "name = \"Lua\"\nextensions = [\"lua\"]\nline_comments = [\"--\"]",
+                ),
+                (
+                    "python.toml",
+                    "name = \"Python\"\n\nstrings = [{ start = \"\" }]",
+                ),
+            ],
+        );
+
+        let definitions = SyntaxDefinition::load_all(Some(dir.clone()));
+        let find = |extension: &str| {
+            definitions
+                .list
+                .iter()
+                .find(|definition| definition.extensions.iter().any(|known| known == extension))
+                .map(|definition| definition.name.as_str())
+        };
+
+        assert_eq!(find("lua"), Some("Lua"));
+        assert_eq!(
+            find("py"),
+            Some("Python"),
+            "broken definition keeps the built-in one"
+        );
+        assert_eq!(definitions.errors.len(), 1);
+        assert!(
+            definitions.errors[0].contains("python.toml:3:"),
+            "{}",
+            definitions.errors[0]
+        );
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
@@ -3,6 +3,8 @@ use crate::editor::{
     line::{Line, LineIdx},
 };
 
+/// Highlighters carrying state between lines keep it per line, so highlighting a line again
+/// continues from the state left by the line above it and discards everything below it
 pub trait SyntaxHighlighter {
     fn highlight(&mut self, idx: LineIdx, line: &Line);
     fn get_annotations(&self, idx: LineIdx) -> Option<&Vec<Annotation>>;
@@ -0,0 +1,243 @@
+use super::file_format::FileFormat;
+use super::location::Location;
+
+/// Single reversible change made to the buffer
+#[derive(Clone)]
+pub enum Operation {
+    /// Text (possibly containing newlines) inserted at location
+    Insert { at: Location, text: String },
+    /// Text (possibly containing newlines) removed starting at location
+    Remove { at: Location, text: String },
+    /// Empty line appended at the end of the buffer
+    PushLine,
+    /// Last (empty) line removed from the buffer
+    PopLine,
+    /// Line ending, trailing newline or BOM changed
+    SetFormat { from: FileFormat, to: FileFormat },
+}
+
+impl Operation {
+    pub fn inverse(&self) -> Self {
+        match self {
+            Operation::Insert { at, text } => Operation::Remove {
+                at: *at,
+                text: text.clone(),
+            },
+            Operation::Remove { at, text } => Operation::Insert {
+                at: *at,
+                text: text.clone(),
+            },
+            Operation::PushLine => Operation::PopLine,
+            Operation::PopLine => Operation::PushLine,
+            Operation::SetFormat { from, to } => Operation::SetFormat {
+                from: *to,
+                to: *from,
+            },
+        }
+    }
+
+    fn location(&self) -> Option<Location> {
+        match self {
+            Operation::Insert { at, .. } | Operation::Remove { at, .. } => Some(*at),
+            Operation::PushLine | Operation::PopLine | Operation::SetFormat { .. } => None,
+        }
+    }
+}
+
+/// Decides which consecutive edits get merged into one undo step
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum GroupKind {
+    Insert,
+    Delete,
+    Other,
+}
+
+impl GroupKind {
+    fn can_merge(self) -> bool {
+        !matches!(self, GroupKind::Other)
+    }
+}
+
+pub struct Group {
+    id: usize,
+    kind: GroupKind,
+    pub operations: Vec<Operation>,
+    pub caret_before: Location,
+    pub caret_after: Location,
+}
+
+/// Undo/redo log of buffer edits
+///
+/// Every group gets a unique id, the id of the group on top of the undo stack identifies
+/// the current state of the buffer, which is how modification status is tracked across undo/redo
+#[derive(Default)]
+pub struct History {
+    undo_stack: Vec<Group>,
+    redo_stack: Vec<Group>,
+    is_open: bool,
+    /// Between `begin` and `end`
+    in_edit: bool,
+    next_id: usize,
+    saved_id: usize,
+}
+
+impl History {
+    // ======================================== PUBLIC INTERFACE ==================================================
+
+    /// Starts an edit, continues the last group if it is still open and of the same kind
+    pub fn begin(&mut self, caret: Location, kind: GroupKind) {
+        let continues_last = self.is_open
+            && kind.can_merge()
+            && self
+                .undo_stack
+                .last()
+                .is_some_and(|group| group.kind == kind);
+
+        self.in_edit = true;
+
+        if continues_last {
+            return;
+        }
+
+        self.next_id = self.next_id.saturating_add(1);
+        self.undo_stack.push(Group {
+            id: self.next_id,
+            kind,
+            operations: Vec::new(),
+            caret_before: caret,
+            caret_after: caret,
+        });
+        self.is_open = true;
+    }
+
+    /// Outside of `begin`/`end` the operation gets a group of its own instead of joining a finished one
+    pub fn record(&mut self, operation: Operation) {
+        let stray = !self.in_edit;
+        if stray {
+            self.seal();
+            self.begin(operation.location().unwrap_or_default(), GroupKind::Other);
+        }
+
+        if let Some(group) = self.undo_stack.last_mut() {
+            group.operations.push(operation);
+            self.redo_stack.clear();
+        }
+
+        if stray {
+            self.in_edit = false;
+            self.seal();
+        }
+    }
+
+    /// Finishes an edit, edits that changed nothing are dropped from the log
+    pub fn end(&mut self, caret: Location) {
+        self.in_edit = false;
+
+        if let Some(group) = self.undo_stack.last_mut() {
+            if group.operations.is_empty() {
+                self.undo_stack.pop();
+                self.is_open = false;
+            } else {
+                group.caret_after = caret;
+            }
+        }
+    }
+
+    /// Prevents next edit from being merged with the previous one
+    pub fn seal(&mut self) {
+        self.is_open = false;
+    }
+
+    pub fn pop_undo(&mut self) -> Option<&Group> {
+        self.seal();
+        let group = self.undo_stack.pop()?;
+        self.redo_stack.push(group);
+        self.redo_stack.last()
+    }
+
+    pub fn pop_redo(&mut self) -> Option<&Group> {
+        self.seal();
+        let group = self.redo_stack.pop()?;
+        self.undo_stack.push(group);
+        self.undo_stack.last()
+    }
+
+    pub fn mark_saved(&mut self) {
+        self.seal();
+        self.saved_id = self.current_id();
+    }
+
+    pub fn is_modified(&self) -> bool {
+        self.current_id() != self.saved_id
+    }
+
+    pub fn clear(&mut self) {
+        *self = History::default();
+    }
+
+    // ======================================== HELPER METHODS =======================================================
+
+    fn current_id(&self) -> usize {
+        self.undo_stack.last().map_or(0, |group| group.id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn insert(grapheme_idx: usize, text: &str) -> Operation {
+        Operation::Insert {
+            at: Location {
+                line_idx: 0,
+                grapheme_idx,
+            },
+            text: text.to_string(),
+        }
+    }
+
+    #[test]
+    fn operation_outside_an_edit_is_undone_on_its_own() {
+        let mut history = History::default();
+        let caret = Location::default();
+
+        history.begin(caret, GroupKind::Insert);
+        history.record(insert(0, "a"));
+        history.end(caret);
+
+        history.record(insert(1, "b"));
+
+        let group = history.pop_undo().expect("stray operation has a group");
+        assert_eq!(group.operations.len(), 1);
+        assert_eq!(group.caret_before.grapheme_idx, 1);
+
+        let group = history.pop_undo().expect("first edit is kept apart");
+        assert_eq!(group.operations.len(), 1);
+        assert!(history.pop_undo().is_none());
+    }
+
+    #[test]
+    fn edits_of_same_kind_are_merged_until_sealed() {
+        let mut history = History::default();
+        let caret = Location::default();
+
+        for idx in 0..3 {
+            history.begin(caret, GroupKind::Insert);
+            history.record(insert(idx, "a"));
+            history.end(caret);
+        }
+        history.seal();
+        history.begin(caret, GroupKind::Insert);
+        history.record(insert(3, "a"));
+        history.end(caret);
+
+        assert_eq!(
+            history.pop_undo().map(|group| group.operations.len()),
+            Some(1)
+        );
+        assert_eq!(
+            history.pop_undo().map(|group| group.operations.len()),
+            Some(3)
+        );
+    }
+}
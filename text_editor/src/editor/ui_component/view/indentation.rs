@@ -0,0 +1,138 @@
+use std::collections::HashMap;
+
+use serde::Deserialize;
+
+use super::highlighter::file_type::FileType;
+
+/// Columns a tab is assumed to take when outdenting space indented lines
+const TAB_WIDTH: usize = 4;
+
+/// How a single level of indentation is written, configured as `"tabs"` or a number of spaces
+#[derive(Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(try_from = "toml::Value")]
+pub enum Indentation {
+    Tabs,
+    Spaces(usize),
+}
+
+impl Indentation {
+    /// Files without a known type have no built-in indentation
+    fn built_in(file_type: Option<FileType>) -> Option<Self> {
+        match file_type? {
+            FileType::Rust => Some(Indentation::Spaces(4)),
+            file_type => file_type.syntax_definition()?.indentation,
+        }
+    }
+
+    /// One level of indentation
+    pub fn unit(self) -> String {
+        match self {
+            Indentation::Tabs => String::from("\t"),
+            Indentation::Spaces(width) => " ".repeat(width),
+        }
+    }
+
+    /// What Tab inserts at given column, spaces reach the next multiple of the indentation width
+    pub fn tab_text(self, column: usize) -> String {
+        match self {
+            Indentation::Tabs => String::from("\t"),
+            Indentation::Spaces(width) => {
+                " ".repeat(width.saturating_sub(column.checked_rem(width).unwrap_or(0)))
+            }
+        }
+    }
+
+    /// Graphemes at the start of the indentation making up its first level
+    pub fn outdent_len(self, indentation: &str) -> usize {
+        if indentation.starts_with('\t') {
+            return 1;
+        }
+
+        let width = match self {
+            Indentation::Tabs => TAB_WIDTH,
+            Indentation::Spaces(width) => width,
+        };
+
+        indentation
+            .chars()
+            .take(width)
+            .take_while(|chr| *chr == ' ')
+            .count()
+    }
+}
+
+impl TryFrom<toml::Value> for Indentation {
+    type Error = String;
+
+    fn try_from(value: toml::Value) -> Result<Self, Self::Error> {
+        match &value {
+            toml::Value::String(name) if name == "tabs" => Ok(Indentation::Tabs),
+            toml::Value::Integer(width @ 1..=16) => usize::try_from(*width)
+                .map(Indentation::Spaces)
+                .map_err(|err| err.to_string()),
+            _ => Err(format!(
+                "Invalid indentation {value}, expected \"tabs\" or a number of spaces from 1 to 16"
+            )),
+        }
+    }
+}
+
+/// Indentation configured by the user, per file type or as a default for everything else
+#[derive(Clone, Default)]
+pub struct IndentationSettings {
+    /// Set for a single view from the command line
+    pub local: Option<Indentation>,
+    pub per_file_type: HashMap<FileType, Indentation>,
+    /// Used for file types without own setting or built-in indentation
+    pub default: Option<Indentation>,
+}
+
+impl IndentationSettings {
+    pub fn for_file_type(&self, file_type: Option<FileType>) -> Indentation {
+        self.local
+            .or_else(|| file_type.and_then(|file_type| self.per_file_type.get(&file_type).copied()))
+            .or_else(|| Indentation::built_in(file_type))
+            .or(self.default)
+            .unwrap_or(Indentation::Tabs)
+    }
+}
+
+/// Spaces and tabs at the start of the line
+pub fn leading_whitespace(line: &str) -> &str {
+    let end = line
+        .find(|chr: char| chr != ' ' && chr != '\t')
+        .unwrap_or(line.len());
+
+    &line[..end]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tab_text_reaches_the_next_level() {
+        let four = Indentation::Spaces(4);
+
+        assert_eq!(four.tab_text(0), "    ");
+        assert_eq!(four.tab_text(1), "   ");
+        assert_eq!(four.tab_text(3), " ");
+        assert_eq!(four.tab_text(4), "    ");
+        assert_eq!(Indentation::Spaces(2).tab_text(7), " ");
+        assert_eq!(Indentation::Tabs.tab_text(5), "\t");
+    }
+
+    #[test]
+    fn outdent_removes_at_most_one_level() {
+        let four = Indentation::Spaces(4);
+
+        assert_eq!(four.outdent_len("        "), 4);
+        assert_eq!(four.outdent_len("  "), 2);
+        assert_eq!(four.outdent_len(""), 0);
+        assert_eq!(four.outdent_len("\t    "), 1);
+        assert_eq!(four.outdent_len("  \t"), 2);
+        // Space indented lines in a tab indented file lose a tab width of spaces
+        assert_eq!(Indentation::Tabs.outdent_len("      "), TAB_WIDTH);
+        assert_eq!(Indentation::Tabs.outdent_len("\t\t"), 1);
+    }
+}
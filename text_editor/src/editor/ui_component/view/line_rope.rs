@@ -0,0 +1,325 @@
+use std::ops::Range;
+
+use crate::editor::line::{Line, LineIdx};
+
+/// Chunks are split once they grow past this many lines
+const MAX_CHUNK_LEN: usize = 1024;
+
+/// Sequence of lines stored as a shallow rope of bounded chunks
+///
+/// Inserting or removing a line only moves lines within one chunk and shifts chunk offsets,
+/// instead of moving every line below it like a single `Vec` would
+pub struct LineRope {
+    chunks: Vec<Vec<Line>>,
+    /// Index of the first line of every chunk, used to find chunks by binary search
+    starts: Vec<LineIdx>,
+    len: usize,
+}
+
+impl Default for LineRope {
+    fn default() -> Self {
+        LineRope {
+            chunks: vec![Vec::new()],
+            starts: vec![0],
+            len: 0,
+        }
+    }
+}
+
+impl LineRope {
+    // ======================================== PUBLIC INTERFACE ==================================================
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn get(&self, idx: LineIdx) -> Option<&Line> {
+        let (chunk, offset) = self.locate(idx)?;
+        self.chunks.get(chunk)?.get(offset)
+    }
+
+    pub fn get_mut(&mut self, idx: LineIdx) -> Option<&mut Line> {
+        let (chunk, offset) = self.locate(idx)?;
+        self.chunks.get_mut(chunk)?.get_mut(offset)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Line> {
+        self.chunks.iter().flatten()
+    }
+
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = &mut Line> {
+        self.chunks.iter_mut().flatten()
+    }
+
+    pub fn clear(&mut self) {
+        *self = LineRope::default();
+    }
+
+    pub fn push(&mut self, line: Line) {
+        if let Some(last) = self.chunks.last_mut() {
+            last.push(line);
+            self.len = self.len.saturating_add(1);
+        }
+
+        if self
+            .chunks
+            .last()
+            .is_some_and(|last| last.len() > MAX_CHUNK_LEN)
+        {
+            self.split_chunk(self.chunks.len().saturating_sub(1));
+        }
+    }
+
+    pub fn pop(&mut self) -> Option<Line> {
+        let idx = self.len.checked_sub(1)?;
+        self.remove_range(idx..self.len).pop()
+    }
+
+    /// Inserts lines before given index, index equal to length appends them
+    pub fn insert_many(&mut self, idx: LineIdx, lines: Vec<Line>) {
+        if idx > self.len || lines.is_empty() {
+            return;
+        }
+
+        let (chunk, offset) = self.locate_insertion(idx);
+        let count = lines.len();
+
+        if self.chunks[chunk].len().saturating_add(count) <= MAX_CHUNK_LEN {
+            self.chunks[chunk].splice(offset..offset, lines);
+            self.len = self.len.saturating_add(count);
+            self.rebuild_starts();
+            return;
+        }
+
+        let tail = self.chunks[chunk].split_off(offset);
+
+        let mut new_chunks = Vec::new();
+        let mut lines = lines.into_iter().peekable();
+        while lines.peek().is_some() {
+            new_chunks.push(lines.by_ref().take(MAX_CHUNK_LEN).collect::<Vec<Line>>());
+        }
+
+        let insert_at = chunk.saturating_add(1);
+        self.chunks.splice(insert_at..insert_at, new_chunks);
+
+        let tail_idx = insert_at.saturating_add(count.div_ceil(MAX_CHUNK_LEN));
+        self.chunks.insert(tail_idx, tail);
+
+        self.len = self.len.saturating_add(count);
+        self.normalize();
+    }
+
+    /// Removes lines in range and returns them, range is clamped to existing lines
+    pub fn remove_range(&mut self, range: Range<LineIdx>) -> Vec<Line> {
+        let end = range.end.min(self.len);
+        let start = range.start.min(end);
+        let mut removed = Vec::new();
+
+        if start == end {
+            return removed;
+        }
+
+        for (chunk, chunk_start) in self.chunks.iter_mut().zip(self.starts.iter().copied()) {
+            let chunk_end = chunk_start.saturating_add(chunk.len());
+            if chunk_end <= start || chunk_start >= end {
+                continue;
+            }
+
+            let from = start.saturating_sub(chunk_start);
+            let to = end.min(chunk_end).saturating_sub(chunk_start);
+            removed.extend(chunk.drain(from..to));
+        }
+
+        self.len = self.len.saturating_sub(removed.len());
+        self.normalize();
+
+        removed
+    }
+
+    // ======================================== HELPER METHODS ==================================================
+
+    /// Chunk and offset in it of an existing line
+    fn locate(&self, idx: LineIdx) -> Option<(usize, usize)> {
+        if idx >= self.len {
+            return None;
+        }
+
+        let chunk = self
+            .starts
+            .partition_point(|&start| start <= idx)
+            .saturating_sub(1);
+        let offset = idx.saturating_sub(*self.starts.get(chunk)?);
+
+        Some((chunk, offset))
+    }
+
+    /// Like `locate`, but the position right after the last line is valid too
+    fn locate_insertion(&self, idx: LineIdx) -> (usize, usize) {
+        self.locate(idx).unwrap_or_else(|| {
+            let last = self.chunks.len().saturating_sub(1);
+            (last, self.chunks.get(last).map_or(0, Vec::len))
+        })
+    }
+
+    fn split_chunk(&mut self, chunk: usize) {
+        let half = self.chunks[chunk].len() / 2;
+        let second_half = self.chunks[chunk].split_off(half);
+        self.chunks.insert(chunk.saturating_add(1), second_half);
+        self.rebuild_starts();
+    }
+
+    /// Merges neighbouring chunks that fit into one and recomputes chunk offsets
+    fn normalize(&mut self) {
+        let mut chunks: Vec<Vec<Line>> = Vec::with_capacity(self.chunks.len());
+
+        for chunk in self.chunks.drain(..) {
+            match chunks.last_mut() {
+                Some(last) if last.len().saturating_add(chunk.len()) <= MAX_CHUNK_LEN => {
+                    last.extend(chunk);
+                }
+                _ => chunks.push(chunk),
+            }
+        }
+
+        if chunks.is_empty() {
+            chunks.push(Vec::new());
+        }
+
+        self.chunks = chunks;
+        self.rebuild_starts();
+    }
+
+    fn rebuild_starts(&mut self) {
+        self.starts.clear();
+
+        let mut start: LineIdx = 0;
+        for chunk in &self.chunks {
+            self.starts.push(start);
+            start = start.saturating_add(chunk.len());
+        }
+    }
+}
+
+impl FromIterator<Line> for LineRope {
+    fn from_iter<T: IntoIterator<Item = Line>>(iter: T) -> Self {
+        let mut rope = LineRope::default();
+        rope.insert_many(0, iter.into_iter().collect());
+        rope
+    }
+}
+
+impl IntoIterator for LineRope {
+    type Item = Line;
+    type IntoIter = std::iter::Flatten<std::vec::IntoIter<Vec<Line>>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.chunks.into_iter().flatten()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lines(range: Range<usize>) -> Vec<String> {
+        range.map(|idx| format!("line {idx}")).collect()
+    }
+
+    fn rope_of(lines: &[String]) -> LineRope {
+        lines.iter().map(|line| Line::from(line.as_str())).collect()
+    }
+
+    /// Content, length, lookups and chunk bounds agree with the plain list of lines
+    fn assert_rope(rope: &LineRope, expected: &[String]) {
+        assert_eq!(rope.len(), expected.len());
+        assert!(
+            rope.iter()
+                .map(|line| &**line)
+                .eq(expected.iter().map(String::as_str))
+        );
+
+        for (idx, line) in expected.iter().enumerate() {
+            assert_eq!(
+                rope.get(idx).map(|line| &**line),
+                Some(line.as_str()),
+                "line {idx}"
+            );
+        }
+        assert!(rope.get(expected.len()).is_none());
+
+        assert!(rope.chunks.iter().all(|chunk| chunk.len() <= MAX_CHUNK_LEN));
+        assert_eq!(rope.starts.len(), rope.chunks.len());
+    }
+
+    #[test]
+    fn pushing_past_a_full_chunk_splits_it() {
+        let mut rope = LineRope::default();
+        let expected = lines(0..MAX_CHUNK_LEN.saturating_add(1));
+
+        for line in &expected {
+            rope.push(Line::from(line.as_str()));
+        }
+
+        assert_eq!(rope.chunks.len(), 2);
+        assert_rope(&rope, &expected);
+    }
+
+    #[test]
+    fn inserts_across_a_chunk_boundary_keep_the_order() {
+        let mut expected = lines(0..MAX_CHUNK_LEN.saturating_mul(2));
+        let mut rope = rope_of(&expected);
+        assert_eq!(rope.chunks.len(), 2);
+
+        for at in [
+            MAX_CHUNK_LEN.saturating_sub(1),
+            MAX_CHUNK_LEN,
+            MAX_CHUNK_LEN.saturating_add(1),
+        ] {
+            let inserted = vec![format!("inserted at {at}"), format!("after {at}")];
+            rope.insert_many(
+                at,
+                inserted
+                    .iter()
+                    .map(|line| Line::from(line.as_str()))
+                    .collect(),
+            );
+            expected.splice(at..at, inserted);
+            assert_rope(&rope, &expected);
+        }
+    }
+
+    #[test]
+    fn inserting_more_than_a_chunk_in_the_middle() {
+        let mut expected = lines(0..10);
+        let mut rope = rope_of(&expected);
+        let inserted = lines(100..MAX_CHUNK_LEN.saturating_mul(2).saturating_add(105));
+
+        rope.insert_many(5, rope_of(&inserted).into_iter().collect());
+        expected.splice(5..5, inserted);
+
+        assert_rope(&rope, &expected);
+    }
+
+    #[test]
+    fn removing_across_chunks_merges_what_is_left() {
+        let mut expected = lines(0..MAX_CHUNK_LEN.saturating_mul(3));
+        let mut rope = rope_of(&expected);
+        let range =
+            MAX_CHUNK_LEN.saturating_sub(2)..MAX_CHUNK_LEN.saturating_mul(2).saturating_add(2);
+
+        let removed = rope.remove_range(range.clone());
+        let expected_removed: Vec<String> = expected.drain(range).collect();
+
+        assert!(
+            removed
+                .iter()
+                .map(|line| &**line)
+                .eq(expected_removed.iter().map(String::as_str))
+        );
+        assert_rope(&rope, &expected);
+        assert_eq!(rope.chunks.len(), 2);
+
+        while rope.pop().is_some() {}
+        assert_rope(&rope, &[]);
+    }
+}
@@ -1,5 +1,49 @@
-#[derive(Clone, Copy, Default, Debug)]
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Copy, Default, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
 pub struct Location {
-    pub grapheme_idx: usize,
     pub line_idx: usize,
+    pub grapheme_idx: usize,
+}
+
+impl Location {
+    /// Parses `line[:column]` as written by compilers, both counted from 1
+    pub fn parse(text: &str) -> Option<Self> {
+        let (line, column) = match text.trim().split_once(':') {
+            Some((line, column)) => (line, Some(column)),
+            None => (text.trim(), None),
+        };
+
+        let line_idx = line.parse::<usize>().ok()?.checked_sub(1)?;
+        let grapheme_idx = match column {
+            Some(column) => column.parse::<usize>().ok()?.checked_sub(1)?,
+            None => 0,
+        };
+
+        Some(Location {
+            line_idx,
+            grapheme_idx,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn line_and_column_are_counted_from_one() {
+        let location = |line_idx, grapheme_idx| {
+            Some(Location {
+                line_idx,
+                grapheme_idx,
+            })
+        };
+
+        assert_eq!(Location::parse("1"), location(0, 0));
+        assert_eq!(Location::parse(" 12:7 "), location(11, 6));
+        for text in ["", "0", "5:", "3:0", "0:1", ":3", "1:2:3", "-1", "a"] {
+            assert_eq!(Location::parse(text), None, "{text}");
+        }
+    }
 }
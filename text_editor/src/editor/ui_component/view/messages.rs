@@ -1,3 +1,5 @@
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
+
 use crate::editor::size::Size;
 
 use super::buffer::Buffer;
@@ -5,7 +7,13 @@ use super::buffer::Buffer;
 pub struct Message;
 
 impl Message {
-    pub fn build_welcome_message(size: Size, name: &str, version: &str) -> Buffer {
+    /// Recent files are listed below the name, numbered by the Alt-digit key opening them
+    pub fn build_welcome_message(
+        size: Size,
+        name: &str,
+        version: &str,
+        recent_files: &[String],
+    ) -> Buffer {
         let mut buffer = Buffer::default();
 
         let length_of_terminal = size.columns;
@@ -24,7 +32,7 @@ impl Message {
         // Draw Name and version number
         let blank_space = (length_of_terminal - name.len() - version.len()).saturating_sub(3);
 
-        let (padding_left, padding_right) = if blank_space % 2 == 0 {
+        let (padding_left, padding_right) = if blank_space.is_multiple_of(2) {
             (&" ".repeat(blank_space / 2), &" ".repeat(blank_space / 2))
         } else {
             (
@@ -41,8 +49,19 @@ impl Message {
             buffer.push(&blank_line);
         }
 
-        // Draw sides
-        for _ in 0..5 {
+        // Draw sides, the recent files take the place of the blank lines they need
+        let mut sides: usize = 5;
+        if !recent_files.is_empty() {
+            buffer.push(&blank_line);
+            buffer.push(&Self::boxed("  Recent files:", length_of_terminal));
+            for (idx, file) in recent_files.iter().enumerate() {
+                let entry = format!("  Alt-{}  {file}", idx.saturating_add(1));
+                buffer.push(&Self::boxed(&entry, length_of_terminal));
+            }
+            sides = sides.saturating_sub(recent_files.len().saturating_add(2));
+        }
+
+        for _ in 0..sides {
             buffer.push(&blank_line);
         }
 
@@ -51,4 +70,37 @@ impl Message {
 
         buffer
     }
+
+    /// Text between the side bars, cut off when it does not fit
+    fn boxed(text: &str, width: usize) -> String {
+        let inner = width.saturating_sub(2);
+
+        let mut fitting = String::new();
+        let mut used: usize = 0;
+        for chr in text.chars() {
+            used = used.saturating_add(chr.width().unwrap_or(0));
+            if used > inner {
+                break;
+            }
+            fitting.push(chr);
+        }
+        let padding = " ".repeat(inner.saturating_sub(fitting.width()));
+
+        format!("|{fitting}{padding}|")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn boxed_text_keeps_the_border_in_place() {
+        assert_eq!(Message::boxed("ab", 6), "|ab  |");
+        assert_eq!(Message::boxed("abcdef", 6), "|abcd|");
+        // Wide characters take two columns and are not split
+        assert_eq!(Message::boxed("日本", 8), "|日本  |");
+        assert_eq!(Message::boxed("日本語", 7), "|日本 |");
+        assert_eq!(Message::boxed("日本語", 6).width(), 6);
+    }
 }
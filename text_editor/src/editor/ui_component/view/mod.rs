@@ -1,22 +1,43 @@
 mod buffer;
+pub mod file_format;
+pub mod file_io;
+pub mod gutter;
 pub mod highlighter;
+mod history;
+pub mod indentation;
+mod line_rope;
 pub mod location;
 mod messages;
 pub mod search_info;
 
-use std::cmp;
+use std::cell::RefCell;
+use std::cmp::{self, Ordering};
+use std::collections::HashMap;
+use std::ops::{Range, RangeInclusive};
+use std::rc::Rc;
 
 use buffer::Buffer;
+use file_format::FileFormat;
+use gutter::{Gutter, LineNumbers, Sign};
 use highlighter::{Highlighter, file_type::FileType};
+use history::GroupKind;
+use indentation::{Indentation, IndentationSettings, leading_whitespace};
 use location::Location;
 use messages::Message;
 use search_info::SearchInfo;
 
 use crate::editor::{
+    annotated_string::AnnotatedString,
     caret_position::CaretPosition,
-    command::{edit::Edit, movement::Move},
+    command::{
+        edit::Edit,
+        mouse::{Mouse, SCROLL_STEP},
+        movement::Move,
+        selection::Select,
+    },
     document_status::DocumentStatus,
-    line::Line,
+    line::{GraphemeIdx, Line, LineIdx},
+    search_pattern::SearchPattern,
     size::Size,
     terminal::Terminal,
 };
@@ -26,13 +47,34 @@ use super::UiComponent;
 const EDITOR_NAME: &str = "editr";
 const EDITOR_VERSION: &str = env!("CARGO_PKG_VERSION");
 
+/// User configurable defaults every view starts with
+#[derive(Clone, Default)]
+pub struct ViewSettings {
+    pub soft_wrap: bool,
+    pub line_numbers: LineNumbers,
+    pub indentation: IndentationSettings,
+}
+
 pub struct View {
-    buffer: Buffer,
+    /// Shared with every other view of the same file, edits made in one show up in all of them
+    buffer: Rc<RefCell<Buffer>>,
+    /// Buffer revision the highlighting and the screen were last brought up to date with
+    seen_revision: usize,
     needs_redraw: bool,
     size: Size,
     text_location: Location,
     scroll_offset: CaretPosition,
+    /// With soft wrap, visual row of the top line the view starts at, lines taller than the view need it
+    top_wrap_row: usize,
     search_info: Option<SearchInfo>,
+    selection_anchor: Option<Location>,
+    highlighter: Highlighter,
+    /// Long lines are broken into several visual rows instead of scrolling horizontally
+    soft_wrap: bool,
+    gutter: Gutter,
+    indentation_settings: IndentationSettings,
+    /// Offered on the welcome screen of a buffer without file
+    recent_files: Vec<String>,
 }
 
 impl View {
@@ -47,49 +89,273 @@ impl View {
         };
 
         View {
-            buffer: Buffer::default(),
+            buffer: Rc::default(),
+            seen_revision: 0,
             needs_redraw: true,
             size: margined_size,
             text_location: Location::default(),
             scroll_offset: CaretPosition::default(),
+            top_wrap_row: 0,
             search_info: Some(SearchInfo::default()),
+            selection_anchor: None,
+            highlighter: Highlighter::default(),
+            soft_wrap: false,
+            gutter: Gutter::default(),
+            indentation_settings: IndentationSettings::default(),
+            recent_files: Vec::new(),
+        }
+    }
+
+    /// Another view of the same buffer with its own caret, scroll position and settings
+    pub fn split(&self) -> Self {
+        View {
+            buffer: Rc::clone(&self.buffer),
+            seen_revision: 0,
+            needs_redraw: true,
+            size: self.size,
+            text_location: self.text_location,
+            scroll_offset: self.scroll_offset,
+            top_wrap_row: self.top_wrap_row,
+            search_info: None,
+            selection_anchor: None,
+            highlighter: Highlighter::default(),
+            soft_wrap: self.soft_wrap,
+            gutter: self.gutter.clone(),
+            indentation_settings: self.indentation_settings.clone(),
+            recent_files: self.recent_files.clone(),
         }
     }
 
-    pub fn handle_save(&mut self) -> Result<(), std::io::Error> {
-        self.save()?;
+    pub fn shares_buffer(&self, other: &View) -> bool {
+        Rc::ptr_eq(&self.buffer, &other.buffer)
+    }
+
+    /// Caret and selection may point past the end after the buffer was edited through another view
+    pub fn catch_up(&mut self) {
+        if let Some(anchor) = self.selection_anchor {
+            let caret = self.text_location;
+            self.text_location = anchor;
+            self.snap_to_valid_line();
+            self.snap_to_valid_grapheme();
+            self.selection_anchor = Some(self.text_location);
+            self.text_location = caret;
+        }
+
+        self.snap_to_valid_line();
+        self.snap_to_valid_grapheme();
+        self.scroll_text_location_into_view();
+    }
+
+    pub fn apply_settings(&mut self, settings: &ViewSettings) {
+        self.soft_wrap = settings.soft_wrap;
+        self.gutter.line_numbers = settings.line_numbers;
+        self.indentation_settings = settings.indentation.clone();
+        self.mark_redraw(true);
+    }
+
+    pub fn handle_save(&mut self, keep_backup: bool) -> Result<(), std::io::Error> {
+        self.save(keep_backup)?;
         Ok(())
     }
 
     pub fn is_file_given(&self) -> bool {
-        self.buffer.is_file_given()
+        self.buffer.borrow().is_file_given()
+    }
+
+    pub fn is_changed_on_disk(&self) -> bool {
+        self.buffer.borrow().is_changed_on_disk()
     }
 
-    pub fn handle_move_command(&mut self, move_command: Move) {
+    /// Caret stays at the same location, or as close to it as the new content allows
+    pub fn reload(&mut self) -> Result<(), std::io::Error> {
+        let location = self.text_location;
+
+        self.clear_selection();
+        self.buffer.borrow_mut().reload()?;
+        self.jump_to(location);
+
+        Ok(())
+    }
+
+    /// Returns false when the caret could not go any further, the empty line past the end counts as no further
+    pub fn handle_move_command(&mut self, move_command: Move) -> bool {
+        self.buffer.borrow_mut().break_edit_group();
+        self.clear_selection();
+
+        let before = self.text_location;
         self.move_text_location(move_command);
+
+        let past_end = self.text_location.line_idx >= self.buffer.borrow().get_number_of_lines();
+        move_command.is_jump() || (self.text_location != before && !past_end)
+    }
+
+    pub fn handle_select_command(&mut self, select_command: Select) {
+        self.buffer.borrow_mut().break_edit_group();
+
+        match select_command {
+            Select::Extend(move_command) => {
+                if self.selection_anchor.is_none() {
+                    self.selection_anchor = Some(self.text_location);
+                }
+                self.move_text_location(move_command);
+            }
+            Select::All => self.select_all(),
+        }
+
+        self.mark_redraw(true);
+    }
+
+    pub fn handle_mouse_command(&mut self, mouse_command: Mouse) {
+        self.buffer.borrow_mut().break_edit_group();
+
+        match mouse_command {
+            Mouse::Click(position) => {
+                if position.row < self.size.rows {
+                    self.clear_selection();
+                    self.jump_to(self.location_at(position));
+                }
+            }
+            Mouse::Drag(position) => {
+                if self.selection_anchor.is_none() {
+                    self.selection_anchor = Some(self.text_location);
+                }
+                self.jump_to(self.location_at(position));
+            }
+            Mouse::ScrollUp(_) => self.scroll_lines(SCROLL_STEP, true),
+            Mouse::ScrollDown(_) => self.scroll_lines(SCROLL_STEP, false),
+        }
+
+        self.mark_redraw(true);
     }
 
     pub fn handle_edit_command(&mut self, edit_command: Edit) {
+        let kind = match edit_command {
+            Edit::Undo => return self.undo(),
+            Edit::Redo => return self.redo(),
+            Edit::Input(chr) if !chr.is_whitespace() => GroupKind::Insert,
+            Edit::Delete | Edit::Backspace | Edit::DeleteWord | Edit::BackspaceWord => {
+                GroupKind::Delete
+            }
+            Edit::Input(_) | Edit::Tab | Edit::Outdent | Edit::Enter => GroupKind::Other,
+        };
+
+        // Tab indents every line of a selection spanning several lines, Shift+Tab always works on lines
+        if let Edit::Tab | Edit::Outdent = edit_command {
+            let selected_lines = self.selected_lines();
+
+            if selected_lines.is_some() || matches!(edit_command, Edit::Outdent) {
+                let line_idx = self.text_location.line_idx;
+                let lines = selected_lines.unwrap_or(line_idx..=line_idx);
+                let indent = matches!(edit_command, Edit::Tab);
+
+                self.buffer
+                    .borrow_mut()
+                    .begin_edit(self.text_location, GroupKind::Other);
+                self.change_indentation(lines, indent);
+                self.buffer.borrow_mut().end_edit(self.text_location);
+                return;
+            }
+        }
+
+        let kind = if self.selection().is_some() {
+            GroupKind::Other
+        } else {
+            kind
+        };
+
+        self.buffer
+            .borrow_mut()
+            .begin_edit(self.text_location, kind);
+
+        let deleted_selection = self.delete_selection();
+
         match edit_command {
+            Edit::Delete | Edit::Backspace | Edit::DeleteWord | Edit::BackspaceWord
+                if deleted_selection => {}
             Edit::Tab => self.tab(),
             Edit::Enter => self.enter(),
             Edit::Delete => self.delete_grapheme(),
             Edit::Backspace => self.backspace(),
-            Edit::Input(x) => self.add_to_buffer(x),
+            Edit::DeleteWord => self.delete_word(),
+            Edit::BackspaceWord => self.backspace_word(),
+            Edit::Input(x) => self.input(x),
+            Edit::Outdent | Edit::Undo | Edit::Redo => (),
         }
+
+        self.buffer.borrow_mut().end_edit(self.text_location);
+    }
+
+    pub fn load(&mut self, file_name: &str) -> Result<(), std::io::Error> {
+        self.buffer.borrow_mut().load(file_name)
+    }
+
+    pub fn load_text(&mut self, text: &str) {
+        self.buffer.borrow_mut().load_text(text);
+    }
+
+    /// Shared with the other views of the buffer
+    pub fn is_read_only(&self) -> bool {
+        self.buffer.borrow().is_read_only()
     }
 
-    pub fn load(&mut self, file_name: &str) {
-        self.buffer.load(file_name);
+    pub fn set_read_only(&mut self, read_only: bool) {
+        self.buffer.borrow_mut().set_read_only(read_only);
     }
 
     pub fn set_buffer_file(&mut self, file_name: &str) {
-        self.buffer.set_file(file_name);
+        self.buffer.borrow_mut().set_file(file_name);
     }
 
+    /// Position on screen, text starts right after the gutter
     pub fn caret_position(&self) -> CaretPosition {
-        self.text_location_to_position()
-            .saturating_sub(self.scroll_offset)
+        let position = if self.soft_wrap {
+            self.wrapped_caret_position()
+        } else {
+            self.text_location_to_position()
+                .saturating_sub(self.scroll_offset)
+        };
+
+        CaretPosition {
+            column: position.column.saturating_add(self.gutter_width()),
+            row: position.row,
+        }
+    }
+
+    /// Returns whether soft wrap is on after the toggle
+    pub fn toggle_soft_wrap(&mut self) -> bool {
+        self.set_soft_wrap(!self.soft_wrap);
+        self.soft_wrap
+    }
+
+    pub fn set_soft_wrap(&mut self, soft_wrap: bool) {
+        self.soft_wrap = soft_wrap;
+        self.scroll_offset.column = 0;
+        self.top_wrap_row = 0;
+        self.scroll_text_location_into_view();
+        self.mark_redraw(true);
+    }
+
+    /// Cycles through off, absolute and relative, returns the new setting
+    pub fn cycle_line_numbers(&mut self) -> LineNumbers {
+        self.set_line_numbers(self.gutter.line_numbers.next());
+        self.gutter.line_numbers
+    }
+
+    pub fn set_line_numbers(&mut self, line_numbers: LineNumbers) {
+        self.gutter.line_numbers = line_numbers;
+        self.scroll_text_location_into_view();
+        self.mark_redraw(true);
+    }
+
+    /// Wins over the configured indentation until the view is closed
+    pub fn set_indentation(&mut self, indentation: Indentation) {
+        self.indentation_settings.local = Some(indentation);
+    }
+
+    /// Signs shown in the gutter next to given lines, replacing the previous ones
+    pub fn set_diagnostics(&mut self, diagnostics: HashMap<LineIdx, Sign>) {
+        self.gutter.set_diagnostics(diagnostics);
+        self.mark_redraw(true);
     }
 
     pub fn resize(&mut self, new_size: Size) {
@@ -99,97 +365,218 @@ impl View {
     }
 
     pub fn get_status(&self) -> DocumentStatus {
-        let file_type = if let Some(file_name) = self.buffer.get_file_name() {
-            file_name.into()
-        } else {
-            FileType::None
-        };
+        let file_type = self.file_type().unwrap_or_default();
 
         DocumentStatus {
-            caret_position: self.caret_position(),
-            file_name: self.buffer.get_file_name(),
-            number_of_lines: self.buffer.get_number_of_lines(),
-            is_modified: self.buffer.is_modified(),
+            caret_position: self.text_location_to_position(),
+            file_name: self.buffer.borrow().get_file_name(),
+            number_of_lines: self.buffer.borrow().get_number_of_lines(),
+            is_modified: self.buffer.borrow().is_modified(),
+            is_read_only: self.buffer.borrow().is_read_only(),
             file_type,
+            file_format: self.buffer.borrow().get_file_format(),
+            ..DocumentStatus::default()
         }
     }
 
     // ============================================ RENDERING =====================================================
 
-    fn draw_rows(&mut self) -> Result<(), std::io::Error> {
-        let _ = Terminal::move_caret_to(CaretPosition { column: 0, row: 0 });
+    fn draw_rows(&mut self, origin: CaretPosition) -> Result<(), std::io::Error> {
         for row in 0..self.size.rows {
-            Terminal::print_row(row, "~")?;
+            Terminal::print_at(Self::row_origin(origin, row), self.size.columns, "~")?;
         }
-        let _ = Terminal::move_caret_to(CaretPosition { column: 0, row: 0 });
         Ok(())
     }
 
-    fn draw_buffer(&self, origin_y: usize) -> Result<(), std::io::Error> {
-        let (width, height) = (self.size.columns, self.size.rows);
+    /// Start of a row of the view on the screen
+    fn row_origin(origin: CaretPosition, row: usize) -> CaretPosition {
+        CaretPosition {
+            column: origin.column,
+            row: origin.row.saturating_add(row),
+        }
+    }
+
+    fn draw_buffer(&mut self, origin: CaretPosition) -> Result<(), std::io::Error> {
+        let (width, height) = (self.text_width(), self.size.rows);
 
         if width == 0 || height == 0 {
             return Ok(());
         }
 
-        let end_y = origin_y.saturating_add(height);
         let top = self.scroll_offset.row;
 
         let selected_match = self.search_info.is_some().then_some(self.text_location);
         let query = self
             .search_info
             .as_ref()
-            .map(|x| x.search_query.to_string());
+            .and_then(|x| x.search_query.clone());
 
-        let file_name = self.buffer.get_file_name();
-        let file_type: Option<FileType> = if let Some(file_name) = file_name {
-            Some(file_name.into())
-        } else {
-            None
-        };
-        let mut highlighter = Highlighter::new(query, selected_match, file_type);
+        let selection = self.selection();
+
+        self.highlighter
+            .set_overlays(query, selected_match, selection);
+
+        let visible_end = cmp::min(
+            top.saturating_add(height),
+            self.buffer.borrow().get_number_of_lines(),
+        );
+        self.highlight_syntax_until(visible_end);
 
-        // It has to be 0 here because of comment blocks
-        for current_row in 0..self.buffer.get_number_of_lines() {
-            if let Some(line) = self.buffer.get_line(current_row) {
-                highlighter.highlight(current_row, line);
+        for line_idx in top..visible_end {
+            if let Some(line) = self.buffer.borrow().get_line(line_idx) {
+                self.highlighter.highlight_overlays(line_idx, line);
             }
         }
 
-        // This here can start from origin no problems
-        for current_row in origin_y..end_y {
-            let line_idx = current_row.saturating_add(top).saturating_sub(origin_y);
+        if self.soft_wrap {
+            return self.draw_wrapped_lines(origin);
+        }
+
+        for current_row in 0..height {
+            let line_idx = current_row.saturating_add(top);
             let left = self.scroll_offset.column;
             let right = self.scroll_offset.column.saturating_add(width);
 
-            if let Some(annotated_string) =
+            if let Some(mut annotated_string) =
                 self.buffer
-                    .get_highlighted_line(line_idx, left..right, &highlighter)
+                    .borrow()
+                    .get_highlighted_line(line_idx, left..right, &self.highlighter)
             {
-                Terminal::print_annoted_line(current_row, annotated_string)?;
+                annotated_string.prepend(self.render_gutter(Some(line_idx)));
+                Terminal::print_annotated_at(
+                    Self::row_origin(origin, current_row),
+                    self.size.columns,
+                    &annotated_string,
+                )?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Brings syntax highlighting of every line before `end` up to date
+    fn highlight_syntax_until(&mut self, end: LineIdx) {
+        self.highlighter.set_file_type(self.file_type());
+        let first_changed_line = self.buffer.borrow().first_changed_since(self.seen_revision);
+        if let Some(line_idx) = first_changed_line {
+            self.highlighter.invalidate_from(line_idx);
+        }
+        self.seen_revision = self.buffer.borrow().revision();
+
+        // Syntax state flows down from the top of the file, so every line above is needed,
+        // but only lines changed since the last call are scanned again
+        for line_idx in self.highlighter.first_stale_line()..end {
+            if let Some(line) = self.buffer.borrow().get_line(line_idx) {
+                self.highlighter.highlight_syntax(line_idx, line);
+            }
+        }
+    }
+
+    /// Gutter of a row showing given line, `None` for continuation rows of a wrapped line
+    fn render_gutter(&self, line_idx: Option<LineIdx>) -> AnnotatedString {
+        let sign = line_idx.and_then(|line_idx| self.sign_for(line_idx));
+
+        self.gutter.render(
+            line_idx,
+            self.text_location.line_idx,
+            self.buffer.borrow().get_number_of_lines(),
+            sign,
+        )
+    }
+
+    /// Diagnostics come first, then search hits and finally unsaved changes
+    fn sign_for(&self, line_idx: LineIdx) -> Option<Sign> {
+        let query = self
+            .search_info
+            .as_ref()
+            .and_then(|search_info| search_info.search_query.as_ref());
+        let has_search_hit = || {
+            query.is_some_and(|pattern| {
+                self.buffer
+                    .borrow()
+                    .get_line(line_idx)
+                    .is_some_and(|line| !line.find_all(pattern).is_empty())
+            })
+        };
+
+        self.gutter
+            .diagnostic(line_idx)
+            .or_else(|| has_search_hit().then_some(Sign::SEARCH_HIT))
+            .or_else(|| {
+                self.buffer
+                    .borrow()
+                    .is_line_modified(line_idx)
+                    .then_some(Sign::MODIFIED)
+            })
+    }
+
+    /// Every line from the top one is printed as many rows as it wraps into, the top one from its top row on
+    fn draw_wrapped_lines(&self, origin: CaretPosition) -> Result<(), std::io::Error> {
+        let width = self.text_width();
+        let top_wrap_row = self.top_wrap_row();
+        let mut current_row = 0;
+        let buffer = self.buffer.borrow();
+
+        for line_idx in self.scroll_offset.row..buffer.get_number_of_lines() {
+            let Some(line) = buffer.get_line(line_idx) else {
+                break;
+            };
+            let starts = line.wrap_starts(width);
+            let skipped = if line_idx == self.scroll_offset.row {
+                top_wrap_row
+            } else {
+                0
+            };
+
+            for (row, start) in starts.iter().enumerate().skip(skipped) {
+                if current_row >= self.size.rows {
+                    return Ok(());
+                }
+
+                let left = line.width_until(*start);
+                let right = starts
+                    .get(row.saturating_add(1))
+                    .map_or(left.saturating_add(width), |end| line.width_until(*end));
+
+                if let Some(mut annotated_string) =
+                    buffer.get_highlighted_line(line_idx, left..right, &self.highlighter)
+                {
+                    let gutter_line = (row == 0).then_some(line_idx);
+                    annotated_string.prepend(self.render_gutter(gutter_line));
+                    Terminal::print_annotated_at(
+                        Self::row_origin(origin, current_row),
+                        self.size.columns,
+                        &annotated_string,
+                    )?;
+                }
+                current_row = current_row.saturating_add(1);
             }
         }
+
         Ok(())
     }
 
-    fn draw_welcome_message(&self) -> Result<(), std::io::Error> {
-        // File and no welcome
-        if self.is_file_given() {
+    fn draw_welcome_message(&self, origin: CaretPosition) -> Result<(), std::io::Error> {
+        // File or text and no welcome
+        if self.is_file_given() || self.buffer.borrow().get_number_of_lines() > 0 {
             return Ok(());
         }
 
-        let welcome_message_buffer =
-            Message::build_welcome_message(self.size, EDITOR_NAME, EDITOR_VERSION);
+        let welcome_message_buffer = Message::build_welcome_message(
+            self.size,
+            EDITOR_NAME,
+            EDITOR_VERSION,
+            &self.recent_files,
+        );
 
-        let mut start_render_line = self.size.rows / 3;
+        let start_render_line = self.size.rows / 3;
 
-        for line in welcome_message_buffer.data {
+        for (row, line) in (start_render_line..self.size.rows).zip(welcome_message_buffer.data) {
             // Cut off is here if someone wants to build different welcome message they dont have to worry about it fitting perfectly
-            Terminal::print_row(
-                start_render_line,
+            Terminal::print_at(
+                Self::row_origin(origin, row),
+                self.size.columns,
                 &line.get_visable_graphemes(0..self.size.columns),
             )?;
-            start_render_line += 1;
         }
 
         Ok(())
@@ -199,8 +586,17 @@ impl View {
 
     fn move_text_location(&mut self, direction: Move) {
         let Size { rows, .. } = self.size;
+        let line_idx = self.text_location.line_idx;
 
         match direction {
+            Move::Up | Move::PageUp | Move::Down | Move::PageDown if self.soft_wrap => {
+                let step = match direction {
+                    Move::Up | Move::Down => 1,
+                    _ => rows.saturating_sub(1),
+                };
+                let upwards = matches!(direction, Move::Up | Move::PageUp);
+                self.move_by_visual_rows(step, upwards);
+            }
             Move::Up => self.move_up(1),
             Move::Down => self.move_down(1),
             Move::Left => self.move_left(),
@@ -209,6 +605,20 @@ impl View {
             Move::PageDown => self.move_down(rows.saturating_sub(1)),
             Move::Home => self.move_to_start_line(),
             Move::End => self.move_to_end_line(),
+            Move::WordLeft => self.move_word_left(),
+            Move::WordRight => self.move_word_right(),
+            Move::DocumentStart => self.text_location = Location::default(),
+            Move::DocumentEnd => self.text_location = self.end_of_document(),
+            Move::MatchingBracket => {
+                if let Some(location) = self.matching_bracket() {
+                    self.text_location = location;
+                }
+            }
+        }
+
+        // Line numbers are drawn relative to the caret line or highlight it
+        if self.gutter_width() > 0 && line_idx != self.text_location.line_idx {
+            self.mark_redraw(true);
         }
 
         self.scroll_text_location_into_view();
@@ -217,14 +627,18 @@ impl View {
     fn add_to_buffer(&mut self, chr: char) {
         let old_len = self
             .buffer
+            .borrow()
             .data
             .get(self.text_location.line_idx)
             .map_or(0, Line::grapheme_count);
 
-        self.buffer.add_character_at(chr, self.text_location);
+        self.buffer
+            .borrow_mut()
+            .add_character_at(chr, self.text_location);
 
         let new_len = self
             .buffer
+            .borrow()
             .data
             .get(self.text_location.line_idx)
             .map_or(0, Line::grapheme_count);
@@ -247,76 +661,595 @@ impl View {
     }
 
     fn delete_grapheme(&mut self) {
-        self.buffer.delete_character_at(self.text_location);
+        self.buffer
+            .borrow_mut()
+            .delete_character_at(self.text_location);
         self.mark_redraw(true);
     }
 
+    fn delete_word(&mut self) {
+        let start = self.text_location;
+        self.move_word_right();
+        self.buffer
+            .borrow_mut()
+            .delete_range(start, self.text_location);
+        self.jump_to(start);
+    }
+
+    fn backspace_word(&mut self) {
+        let end = self.text_location;
+        self.move_word_left();
+        self.buffer
+            .borrow_mut()
+            .delete_range(self.text_location, end);
+        self.jump_to(self.text_location);
+    }
+
+    /// New line keeps the indentation of the current one, one level more after an opening bracket
+    ///
+    /// Closing bracket right after the caret goes to its own line at the original indentation
     fn enter(&mut self) {
-        self.buffer.insert_newline(self.text_location);
-        self.move_text_location(Move::Down);
-        self.move_to_start_line();
+        let Location {
+            line_idx,
+            grapheme_idx,
+        } = self.text_location;
+
+        let (indentation, opens, closes, blanks_after) =
+            self.buffer.borrow().get_line(line_idx).map_or_else(
+                || (String::new(), false, false, 0),
+                |line| {
+                    let before = line.get_graphemes(0..grapheme_idx);
+                    let after = line.get_graphemes(grapheme_idx..line.grapheme_count());
+
+                    (
+                        leading_whitespace(&before).to_string(),
+                        before.trim_end().ends_with(['{', '(', '[']),
+                        after.trim_start().starts_with(['}', ')', ']']),
+                        leading_whitespace(&after).len(),
+                    )
+                },
+            );
+
+        if indentation.is_empty() && !opens {
+            self.buffer.borrow_mut().insert_newline(self.text_location);
+            self.move_text_location(Move::Down);
+            self.move_to_start_line();
+            self.mark_redraw(true);
+            return;
+        }
+
+        // The rest of the line gets the new indentation instead of adding its own blanks to it
+        self.buffer.borrow_mut().delete_range(
+            self.text_location,
+            Location {
+                line_idx,
+                grapheme_idx: grapheme_idx.saturating_add(blanks_after),
+            },
+        );
+
+        let mut text = format!("\n{indentation}");
+        if opens {
+            text.push_str(&self.indentation().unit());
+        }
+
+        let caret = self
+            .buffer
+            .borrow_mut()
+            .insert_text_at(&text, self.text_location);
+        if opens && closes {
+            self.buffer
+                .borrow_mut()
+                .insert_text_at(&format!("\n{indentation}"), caret);
+        }
+
+        self.text_location = caret;
+        self.scroll_text_location_into_view();
         self.mark_redraw(true);
     }
 
+    /// Closing brace typed as the first thing on a line takes one level of indentation away
+    fn input(&mut self, chr: char) {
+        let Location {
+            line_idx,
+            grapheme_idx,
+        } = self.text_location;
+
+        let only_indentation_before = self.buffer.borrow().get_line(line_idx).is_some_and(|line| {
+            let before = line.get_graphemes(0..grapheme_idx);
+            !before.is_empty() && before.trim().is_empty()
+        });
+
+        if chr == '}' && only_indentation_before {
+            let removed = self.outdent_line(line_idx);
+            self.text_location.grapheme_idx = grapheme_idx.saturating_sub(removed);
+        }
+
+        self.add_to_buffer(chr);
+    }
+
     fn tab(&mut self) {
-        self.add_to_buffer('\t');
+        let column = self
+            .buffer
+            .borrow()
+            .get_line(self.text_location.line_idx)
+            .map_or(0, |line| line.width_until(self.text_location.grapheme_idx));
+        let text = self.indentation().tab_text(column);
+
+        self.text_location = self
+            .buffer
+            .borrow_mut()
+            .insert_text_at(&text, self.text_location);
+        self.scroll_text_location_into_view();
+        self.mark_redraw(true);
     }
 
-    fn save(&mut self) -> Result<(), std::io::Error> {
-        self.buffer.save()
+    /// Adds or removes one level of indentation on every line in range
+    ///
+    /// With a selection whole lines end up selected, otherwise the caret stays on the same text
+    fn change_indentation(&mut self, lines: RangeInclusive<LineIdx>, indent: bool) {
+        let caret_line_idx = self.text_location.line_idx;
+        let mut caret_shift = 0;
+
+        for line_idx in lines.clone() {
+            let changed = if indent {
+                self.indent_line(line_idx)
+            } else {
+                self.outdent_line(line_idx)
+            };
+
+            if line_idx == caret_line_idx {
+                caret_shift = changed;
+            }
+        }
+
+        if self.selection_anchor.is_some() {
+            let last_line_idx = *lines.end();
+
+            self.selection_anchor = Some(Location {
+                line_idx: *lines.start(),
+                grapheme_idx: 0,
+            });
+            self.text_location = Location {
+                line_idx: last_line_idx,
+                grapheme_idx: self
+                    .buffer
+                    .borrow()
+                    .get_line(last_line_idx)
+                    .map_or(0, Line::grapheme_count),
+            };
+        } else if indent {
+            self.text_location.grapheme_idx =
+                self.text_location.grapheme_idx.saturating_add(caret_shift);
+        } else {
+            self.text_location.grapheme_idx =
+                self.text_location.grapheme_idx.saturating_sub(caret_shift);
+        }
+
+        self.scroll_text_location_into_view();
+        self.mark_redraw(true);
     }
 
-    // ======================================= SEARCH =================================================================
+    /// Empty lines are left alone, returns number of graphemes added
+    fn indent_line(&mut self, line_idx: LineIdx) -> usize {
+        if self
+            .buffer
+            .borrow()
+            .get_line(line_idx)
+            .is_none_or(|line| line.is_empty())
+        {
+            return 0;
+        }
 
-    pub fn search_previous(&mut self, search_string: &str) {
-        let before_location = Location {
-            grapheme_idx: self.text_location.grapheme_idx.saturating_sub(1),
-            line_idx: self.text_location.line_idx,
+        let unit = self.indentation().unit();
+        let at = Location {
+            line_idx,
+            grapheme_idx: 0,
+        };
+        self.buffer
+            .borrow_mut()
+            .insert_text_at(&unit, at)
+            .grapheme_idx
+    }
+
+    /// Returns number of graphemes removed
+    fn outdent_line(&mut self, line_idx: LineIdx) -> usize {
+        let Some(removed) = self
+            .buffer
+            .borrow()
+            .get_line(line_idx)
+            .map(|line| self.indentation().outdent_len(leading_whitespace(line)))
+        else {
+            return 0;
         };
 
-        self.reverse_query(search_string, before_location);
+        self.buffer.borrow_mut().delete_range(
+            Location {
+                line_idx,
+                grapheme_idx: 0,
+            },
+            Location {
+                line_idx,
+                grapheme_idx: removed,
+            },
+        );
+
+        removed
+    }
+
+    fn indentation(&self) -> Indentation {
+        self.indentation_settings.for_file_type(self.file_type())
+    }
+
+    fn save(&mut self, keep_backup: bool) -> Result<(), std::io::Error> {
+        self.buffer.borrow_mut().save(keep_backup)
     }
 
-    pub fn search_next(&mut self, search_string: &str) {
-        self.query(
-            search_string,
+    fn undo(&mut self) {
+        self.clear_selection();
+        let location = self.buffer.borrow_mut().undo();
+        if let Some(location) = location {
+            self.jump_to(location);
+        }
+    }
+
+    fn redo(&mut self) {
+        self.clear_selection();
+        let location = self.buffer.borrow_mut().redo();
+        if let Some(location) = location {
+            self.jump_to(location);
+        }
+    }
+
+    /// Location is clamped to the buffer, caret ends up in the middle of the view
+    pub fn go_to(&mut self, location: Location) {
+        let last_line_idx = self.buffer.borrow().get_number_of_lines().saturating_sub(1);
+
+        self.clear_selection();
+        self.jump_to(Location {
+            line_idx: cmp::min(location.line_idx, last_line_idx),
+            ..location
+        });
+        self.center_text_location();
+    }
+
+    fn jump_to(&mut self, location: Location) {
+        self.text_location = location;
+        self.snap_to_valid_line();
+        self.snap_to_valid_grapheme();
+        self.scroll_text_location_into_view();
+        self.mark_redraw(true);
+    }
+
+    // ======================================= SELECTION ==============================================================
+
+    /// Selected range ordered from start to end, `None` when nothing is selected
+    pub fn selection(&self) -> Option<Range<Location>> {
+        let anchor = self.selection_anchor?;
+
+        match anchor.cmp(&self.text_location) {
+            Ordering::Less => Some(anchor..self.text_location),
+            Ordering::Greater => Some(self.text_location..anchor),
+            Ordering::Equal => None,
+        }
+    }
+
+    pub fn selected_text(&self) -> Option<String> {
+        self.selection().map(|selection| {
             self.buffer
-                .next_valid_search_location(self.text_location, search_string),
+                .borrow()
+                .get_text(selection.start, selection.end)
+        })
+    }
+
+    /// Removes selected text and returns it
+    pub fn cut_selection(&mut self) -> Option<String> {
+        let text = self.selected_text()?;
+
+        self.buffer
+            .borrow_mut()
+            .begin_edit(self.text_location, GroupKind::Other);
+        self.delete_selection();
+        self.buffer.borrow_mut().end_edit(self.text_location);
+
+        Some(text)
+    }
+
+    /// Inserts text at the caret replacing current selection
+    pub fn paste(&mut self, text: &str) {
+        self.buffer
+            .borrow_mut()
+            .begin_edit(self.text_location, GroupKind::Other);
+        self.delete_selection();
+        self.text_location = self
+            .buffer
+            .borrow_mut()
+            .insert_text_at(text, self.text_location);
+        self.buffer.borrow_mut().end_edit(self.text_location);
+
+        self.scroll_text_location_into_view();
+        self.mark_redraw(true);
+    }
+
+    /// Lines of a selection spanning more than one line, a selection ending at the start of a line leaves it out
+    fn selected_lines(&self) -> Option<RangeInclusive<LineIdx>> {
+        let Range { start, end } = self.selection()?;
+
+        if start.line_idx == end.line_idx {
+            return None;
+        }
+
+        let last_line_idx = if end.grapheme_idx == 0 {
+            end.line_idx.saturating_sub(1)
+        } else {
+            end.line_idx
+        };
+        let last_line_idx = cmp::min(
+            last_line_idx,
+            self.buffer.borrow().get_number_of_lines().saturating_sub(1),
         );
+
+        Some(start.line_idx..=last_line_idx)
     }
 
-    pub fn search(&mut self, search_string: &str) {
-        // This is a place for optimisation in case of Search
-        self.search_info
-            .as_mut()
-            .map(|x| x.search_query = Line::from(search_string));
+    fn delete_selection(&mut self) -> bool {
+        let Some(selection) = self.selection() else {
+            self.selection_anchor = None;
+            return false;
+        };
 
-        self.query(search_string, self.text_location);
+        self.buffer
+            .borrow_mut()
+            .delete_range(selection.start, selection.end);
+        self.selection_anchor = None;
+        self.text_location = selection.start;
+        self.scroll_text_location_into_view();
+        self.mark_redraw(true);
+
+        true
     }
 
-    fn query(&mut self, search_string: &str, start_location: Location) {
-        if search_string.is_empty() {
-            return;
+    // ============================================ SWAP FILE =====================================================
+
+    pub fn write_swap(&mut self) -> Result<(), std::io::Error> {
+        self.buffer.borrow_mut().write_swap()
+    }
+
+    pub fn remove_swap(&self) {
+        self.buffer.borrow().remove_swap();
+    }
+
+    pub fn get_swap_content(&self) -> Option<String> {
+        self.buffer.borrow().get_swap_content()
+    }
+
+    /// Replaces the whole buffer with recovered text as a single undoable edit
+    pub fn recover(&mut self, text: &str) {
+        let start = Location::default();
+        let last_line_idx = self.buffer.borrow().get_number_of_lines().saturating_sub(1);
+        let end = Location {
+            line_idx: last_line_idx,
+            grapheme_idx: self
+                .buffer
+                .borrow()
+                .get_line(last_line_idx)
+                .map_or(0, Line::grapheme_count),
+        };
+
+        self.clear_selection();
+        self.buffer
+            .borrow_mut()
+            .begin_edit(self.text_location, GroupKind::Other);
+        self.buffer.borrow_mut().delete_range(start, end);
+        self.buffer.borrow_mut().insert_text_at(text, start);
+        self.buffer.borrow_mut().end_edit(start);
+
+        self.jump_to(start);
+    }
+
+    // ============================================ FILE FORMAT ===================================================
+
+    pub fn get_file_format(&self) -> FileFormat {
+        self.buffer.borrow().get_file_format()
+    }
+
+    /// Conversion is a single undoable edit which marks the buffer as modified
+    pub fn set_file_format(&mut self, file_format: FileFormat) {
+        self.buffer
+            .borrow_mut()
+            .begin_edit(self.text_location, GroupKind::Other);
+        self.buffer.borrow_mut().set_file_format(file_format);
+        self.buffer.borrow_mut().end_edit(self.text_location);
+    }
+
+    pub fn clear_selection(&mut self) {
+        if self.selection_anchor.take().is_some() {
+            self.mark_redraw(true);
         }
+    }
 
-        let result = self.buffer.forward_find(search_string, start_location);
+    fn select_all(&mut self) {
+        self.selection_anchor = Some(Location::default());
+        self.text_location = self.end_of_document();
+        self.scroll_text_location_into_view();
+    }
 
-        if let Some(found) = result {
-            self.text_location = found;
-            self.scroll_text_location_into_view();
-            self.center_text_location();
+    // ======================================= BRACKETS ===============================================================
+
+    /// Bracket pair the grapheme is part of and whether it opens the pair
+    fn bracket_pair(grapheme: &str) -> Option<(&'static str, &'static str, bool)> {
+        const PAIRS: [(&str, &str); 3] = [("(", ")"), ("[", "]"), ("{", "}")];
+
+        PAIRS.iter().find_map(|(open, close)| match grapheme {
+            _ if grapheme == *open => Some((*open, *close, true)),
+            _ if grapheme == *close => Some((*open, *close, false)),
+            _ => None,
+        })
+    }
+
+    /// Bracket matching the one under the caret, or right before it, brackets in comments and literals are skipped
+    fn matching_bracket(&mut self) -> Option<Location> {
+        let Location {
+            line_idx,
+            grapheme_idx,
+        } = self.text_location;
+
+        let (start, (open, close, opens)) = std::iter::once(grapheme_idx)
+            .chain(grapheme_idx.checked_sub(1))
+            .find_map(|idx| {
+                let pair = Self::bracket_pair(
+                    self.buffer.borrow().get_line(line_idx)?.get_grapheme(idx)?,
+                )?;
+                Some((idx, pair))
+            })?;
+
+        self.highlight_syntax_until(line_idx.saturating_add(1));
+        if self.is_in_literal(line_idx, start) {
+            return None;
+        }
+
+        let (same, other) = if opens { (open, close) } else { (close, open) };
+        let mut depth: usize = 0;
+        let mut location = Location {
+            line_idx,
+            grapheme_idx: start,
+        };
+
+        while let Some(next) = self.step_for_bracket(location, opens) {
+            location = next;
+
+            let grapheme = self
+                .buffer
+                .borrow()
+                .get_line(location.line_idx)
+                .and_then(|line| line.get_grapheme(location.grapheme_idx))
+                .and_then(|grapheme| {
+                    [same, other]
+                        .into_iter()
+                        .find(|bracket| *bracket == grapheme)
+                });
+
+            let is_bracket = grapheme.is_some();
+            if !is_bracket || self.is_in_literal(location.line_idx, location.grapheme_idx) {
+                continue;
+            }
+
+            if grapheme == Some(same) {
+                depth = depth.saturating_add(1);
+            } else if depth == 0 {
+                return Some(location);
+            } else {
+                depth = depth.saturating_sub(1);
+            }
         }
+
+        None
     }
 
-    fn reverse_query(&mut self, search_string: &str, start_location: Location) {
-        if search_string.is_empty() {
-            return;
+    /// Next grapheme in given direction, syntax highlighting is extended as lines below are reached
+    fn step_for_bracket(&mut self, location: Location, forward: bool) -> Option<Location> {
+        let Location {
+            line_idx,
+            grapheme_idx,
+        } = location;
+
+        if forward {
+            let line_len = self.buffer.borrow().get_line(line_idx)?.grapheme_count();
+            if grapheme_idx.saturating_add(1) < line_len {
+                return Some(Location {
+                    line_idx,
+                    grapheme_idx: grapheme_idx.saturating_add(1),
+                });
+            }
+
+            let next_line_idx = (line_idx.saturating_add(1)
+                ..self.buffer.borrow().get_number_of_lines())
+                .find(|idx| {
+                    self.buffer
+                        .borrow()
+                        .get_line(*idx)
+                        .is_some_and(|line| line.grapheme_count() > 0)
+                })?;
+            self.highlight_syntax_until(next_line_idx.saturating_add(1));
+
+            return Some(Location {
+                line_idx: next_line_idx,
+                grapheme_idx: 0,
+            });
+        }
+
+        if let Some(previous) = grapheme_idx.checked_sub(1) {
+            return Some(Location {
+                line_idx,
+                grapheme_idx: previous,
+            });
         }
 
-        let result = self.buffer.backward_find(search_string, start_location);
+        let previous_line_idx = (0..line_idx).rev().find(|idx| {
+            self.buffer
+                .borrow()
+                .get_line(*idx)
+                .is_some_and(|line| line.grapheme_count() > 0)
+        })?;
+        let line_len = self
+            .buffer
+            .borrow()
+            .get_line(previous_line_idx)?
+            .grapheme_count();
+
+        Some(Location {
+            line_idx: previous_line_idx,
+            grapheme_idx: line_len.saturating_sub(1),
+        })
+    }
+
+    fn is_in_literal(&self, line_idx: LineIdx, grapheme_idx: GraphemeIdx) -> bool {
+        self.buffer.borrow().get_line(line_idx).is_some_and(|line| {
+            self.highlighter
+                .is_in_literal(line_idx, line.grapheme_idx_to_byte_idx(grapheme_idx))
+        })
+    }
+
+    // ======================================= SEARCH =================================================================
+
+    pub fn search_previous(&mut self, pattern: &SearchPattern) {
+        let result = self
+            .buffer
+            .borrow()
+            .backward_find(pattern, self.text_location);
+        self.jump_to_match(result);
+    }
 
+    pub fn search_next(&mut self, pattern: &SearchPattern) {
+        let after_location = Location {
+            grapheme_idx: self.text_location.grapheme_idx.saturating_add(1),
+            line_idx: self.text_location.line_idx,
+        };
+
+        let result = self.buffer.borrow().forward_find(pattern, after_location);
+        self.jump_to_match(result);
+    }
+
+    /// Incremental search, `None` clears current query
+    pub fn search(&mut self, pattern: Option<&SearchPattern>) {
+        // This is a place for optimisation in case of Search
+        if let Some(search_info) = self.search_info.as_mut() {
+            search_info.search_query = pattern.cloned();
+        }
+
+        if let Some(pattern) = pattern {
+            let result = self
+                .buffer
+                .borrow()
+                .forward_find(pattern, self.text_location);
+            self.jump_to_match(result);
+        }
+
+        self.mark_redraw(true);
+    }
+
+    fn jump_to_match(&mut self, result: Option<Range<Location>>) {
         if let Some(found) = result {
-            self.text_location = found;
+            self.text_location = found.start;
             self.scroll_text_location_into_view();
             self.center_text_location();
         }
@@ -325,12 +1258,24 @@ impl View {
     pub fn enter_search(&mut self) {
         self.search_info = Some(SearchInfo {
             prev_location: self.text_location,
-            search_query: Line::from(""),
+            search_query: None,
         });
     }
 
     fn center_text_location(&mut self) {
-        let Size { columns, rows } = self.size;
+        let columns = self.text_width();
+
+        if self.soft_wrap {
+            let (caret_row, _) = self.caret_visual_row();
+            let (top, top_wrap_row) =
+                self.wrapped_top_fitting(caret_row, self.size.rows.div_ceil(2), 0);
+            self.scroll_offset.row = top;
+            self.top_wrap_row = top_wrap_row;
+            self.needs_redraw = true;
+            return;
+        }
+
+        let rows = self.size.rows;
         let CaretPosition { column, row } = self.text_location_to_position();
 
         let vertical_middle = rows.div_ceil(2);
@@ -353,9 +1298,175 @@ impl View {
 
     pub fn exit_search(&mut self) {
         self.search_info = None;
+        self.mark_redraw(true);
+    }
+
+    // ======================================= REPLACE ================================================================
+
+    /// Replaces the match under the caret, caret ends up right after the replacement
+    pub fn replace_current(&mut self, pattern: &SearchPattern, replacement: &str) -> bool {
+        let Some(found) = self
+            .buffer
+            .borrow()
+            .find_all_after(pattern, self.text_location)
+            .into_iter()
+            .next()
+            .filter(|found| found.start == self.text_location)
+        else {
+            return false;
+        };
+
+        self.buffer
+            .borrow_mut()
+            .begin_edit(self.text_location, GroupKind::Other);
+        self.replace_at(pattern, replacement, found);
+        self.buffer.borrow_mut().end_edit(self.text_location);
+
+        self.scroll_text_location_into_view();
+        self.mark_redraw(true);
+        true
     }
+
+    /// Replaces every match in the buffer as a single edit
+    pub fn replace_all(&mut self, pattern: &SearchPattern, replacement: &str) -> usize {
+        let matches = self
+            .buffer
+            .borrow()
+            .find_all_after(pattern, Location::default());
+        self.replace_matches(pattern, replacement, &matches)
+    }
+
+    /// Replaces every match from the caret until the end of the buffer as a single edit
+    pub fn replace_remaining(&mut self, pattern: &SearchPattern, replacement: &str) -> usize {
+        let matches = self
+            .buffer
+            .borrow()
+            .find_all_after(pattern, self.text_location);
+        self.replace_matches(pattern, replacement, &matches)
+    }
+
+    /// Replaces matches on given lines as a single edit, only the first one of every line unless `every_match`
+    pub fn substitute(
+        &mut self,
+        pattern: &SearchPattern,
+        replacement: &str,
+        lines: RangeInclusive<LineIdx>,
+        every_match: bool,
+    ) -> usize {
+        let from = Location {
+            line_idx: *lines.start(),
+            grapheme_idx: 0,
+        };
+
+        let mut matches = self.buffer.borrow().find_all_after(pattern, from);
+        matches.retain(|found| lines.contains(&found.start.line_idx));
+        if !every_match {
+            matches.dedup_by_key(|found| found.start.line_idx);
+        }
+
+        self.replace_matches(pattern, replacement, &matches)
+    }
+
+    pub fn set_recent_files(&mut self, recent_files: Vec<String>) {
+        self.recent_files = recent_files;
+        self.mark_redraw(true);
+    }
+
+    pub fn caret_line_idx(&self) -> LineIdx {
+        self.text_location.line_idx
+    }
+
+    pub fn location(&self) -> Location {
+        self.text_location
+    }
+
+    /// Changes with every edit of the buffer, also one made through another view
+    pub fn revision(&self) -> usize {
+        self.buffer.borrow().revision()
+    }
+
+    #[cfg(test)]
+    pub fn get_content(&self) -> String {
+        self.buffer.borrow().get_content()
+    }
+
+    fn replace_matches(
+        &mut self,
+        pattern: &SearchPattern,
+        replacement: &str,
+        matches: &[Range<Location>],
+    ) -> usize {
+        if matches.is_empty() {
+            return 0;
+        }
+
+        let caret = self.text_location;
+        self.buffer.borrow_mut().begin_edit(caret, GroupKind::Other);
+
+        // Going backwards keeps locations of the remaining matches valid
+        for found in matches.iter().rev() {
+            self.replace_at(pattern, replacement, found.clone());
+        }
+
+        self.text_location = caret;
+        self.snap_to_valid_line();
+        self.snap_to_valid_grapheme();
+        self.buffer.borrow_mut().end_edit(self.text_location);
+
+        self.scroll_text_location_into_view();
+        self.mark_redraw(true);
+        matches.len()
+    }
+
+    /// Moves the caret onto the next match without looping around the buffer
+    pub fn next_match(&mut self, pattern: &SearchPattern, skip_current: bool) -> bool {
+        let from = if skip_current {
+            Location {
+                line_idx: self.text_location.line_idx,
+                grapheme_idx: self.text_location.grapheme_idx.saturating_add(1),
+            }
+        } else {
+            self.text_location
+        };
+
+        let found = self
+            .buffer
+            .borrow()
+            .find_all_after(pattern, from)
+            .into_iter()
+            .next();
+        let has_match = found.is_some();
+        self.jump_to_match(found);
+
+        has_match
+    }
+
+    fn replace_at(&mut self, pattern: &SearchPattern, replacement: &str, found: Range<Location>) {
+        let replacement = self
+            .buffer
+            .borrow()
+            .get_line(found.start.line_idx)
+            .map(|line| {
+                let match_start = line.grapheme_idx_to_byte_idx(found.start.grapheme_idx);
+                pattern.replacement_for(line, match_start, replacement)
+            })
+            .unwrap_or_default();
+
+        self.buffer
+            .borrow_mut()
+            .delete_range(found.start, found.end);
+        self.text_location = self
+            .buffer
+            .borrow_mut()
+            .insert_text_at(&replacement, found.start);
+    }
+
     // =========================================== SCROLLING ===================================================
     fn scroll_text_location_into_view(&mut self) {
+        if self.soft_wrap {
+            return self.scroll_wrapped_into_view();
+        }
+
         let CaretPosition { column, row } = self.text_location_to_position();
 
         self.scroll_vertical(row);
@@ -363,7 +1474,7 @@ impl View {
     }
 
     fn scroll_horizontal(&mut self, to: usize) {
-        let Size { columns, .. } = self.size;
+        let columns = self.text_width();
 
         let offset_changed = if to < self.scroll_offset.column {
             self.scroll_offset.column = to;
@@ -377,7 +1488,7 @@ impl View {
 
         if offset_changed {
             self.mark_redraw(offset_changed);
-        };
+        }
     }
 
     fn scroll_vertical(&mut self, to: usize) {
@@ -395,7 +1506,135 @@ impl View {
 
         if offset_changed {
             self.mark_redraw(offset_changed);
+        }
+    }
+
+    /// Moves the view without following the caret, the caret is pulled along only when it would leave the view
+    fn scroll_lines(&mut self, step: usize, upwards: bool) {
+        let last_line_idx = self.buffer.borrow().get_number_of_lines().saturating_sub(1);
+        self.scroll_offset.row = if upwards {
+            self.scroll_offset.row.saturating_sub(step)
+        } else {
+            cmp::min(self.scroll_offset.row.saturating_add(step), last_line_idx)
         };
+        self.top_wrap_row = 0;
+
+        let top = self.scroll_offset.row;
+        if self.text_location.line_idx < top {
+            self.text_location.line_idx = top;
+        }
+        while self.text_location.line_idx > top && self.caret_position().row >= self.size.rows {
+            self.text_location.line_idx = self.text_location.line_idx.saturating_sub(1);
+        }
+        self.snap_to_valid_grapheme();
+    }
+
+    /// Location shown at a position on the screen, clamped to the text of the view
+    fn location_at(&self, position: CaretPosition) -> Location {
+        let row = cmp::min(position.row, self.size.rows.saturating_sub(1));
+        let column = position.column.saturating_sub(self.gutter_width());
+
+        let (line_idx, row_start, row_end) = if self.soft_wrap {
+            self.wrapped_row_at(row)
+        } else {
+            (self.scroll_offset.row.saturating_add(row), 0, None)
+        };
+        let line_idx = cmp::min(line_idx, self.buffer.borrow().get_number_of_lines());
+
+        let grapheme_idx = self.buffer.borrow().get_line(line_idx).map_or(0, |line| {
+            let left = if self.soft_wrap {
+                line.width_until(row_start)
+            } else {
+                self.scroll_offset.column
+            };
+            let grapheme_idx = line.grapheme_idx_at(left.saturating_add(column));
+
+            // Clicking past the end of a wrapped row puts the caret at its end, not on the next row
+            row_end.map_or(grapheme_idx, |end: GraphemeIdx| {
+                cmp::min(grapheme_idx, end.saturating_sub(1))
+            })
+        });
+
+        Location {
+            line_idx,
+            grapheme_idx,
+        }
+    }
+
+    /// Line shown on given visual row with soft wrap, with the grapheme the row starts at and the start of the next row
+    fn wrapped_row_at(&self, row: usize) -> (LineIdx, GraphemeIdx, Option<GraphemeIdx>) {
+        // Counted from the start of the top line, rows above the top row are out of view
+        let row = row.saturating_add(self.top_wrap_row());
+        let mut rows_above: usize = 0;
+
+        for line_idx in self.scroll_offset.row..self.buffer.borrow().get_number_of_lines() {
+            let starts = self.visual_rows(line_idx);
+            let line_row = row.saturating_sub(rows_above);
+
+            if line_row < starts.len() {
+                return (
+                    line_idx,
+                    starts.get(line_row).copied().unwrap_or(0),
+                    starts.get(line_row.saturating_add(1)).copied(),
+                );
+            }
+            rows_above = rows_above.saturating_add(starts.len());
+        }
+
+        (self.buffer.borrow().get_number_of_lines(), 0, None)
+    }
+
+    /// Top moves only as far as needed for the caret to fit, whole lines are shown as long as the caret line allows it
+    fn scroll_wrapped_into_view(&mut self) {
+        let rows = self.size.rows;
+        let (caret_row, _) = self.caret_visual_row();
+        let caret_line_idx = self.text_location.line_idx;
+        let top = (self.scroll_offset.row, self.top_wrap_row());
+
+        let (top_line_idx, top_wrap_row) = if (caret_line_idx, caret_row) < top {
+            let row = if caret_row < rows { 0 } else { caret_row };
+            (caret_line_idx, row)
+        } else if self.wrapped_caret_position().row < rows {
+            top
+        } else {
+            self.wrapped_top_fitting(caret_row, rows, self.scroll_offset.row)
+        };
+
+        if top_line_idx != self.scroll_offset.row
+            || top_wrap_row != self.top_wrap_row
+            || self.scroll_offset.column != 0
+        {
+            self.scroll_offset = CaretPosition {
+                column: 0,
+                row: top_line_idx,
+            };
+            self.top_wrap_row = top_wrap_row;
+            self.mark_redraw(true);
+        }
+    }
+
+    /// Top line and its top row, not above `lowest_top`, from which the caret's visual row is at most `rows` rows down
+    ///
+    /// Lines start at their first row unless the caret line alone is taller than `rows`
+    fn wrapped_top_fitting(
+        &self,
+        caret_row: usize,
+        rows: usize,
+        lowest_top: LineIdx,
+    ) -> (LineIdx, usize) {
+        let mut used_rows = caret_row.saturating_add(1);
+        let mut top = self.text_location.line_idx;
+
+        while top > lowest_top {
+            let above = self.visual_rows(top.saturating_sub(1)).len();
+            if used_rows.saturating_add(above) > rows {
+                break;
+            }
+            used_rows = used_rows.saturating_add(above);
+            top = top.saturating_sub(1);
+        }
+
+        (top, used_rows.saturating_sub(rows.max(1)).min(caret_row))
     }
 
     // =========================================== HELPERS =====================================================
@@ -405,6 +1644,7 @@ impl View {
     fn snap_to_valid_grapheme(&mut self) {
         self.text_location.grapheme_idx = self
             .buffer
+            .borrow()
             .data
             .get(self.text_location.line_idx)
             .map_or(0, |line| {
@@ -415,7 +1655,7 @@ impl View {
     fn snap_to_valid_line(&mut self) {
         self.text_location.line_idx = cmp::min(
             self.text_location.line_idx,
-            self.buffer.get_number_of_lines(),
+            self.buffer.borrow().get_number_of_lines(),
         );
     }
 
@@ -432,6 +1672,55 @@ impl View {
         self.snap_to_valid_line();
     }
 
+    /// Keeps the visual column of the caret, clamped to the end of the row it lands on
+    fn move_by_visual_rows(&mut self, step: usize, upwards: bool) {
+        let (mut row, row_start) = self.caret_visual_row();
+        let mut line_idx = self.text_location.line_idx;
+        let column = self.buffer.borrow().get_line(line_idx).map_or(0, |line| {
+            line.width_until(self.text_location.grapheme_idx)
+                .saturating_sub(line.width_until(row_start))
+        });
+
+        for _ in 0..step {
+            if upwards {
+                if row > 0 {
+                    row = row.saturating_sub(1);
+                } else if line_idx > 0 {
+                    line_idx = line_idx.saturating_sub(1);
+                    row = self.visual_rows(line_idx).len().saturating_sub(1);
+                } else {
+                    break;
+                }
+            } else if row.saturating_add(1) < self.visual_rows(line_idx).len() {
+                row = row.saturating_add(1);
+            } else if line_idx < self.buffer.borrow().get_number_of_lines() {
+                line_idx = line_idx.saturating_add(1);
+                row = 0;
+            } else {
+                break;
+            }
+        }
+
+        let starts = self.visual_rows(line_idx);
+        let grapheme_idx = self.buffer.borrow().get_line(line_idx).map_or(0, |line| {
+            let start = starts.get(row).copied().unwrap_or(0);
+            // Last grapheme of a row is as far as caret goes, the position after it is on the next row
+            let end = starts
+                .get(row.saturating_add(1))
+                .map_or(line.grapheme_count(), |next| next.saturating_sub(1));
+
+            cmp::min(
+                line.grapheme_idx_at(line.width_until(start).saturating_add(column)),
+                end,
+            )
+        });
+
+        self.text_location = Location {
+            line_idx,
+            grapheme_idx,
+        };
+    }
+
     fn move_left(&mut self) {
         if self.text_location.grapheme_idx > 0 {
             self.text_location.grapheme_idx -= 1;
@@ -444,6 +1733,7 @@ impl View {
     fn move_right(&mut self) {
         let line_width = self
             .buffer
+            .borrow()
             .data
             .get(self.text_location.line_idx)
             .map_or(0, Line::grapheme_count);
@@ -456,6 +1746,49 @@ impl View {
         }
     }
 
+    /// Stops at the end of the next word, lines without one are crossed one step at a time
+    fn move_word_right(&mut self) {
+        let word_end = self
+            .buffer
+            .borrow()
+            .get_line(self.text_location.line_idx)
+            .and_then(|line| line.next_word_end(self.text_location.grapheme_idx));
+
+        if let Some(word_end) = word_end {
+            self.text_location.grapheme_idx = word_end;
+            return;
+        }
+
+        let at_line_end = self
+            .buffer
+            .borrow()
+            .get_line(self.text_location.line_idx)
+            .is_none_or(|line| self.text_location.grapheme_idx >= line.grapheme_count());
+
+        if at_line_end {
+            self.move_right();
+        } else {
+            self.move_to_end_line();
+        }
+    }
+
+    /// Stops at the start of the previous word, lines without one are crossed one step at a time
+    fn move_word_left(&mut self) {
+        let word_start = self
+            .buffer
+            .borrow()
+            .get_line(self.text_location.line_idx)
+            .and_then(|line| line.previous_word_start(self.text_location.grapheme_idx));
+
+        if let Some(word_start) = word_start {
+            self.text_location.grapheme_idx = word_start;
+        } else if self.text_location.grapheme_idx > 0 {
+            self.move_to_start_line();
+        } else {
+            self.move_left();
+        }
+    }
+
     fn move_to_start_line(&mut self) {
         self.text_location.grapheme_idx = 0;
     }
@@ -463,6 +1796,7 @@ impl View {
     fn move_to_end_line(&mut self) {
         self.text_location.grapheme_idx = self
             .buffer
+            .borrow()
             .data
             .get(self.text_location.line_idx)
             .map_or(0, Line::grapheme_count);
@@ -470,10 +1804,94 @@ impl View {
 
     // ===================================== Additional Helpers ===================================================
 
+    fn file_type(&self) -> Option<FileType> {
+        self.buffer
+            .borrow()
+            .get_file_name()
+            .map(|file_name| FileType::from_file_name(&file_name))
+    }
+
+    fn end_of_document(&self) -> Location {
+        let last_line_idx = self.buffer.borrow().get_number_of_lines().saturating_sub(1);
+
+        Location {
+            line_idx: last_line_idx,
+            grapheme_idx: self
+                .buffer
+                .borrow()
+                .get_line(last_line_idx)
+                .map_or(0, Line::grapheme_count),
+        }
+    }
+
+    fn gutter_width(&self) -> usize {
+        self.gutter
+            .width(self.buffer.borrow().get_number_of_lines())
+    }
+
+    /// Columns left for the text next to the gutter
+    fn text_width(&self) -> usize {
+        self.size.columns.saturating_sub(self.gutter_width())
+    }
+
+    /// Grapheme index where each visual row of the line starts, the line after the last one has a single row
+    fn visual_rows(&self, line_idx: LineIdx) -> Vec<GraphemeIdx> {
+        self.buffer
+            .borrow()
+            .get_line(line_idx)
+            .map_or_else(|| vec![0], |line| line.wrap_starts(self.text_width()))
+    }
+
+    /// Visual row of the caret within its line and the grapheme that row starts at
+    fn caret_visual_row(&self) -> (usize, GraphemeIdx) {
+        let starts = self.visual_rows(self.text_location.line_idx);
+        let row = starts
+            .partition_point(|start| *start <= self.text_location.grapheme_idx)
+            .saturating_sub(1);
+
+        (row, starts.get(row).copied().unwrap_or(0))
+    }
+
+    /// With soft wrap rows are counted from the top row of the top line
+    fn wrapped_caret_position(&self) -> CaretPosition {
+        let rows_above: usize = (self.scroll_offset.row..self.text_location.line_idx)
+            .map(|line_idx| self.visual_rows(line_idx).len())
+            .sum::<usize>()
+            .saturating_sub(self.top_wrap_row());
+        let (row, row_start) = self.caret_visual_row();
+        let column = self
+            .buffer
+            .borrow()
+            .get_line(self.text_location.line_idx)
+            .map_or(0, |line| {
+                line.width_until(self.text_location.grapheme_idx)
+                    .saturating_sub(line.width_until(row_start))
+            });
+
+        let row = if self.text_location.line_idx == self.scroll_offset.row {
+            row.saturating_sub(self.top_wrap_row())
+        } else {
+            rows_above.saturating_add(row)
+        };
+
+        CaretPosition { column, row }
+    }
+
+    /// Top row kept within the top line, which may have become shorter since
+    fn top_wrap_row(&self) -> usize {
+        cmp::min(
+            self.top_wrap_row,
+            self.visual_rows(self.scroll_offset.row)
+                .len()
+                .saturating_sub(1),
+        )
+    }
+
     fn text_location_to_position(&self) -> CaretPosition {
         let row = self.text_location.line_idx;
         let col = self
             .buffer
+            .borrow()
             .data
             .get(row)
             .map_or(0, |line| line.width_until(self.text_location.grapheme_idx));
@@ -487,9 +1905,9 @@ impl UiComponent for View {
         self.needs_redraw = needs_redraw;
     }
 
-    /// Get status of redraw
+    /// Get status of redraw, edits made through another view of the buffer count as well
     fn needs_redraw(&self) -> bool {
-        self.needs_redraw
+        self.needs_redraw || self.buffer.borrow().revision() != self.seen_revision
     }
 
     /// Set the size of the component
@@ -498,10 +1916,10 @@ impl UiComponent for View {
     }
 
     /// Method to actually draw the component, must be implemented by each component
-    fn draw(&mut self, origin_y: usize) -> Result<(), std::io::Error> {
-        self.draw_rows()?;
-        self.draw_buffer(origin_y)?;
-        self.draw_welcome_message()?;
+    fn draw(&mut self, origin: CaretPosition) -> Result<(), std::io::Error> {
+        self.draw_rows(origin)?;
+        self.draw_buffer(origin)?;
+        self.draw_welcome_message(origin)?;
         Ok(())
     }
 }
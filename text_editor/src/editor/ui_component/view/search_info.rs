@@ -1,9 +1,9 @@
-use crate::editor::line::Line;
+use crate::editor::search_pattern::SearchPattern;
 
 use super::location::Location;
 
 #[derive(Clone, Default)]
 pub struct SearchInfo {
     pub prev_location: Location,
-    pub search_query: Line,
+    pub search_query: Option<SearchPattern>,
 }
@@ -1,7 +1,30 @@
 #![warn(clippy::all, clippy::pedantic)]
 use editor::Editor;
+use editor::cli::{self, Action};
 mod editor;
 
 fn main() {
-    Editor::new().unwrap().run();
+    let args = match cli::parse(std::env::args().skip(1)) {
+        Ok(Action::Edit(args)) => args,
+        Ok(Action::Help) => {
+            print!("{}", cli::USAGE);
+            return;
+        }
+        Ok(Action::Version) => {
+            println!("editr {}", env!("CARGO_PKG_VERSION"));
+            return;
+        }
+        Err(err) => {
+            eprintln!("editr: {err}\nTry 'editr --help' for more information.");
+            std::process::exit(2);
+        }
+    };
+
+    match Editor::new(&args) {
+        Ok(mut editor) => editor.run(),
+        Err(err) => {
+            eprintln!("editr: could not start the editor: {err}");
+            std::process::exit(1);
+        }
+    }
 }
@@ -0,0 +1,7 @@
+fn main() {
+    let text = "}";
+    if true {
+        // a stray ) and }
+        call('{', (1, [2]));
+    }
+}